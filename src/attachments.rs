@@ -0,0 +1,232 @@
+//! Embedded files
+//!
+//! Files can be attached to a PDF at document level, through the
+//! `/Names /EmbeddedFiles` name tree (used by PDF portfolios and ZUGFeRD /
+//! Factur-X e-invoices), or on a page through `/FileAttachment` annotations.
+
+use crate::extractor::{collect_name_tree, decode_text_string, resolve_dict};
+use crate::PdfError;
+use lopdf::{Document, Object, ObjectId};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// A file embedded in a PDF
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    /// File name (/UF or /F of the file specification, else the name tree key)
+    pub name: String,
+    /// MIME type from the embedded file's /Subtype, e.g. `application/pdf`
+    pub mime_type: Option<String>,
+    /// Size of the decompressed file in bytes
+    pub size: usize,
+    /// Description (/Desc), or the annotation's /Contents for page attachments
+    pub description: Option<String>,
+    /// Page of the `/FileAttachment` annotation; `None` for document-level files
+    pub page: Option<u32>,
+    /// Decompressed file contents
+    pub data: Vec<u8>,
+}
+
+impl Attachment {
+    /// Whether the attachment is itself a PDF
+    pub fn is_pdf(&self) -> bool {
+        self.mime_type.as_deref() == Some("application/pdf") || self.data.starts_with(b"%PDF")
+    }
+}
+
+/// Collect the document's attachments: embedded files first, in name tree
+/// order, then file attachment annotations in page order. A file referenced
+/// from both places is reported once.
+pub fn attachments(doc: &Document) -> Vec<Attachment> {
+    let mut result = Vec::new();
+    let mut seen = HashSet::new();
+
+    let tree = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"Names").ok())
+        .and_then(|names| resolve_dict(doc, names))
+        .and_then(|names| names.get(b"EmbeddedFiles").ok())
+        .and_then(|tree| resolve_dict(doc, tree));
+    if let Some(tree) = tree {
+        let mut entries = HashMap::new();
+        collect_name_tree(doc, tree, &mut entries, 0);
+        let mut entries: Vec<_> = entries.into_iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        for (key, file_spec) in entries {
+            let fallback = String::from_utf8_lossy(&key).to_string();
+            if let Some(attachment) = read_file_spec(doc, file_spec, fallback, &mut seen) {
+                result.push(attachment);
+            }
+        }
+    }
+
+    for (page_num, page_id) in doc.get_pages() {
+        let Ok(annotations) = doc.get_page_annotations(page_id) else {
+            continue;
+        };
+        for annot in annotations {
+            if annot.get(b"Subtype").and_then(Object::as_name).ok() != Some(b"FileAttachment") {
+                continue;
+            }
+            let Ok(file_spec) = annot.get(b"FS") else {
+                continue;
+            };
+            if let Some(mut attachment) = read_file_spec(doc, file_spec, String::new(), &mut seen) {
+                attachment.page = Some(page_num);
+                if attachment.description.is_none() {
+                    attachment.description = annot
+                        .get(b"Contents")
+                        .ok()
+                        .and_then(|o| decode_text_string(doc, o))
+                        .filter(|s| !s.is_empty());
+                }
+                result.push(attachment);
+            }
+        }
+    }
+
+    result
+}
+
+/// Read the attachments of a PDF file
+pub fn extract_attachments<P: AsRef<Path>>(path: P) -> Result<Vec<Attachment>, PdfError> {
+    let doc = Document::load(path)?;
+    Ok(attachments(&doc))
+}
+
+/// Read the attachments of a PDF in memory
+pub fn extract_attachments_mem(buffer: &[u8]) -> Result<Vec<Attachment>, PdfError> {
+    let doc = Document::load_mem(buffer)?;
+    Ok(attachments(&doc))
+}
+
+/// Read an embedded file through its file specification (PDF 32000-1, 7.11.4),
+/// skipping files already in `seen`
+fn read_file_spec(
+    doc: &Document,
+    file_spec: &Object,
+    fallback_name: String,
+    seen: &mut HashSet<ObjectId>,
+) -> Option<Attachment> {
+    let spec = resolve_dict(doc, file_spec)?;
+    let embedded = spec.get(b"EF").ok().and_then(|o| resolve_dict(doc, o))?;
+    let stream_id = embedded
+        .get(b"UF")
+        .or_else(|_| embedded.get(b"F"))
+        .and_then(Object::as_reference)
+        .ok()?;
+    if !seen.insert(stream_id) {
+        return None;
+    }
+    let stream = doc.get_object(stream_id).ok()?.as_stream().ok()?;
+    // Uncompressed streams have no filter to decode
+    let data = if stream.dict.has(b"Filter") {
+        stream.decompressed_content().ok()?
+    } else {
+        stream.content.clone()
+    };
+
+    let name = [&b"UF"[..], b"F"]
+        .iter()
+        .find_map(|key| spec.get(key).ok().and_then(|o| decode_text_string(doc, o)))
+        .filter(|name| !name.is_empty())
+        .unwrap_or(fallback_name);
+    let mime_type = stream
+        .dict
+        .get(b"Subtype")
+        .and_then(Object::as_name)
+        .ok()
+        .map(|subtype| String::from_utf8_lossy(subtype).to_string());
+    let description = spec
+        .get(b"Desc")
+        .ok()
+        .and_then(|o| decode_text_string(doc, o))
+        .filter(|s| !s.is_empty());
+
+    Some(Attachment {
+        name,
+        mime_type,
+        size: data.len(),
+        description,
+        page: None,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractor::tests::add_single_page;
+    use lopdf::{dictionary, Stream};
+
+    #[test]
+    fn test_embedded_files_and_file_attachment_annotations() {
+        let mut doc = Document::with_version("1.7");
+        let invoice = doc.add_object(Stream::new(
+            dictionary! { "Type" => "EmbeddedFile", "Subtype" => "text/xml" },
+            b"<Invoice/>".to_vec(),
+        ));
+        let report = doc.add_object(Stream::new(
+            dictionary! { "Type" => "EmbeddedFile" },
+            b"%PDF-1.4 ...".to_vec(),
+        ));
+        let invoice_spec = doc.add_object(dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal("factur-x.xml"),
+            "Desc" => Object::string_literal("Invoice data"),
+            "EF" => dictionary! { "F" => invoice },
+        });
+        add_single_page(
+            &mut doc,
+            b"",
+            dictionary! {
+                "Annots" => vec![
+                    dictionary! {
+                        "Type" => "Annot",
+                        "Subtype" => "FileAttachment",
+                        "Contents" => Object::string_literal("Full report"),
+                        "FS" => dictionary! {
+                            "Type" => "Filespec",
+                            "UF" => Object::string_literal("report.pdf"),
+                            "EF" => dictionary! { "F" => report },
+                        },
+                    }
+                    .into(),
+                    // The same file as the name tree entry is only reported once
+                    dictionary! {
+                        "Type" => "Annot",
+                        "Subtype" => "FileAttachment",
+                        "FS" => invoice_spec,
+                    }
+                    .into(),
+                ],
+            },
+        );
+        doc.catalog_mut().unwrap().set(
+            "Names",
+            dictionary! {
+                "EmbeddedFiles" => dictionary! {
+                    "Names" => vec![Object::string_literal("invoice"), invoice_spec.into()],
+                },
+            },
+        );
+
+        let found = attachments(&doc);
+        assert_eq!(found.len(), 2);
+
+        assert_eq!(found[0].name, "factur-x.xml");
+        assert_eq!(found[0].mime_type.as_deref(), Some("text/xml"));
+        assert_eq!(found[0].size, 10);
+        assert_eq!(found[0].description.as_deref(), Some("Invoice data"));
+        assert_eq!(found[0].page, None);
+        assert_eq!(found[0].data, b"<Invoice/>");
+        assert!(!found[0].is_pdf());
+
+        assert_eq!(found[1].name, "report.pdf");
+        assert_eq!(found[1].mime_type, None);
+        assert_eq!(found[1].description.as_deref(), Some("Full report"));
+        assert_eq!(found[1].page, Some(1));
+        assert!(found[1].is_pdf());
+    }
+}
@@ -1,8 +1,12 @@
 //! CLI tool for PDF to Markdown conversion
 
-use pdf_inspector::{process_pdf, PdfType};
+use pdf_inspector::table_export::json_string;
+use pdf_inspector::{
+    process_pdf_with_options, tables_to_json, ExtractedTable, PdfType, ProcessOptions,
+};
 use std::env;
 use std::fs;
+use std::path::Path;
 use std::process;
 
 fn main() {
@@ -19,6 +23,8 @@ fn main() {
         eprintln!("Options:");
         eprintln!("  --json    Output result as JSON");
         eprintln!("  --raw     Output only markdown (no headers)");
+        eprintln!("  --attachments  Append the markdown of attached PDFs");
+        eprintln!("  --tables <file>  Also write the tables as JSON (one CSV per table if .csv)");
         process::exit(1);
     }
 
@@ -29,22 +35,42 @@ fn main() {
         .get(2)
         .filter(|a| !a.starts_with("--"))
         .map(|s| s.as_str());
+    let tables_file = args
+        .iter()
+        .position(|a| a == "--tables")
+        .and_then(|i| args.get(i + 1));
+    let options = ProcessOptions {
+        include_attachments: args.iter().any(|a| a == "--attachments"),
+        page_labels: json_output,
+        tables: tables_file.is_some(),
+        ..Default::default()
+    };
 
-    match process_pdf(pdf_path) {
+    match process_pdf_with_options(pdf_path, &options) {
         Ok(result) => {
+            if let Some(tables_file) = tables_file {
+                if let Err(e) = write_tables(&result.tables, tables_file) {
+                    eprintln!("Error: failed to write tables: {}", e);
+                    process::exit(1);
+                }
+            }
             if json_output {
-                let md_escaped = result
-                    .markdown
-                    .as_ref()
-                    .map(|m| {
-                        m.replace('\\', "\\\\")
-                            .replace('"', "\\\"")
-                            .replace('\n', "\\n")
-                    })
-                    .unwrap_or_default();
+                let md_escaped = json_string(result.markdown.as_deref().unwrap_or_default());
+                let labels = result
+                    .page_labels
+                    .iter()
+                    .map(|l| json_string(l))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let watermarks = result
+                    .watermarks
+                    .iter()
+                    .map(|w| json_string(&w.text))
+                    .collect::<Vec<_>>()
+                    .join(",");
 
                 println!(
-                    r#"{{"pdf_type":"{}","page_count":{},"has_text":{},"processing_time_ms":{},"markdown_length":{},"markdown":"{}"}}"#,
+                    r#"{{"pdf_type":"{}","page_count":{},"page_labels":[{}],"watermarks":[{}],"hidden_text_items":{},"has_text":{},"processing_time_ms":{},"markdown_length":{},"markdown":{}}}"#,
                     match result.pdf_type {
                         PdfType::TextBased => "text_based",
                         PdfType::Scanned => "scanned",
@@ -52,6 +78,9 @@ fn main() {
                         PdfType::Mixed => "mixed",
                     },
                     result.page_count,
+                    labels,
+                    watermarks,
+                    result.hidden_text.items.len(),
                     result.text.is_some(),
                     result.processing_time_ms,
                     result.markdown.as_ref().map(|m| m.len()).unwrap_or(0),
@@ -139,7 +168,7 @@ fn main() {
         }
         Err(e) => {
             if json_output {
-                println!(r#"{{"error":"{}"}}"#, e);
+                println!(r#"{{"error":{}}}"#, json_string(&e.to_string()));
             } else {
                 eprintln!("Error: {}", e);
             }
@@ -147,3 +176,18 @@ fn main() {
         }
     }
 }
+
+/// Write `tables` to `output` as JSON, or as numbered CSV files
+fn write_tables(tables: &[ExtractedTable], output: &str) -> std::io::Result<()> {
+    let output = Path::new(output);
+    if output.extension().is_some_and(|ext| ext == "csv") {
+        let stem = output.with_extension("");
+        for (i, table) in tables.iter().enumerate() {
+            fs::write(format!("{}-{}.csv", stem.display(), i + 1), table.to_csv())?;
+        }
+    } else {
+        fs::write(output, tables_to_json(tables))?;
+    }
+    eprintln!("{} table(s) written to: {}", tables.len(), output.display());
+    Ok(())
+}
@@ -0,0 +1,598 @@
+//! CCITT fax decoding
+//!
+//! Decoder for the `/CCITTFaxDecode` filter (ITU-T T.4 and T.6): Group 3
+//! one-dimensional (`K = 0`), mixed one- and two-dimensional (`K > 0`) and
+//! Group 4 (`K < 0`) encodings, producing 1 bit per pixel rows as PDF expects.
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+/// Decoding parameters (/DecodeParms of the CCITTFaxDecode filter)
+#[derive(Debug, Clone)]
+pub struct CcittParams {
+    /// Encoding scheme: negative for Group 4, 0 for Group 3 1D, positive for mixed
+    pub k: i64,
+    /// Width of the image in pixels
+    pub columns: usize,
+    /// Height of the image in rows, 0 if unknown
+    pub rows: usize,
+    /// Whether each encoded line starts on a byte boundary
+    pub encoded_byte_align: bool,
+    /// Whether 1 bits are black (otherwise 0 bits are black)
+    pub black_is_1: bool,
+}
+
+impl Default for CcittParams {
+    fn default() -> Self {
+        CcittParams {
+            k: 0,
+            columns: 1728,
+            rows: 0,
+            encoded_byte_align: false,
+            black_is_1: false,
+        }
+    }
+}
+
+/// White run-length codes (ITU-T T.4, tables 2 and 3), as (run length, bits)
+const WHITE_CODES: &[(u16, &str)] = &[
+    (0, "00110101"),
+    (1, "000111"),
+    (2, "0111"),
+    (3, "1000"),
+    (4, "1011"),
+    (5, "1100"),
+    (6, "1110"),
+    (7, "1111"),
+    (8, "10011"),
+    (9, "10100"),
+    (10, "00111"),
+    (11, "01000"),
+    (12, "001000"),
+    (13, "000011"),
+    (14, "110100"),
+    (15, "110101"),
+    (16, "101010"),
+    (17, "101011"),
+    (18, "0100111"),
+    (19, "0001100"),
+    (20, "0001000"),
+    (21, "0010111"),
+    (22, "0000011"),
+    (23, "0000100"),
+    (24, "0101000"),
+    (25, "0101011"),
+    (26, "0010011"),
+    (27, "0100100"),
+    (28, "0011000"),
+    (29, "00000010"),
+    (30, "00000011"),
+    (31, "00011010"),
+    (32, "00011011"),
+    (33, "00010010"),
+    (34, "00010011"),
+    (35, "00010100"),
+    (36, "00010101"),
+    (37, "00010110"),
+    (38, "00010111"),
+    (39, "00101000"),
+    (40, "00101001"),
+    (41, "00101010"),
+    (42, "00101011"),
+    (43, "00101100"),
+    (44, "00101101"),
+    (45, "00000100"),
+    (46, "00000101"),
+    (47, "00001010"),
+    (48, "00001011"),
+    (49, "01010010"),
+    (50, "01010011"),
+    (51, "01010100"),
+    (52, "01010101"),
+    (53, "00100100"),
+    (54, "00100101"),
+    (55, "01011000"),
+    (56, "01011001"),
+    (57, "01011010"),
+    (58, "01011011"),
+    (59, "01001010"),
+    (60, "01001011"),
+    (61, "00110010"),
+    (62, "00110011"),
+    (63, "00110100"),
+    (64, "11011"),
+    (128, "10010"),
+    (192, "010111"),
+    (256, "0110111"),
+    (320, "00110110"),
+    (384, "00110111"),
+    (448, "01100100"),
+    (512, "01100101"),
+    (576, "01101000"),
+    (640, "01100111"),
+    (704, "011001100"),
+    (768, "011001101"),
+    (832, "011010010"),
+    (896, "011010011"),
+    (960, "011010100"),
+    (1024, "011010101"),
+    (1088, "011010110"),
+    (1152, "011010111"),
+    (1216, "011011000"),
+    (1280, "011011001"),
+    (1344, "011011010"),
+    (1408, "011011011"),
+    (1472, "010011000"),
+    (1536, "010011001"),
+    (1600, "010011010"),
+    (1664, "011000"),
+    (1728, "010011011"),
+];
+
+/// Black run-length codes (ITU-T T.4, tables 2 and 3), as (run length, bits)
+const BLACK_CODES: &[(u16, &str)] = &[
+    (0, "0000110111"),
+    (1, "010"),
+    (2, "11"),
+    (3, "10"),
+    (4, "011"),
+    (5, "0011"),
+    (6, "0010"),
+    (7, "00011"),
+    (8, "000101"),
+    (9, "000100"),
+    (10, "0000100"),
+    (11, "0000101"),
+    (12, "0000111"),
+    (13, "00000100"),
+    (14, "00000111"),
+    (15, "000011000"),
+    (16, "0000010111"),
+    (17, "0000011000"),
+    (18, "0000001000"),
+    (19, "00001100111"),
+    (20, "00001101000"),
+    (21, "00001101100"),
+    (22, "00000110111"),
+    (23, "00000101000"),
+    (24, "00000010111"),
+    (25, "00000011000"),
+    (26, "000011001010"),
+    (27, "000011001011"),
+    (28, "000011001100"),
+    (29, "000011001101"),
+    (30, "000001101000"),
+    (31, "000001101001"),
+    (32, "000001101010"),
+    (33, "000001101011"),
+    (34, "000011010010"),
+    (35, "000011010011"),
+    (36, "000011010100"),
+    (37, "000011010101"),
+    (38, "000011010110"),
+    (39, "000011010111"),
+    (40, "000001101100"),
+    (41, "000001101101"),
+    (42, "000011011010"),
+    (43, "000011011011"),
+    (44, "000001010100"),
+    (45, "000001010101"),
+    (46, "000001010110"),
+    (47, "000001010111"),
+    (48, "000001100100"),
+    (49, "000001100101"),
+    (50, "000001010010"),
+    (51, "000001010011"),
+    (52, "000000100100"),
+    (53, "000000110111"),
+    (54, "000000111000"),
+    (55, "000000100111"),
+    (56, "000000101000"),
+    (57, "000001011000"),
+    (58, "000001011001"),
+    (59, "000000101011"),
+    (60, "000000101100"),
+    (61, "000001011010"),
+    (62, "000001100110"),
+    (63, "000001100111"),
+    (64, "0000001111"),
+    (128, "000011001000"),
+    (192, "000011001001"),
+    (256, "000001011011"),
+    (320, "000000110011"),
+    (384, "000000110100"),
+    (448, "000000110101"),
+    (512, "0000001101100"),
+    (576, "0000001101101"),
+    (640, "0000001001010"),
+    (704, "0000001001011"),
+    (768, "0000001001100"),
+    (832, "0000001001101"),
+    (896, "0000001110010"),
+    (960, "0000001110011"),
+    (1024, "0000001110100"),
+    (1088, "0000001110101"),
+    (1152, "0000001110110"),
+    (1216, "0000001110111"),
+    (1280, "0000001010010"),
+    (1344, "0000001010011"),
+    (1408, "0000001010100"),
+    (1472, "0000001010101"),
+    (1536, "0000001011010"),
+    (1600, "0000001011011"),
+    (1664, "0000001100100"),
+    (1728, "0000001100101"),
+];
+
+/// Extended make-up codes shared by both colors (ITU-T T.4, table 4)
+const EXTENDED_MAKEUP_CODES: &[(u16, &str)] = &[
+    (1792, "00000001000"),
+    (1856, "00000001100"),
+    (1920, "00000001101"),
+    (1984, "000000010010"),
+    (2048, "000000010011"),
+    (2112, "000000010100"),
+    (2176, "000000010101"),
+    (2240, "000000010110"),
+    (2304, "000000010111"),
+    (2368, "000000011100"),
+    (2432, "000000011101"),
+    (2496, "000000011110"),
+    (2560, "000000011111"),
+];
+
+/// Longest run-length code, in bits
+const MAX_CODE_BITS: u8 = 13;
+
+/// Run-length code lookup keyed by (code length, code value)
+type CodeTable = HashMap<(u8, u16), u16>;
+
+fn build_table(codes: &[(u16, &str)]) -> CodeTable {
+    codes
+        .iter()
+        .chain(EXTENDED_MAKEUP_CODES)
+        .map(|&(run, bits)| {
+            let value = u16::from_str_radix(bits, 2).expect("valid code");
+            ((bits.len() as u8, value), run)
+        })
+        .collect()
+}
+
+static WHITE_TABLE: Lazy<CodeTable> = Lazy::new(|| build_table(WHITE_CODES));
+static BLACK_TABLE: Lazy<CodeTable> = Lazy::new(|| build_table(BLACK_CODES));
+
+/// Two-dimensional coding modes (ITU-T T.4, table 5)
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Pass,
+    Horizontal,
+    /// Vertical mode with the offset of a1 from b1
+    Vertical(isize),
+    EndOfLine,
+}
+
+/// MSB-first bit reader over the encoded data
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn bit(&mut self) -> Option<u16> {
+        let byte = *self.data.get(self.pos / 8)?;
+        let bit = (byte >> (7 - self.pos % 8)) & 1;
+        self.pos += 1;
+        Some(bit as u16)
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.data.len() * 8
+    }
+
+    fn align(&mut self) {
+        self.pos = self.pos.div_ceil(8) * 8;
+    }
+
+    /// Consume an end-of-line code (11 or more zero bits then a one) if present
+    fn skip_eol(&mut self) -> bool {
+        let start = self.pos;
+        let mut zeros = 0;
+        loop {
+            match self.bit() {
+                Some(0) => zeros += 1,
+                Some(_) if zeros >= 11 => return true,
+                _ => break,
+            }
+        }
+        self.pos = start;
+        false
+    }
+
+    /// Total length of one run: make-up codes followed by a terminating code
+    fn run_length(&mut self, table: &CodeTable) -> Option<usize> {
+        let mut total = 0usize;
+        loop {
+            let mut value = 0u16;
+            let mut run = None;
+            for len in 1..=MAX_CODE_BITS {
+                value = (value << 1) | self.bit()?;
+                if let Some(&r) = table.get(&(len, value)) {
+                    run = Some(r);
+                    break;
+                }
+            }
+            let run = run? as usize;
+            total += run;
+            if run < 64 {
+                return Some(total);
+            }
+        }
+    }
+
+    fn mode(&mut self) -> Option<Mode> {
+        let mut value = 0u16;
+        for len in 1..=12 {
+            value = (value << 1) | self.bit()?;
+            let mode = match (len, value) {
+                (1, 0b1) => Mode::Vertical(0),
+                (3, 0b011) => Mode::Vertical(1),
+                (3, 0b010) => Mode::Vertical(-1),
+                (3, 0b001) => Mode::Horizontal,
+                (4, 0b0001) => Mode::Pass,
+                (6, 0b000011) => Mode::Vertical(2),
+                (6, 0b000010) => Mode::Vertical(-2),
+                (7, 0b0000011) => Mode::Vertical(3),
+                (7, 0b0000010) => Mode::Vertical(-3),
+                (12, 0b000000000001) => Mode::EndOfLine,
+                _ => continue,
+            };
+            return Some(mode);
+        }
+        None
+    }
+}
+
+/// Decode CCITT fax data into packed rows of 1 bit per pixel. Decoding stops at
+/// the end of data, at an end-of-block code or on a coding error; with a known
+/// row count, missing rows are filled with white. Returns `None` if the rows
+/// would take more than `max_bytes`.
+pub fn decode(data: &[u8], params: &CcittParams, max_bytes: usize) -> Option<Vec<u8>> {
+    let columns = params.columns.max(1);
+    let row_bytes = columns.div_ceil(8);
+    if row_bytes.checked_mul(params.rows.max(1))? > max_bytes {
+        return None;
+    }
+    let mut reader = BitReader { data, pos: 0 };
+    let mut output = Vec::new();
+    // Changing elements of the reference line; the line above the first is all white
+    let mut reference: Vec<usize> = Vec::new();
+    let mut rows = 0;
+
+    while params.rows == 0 || rows < params.rows {
+        if params.encoded_byte_align {
+            reader.align();
+        }
+        let mut two_dimensional = params.k < 0;
+        if params.k >= 0 {
+            while reader.skip_eol() {}
+            if params.k > 0 {
+                match reader.bit() {
+                    Some(tag) => two_dimensional = tag == 0,
+                    None => break,
+                }
+            }
+        }
+        if reader.at_end() {
+            break;
+        }
+
+        let line = if two_dimensional {
+            decode_2d_line(&mut reader, &reference, columns)
+        } else {
+            decode_1d_line(&mut reader, columns)
+        };
+        let Some(line) = line else {
+            break;
+        };
+        if output.len() + row_bytes > max_bytes {
+            return None;
+        }
+        output.extend(pack_line(&line, columns, params.black_is_1));
+        reference = line;
+        rows += 1;
+    }
+
+    if params.rows > rows {
+        let white = if params.black_is_1 { 0x00 } else { 0xFF };
+        output.resize(params.rows * row_bytes, white);
+    }
+    Some(output)
+}
+
+/// Decode a line of alternating white and black runs (modified Huffman)
+fn decode_1d_line(reader: &mut BitReader, columns: usize) -> Option<Vec<usize>> {
+    let mut changes = Vec::new();
+    let mut position = 0;
+    let mut black = false;
+    while position < columns {
+        let table = if black { &*BLACK_TABLE } else { &*WHITE_TABLE };
+        position = (position + reader.run_length(table)?).min(columns);
+        changes.push(position);
+        black = !black;
+    }
+    Some(changes)
+}
+
+/// Decode a line coded relative to the reference line (modified READ)
+fn decode_2d_line(
+    reader: &mut BitReader,
+    reference: &[usize],
+    columns: usize,
+) -> Option<Vec<usize>> {
+    let mut changes: Vec<usize> = Vec::new();
+    // a0 starts on an imaginary white element just before the line
+    let mut a0: isize = -1;
+    let mut black = false;
+
+    while a0 < columns as isize {
+        // b1: first change on the reference line right of a0 with the opposite
+        // color to a0; changes at even indices turn black, odd ones turn white
+        let parity = usize::from(black);
+        let b1_index = (0..reference.len())
+            .find(|&i| i % 2 == parity && reference[i] as isize > a0)
+            .unwrap_or(reference.len());
+        let b1 = reference.get(b1_index).copied().unwrap_or(columns);
+        let b2 = reference.get(b1_index + 1).copied().unwrap_or(columns);
+
+        match reader.mode()? {
+            Mode::Pass => a0 = b2 as isize,
+            Mode::Horizontal => {
+                let start = a0.max(0) as usize;
+                let (first, second) = if black {
+                    (&*BLACK_TABLE, &*WHITE_TABLE)
+                } else {
+                    (&*WHITE_TABLE, &*BLACK_TABLE)
+                };
+                let a1 = (start + reader.run_length(first)?).min(columns);
+                let a2 = (a1 + reader.run_length(second)?).min(columns);
+                changes.push(a1);
+                changes.push(a2);
+                a0 = a2 as isize;
+            }
+            Mode::Vertical(offset) => {
+                let a1 = (b1 as isize + offset).clamp(0, columns as isize);
+                if a1 < a0 {
+                    return None;
+                }
+                changes.push(a1 as usize);
+                a0 = a1;
+                black = !black;
+            }
+            Mode::EndOfLine => return None,
+        }
+    }
+    Some(changes)
+}
+
+/// Pack a line given by its changing elements into bytes
+fn pack_line(changes: &[usize], columns: usize, black_is_1: bool) -> Vec<u8> {
+    let mut row = vec![0u8; columns.div_ceil(8)];
+    let mut black = false;
+    let mut start = 0;
+    for &end in changes.iter().chain(std::iter::once(&columns)) {
+        let end = end.min(columns);
+        if black == black_is_1 {
+            for x in start..end {
+                row[x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+        start = start.max(end);
+        black = !black;
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Append a bit string such as "0011" to a bit vector
+    fn push_bits(bits: &mut Vec<bool>, code: &str) {
+        bits.extend(code.chars().map(|c| c == '1'));
+    }
+
+    fn to_bytes(bits: &[bool]) -> Vec<u8> {
+        bits.chunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0u8, |byte, (i, &b)| byte | (u8::from(b) << (7 - i)))
+            })
+            .collect()
+    }
+
+    fn code(table: &[(u16, &'static str)], run: u16) -> &'static str {
+        table
+            .iter()
+            .chain(EXTENDED_MAKEUP_CODES)
+            .find(|(r, _)| *r == run)
+            .map(|(_, bits)| *bits)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_code_tables_are_prefix_free() {
+        for table in [WHITE_CODES, BLACK_CODES] {
+            let codes: Vec<&str> = table
+                .iter()
+                .chain(EXTENDED_MAKEUP_CODES)
+                .map(|(_, bits)| *bits)
+                .collect();
+            for (i, a) in codes.iter().enumerate() {
+                for (j, b) in codes.iter().enumerate() {
+                    assert!(i == j || !b.starts_with(a), "{} is a prefix of {}", a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_group3_and_group4_lines() {
+        // Two 16-pixel lines: 4 white, 8 black, 4 white; then 6 white, 10 black
+        let expected = vec![0b1111_0000, 0b0000_1111, 0b1111_1100, 0b0000_0000];
+
+        let mut g3 = Vec::new();
+        for runs in [[4, 8, 4].as_slice(), [6, 10].as_slice()] {
+            push_bits(&mut g3, "000000000001");
+            for (i, &run) in runs.iter().enumerate() {
+                let table = if i % 2 == 0 { WHITE_CODES } else { BLACK_CODES };
+                push_bits(&mut g3, code(table, run));
+            }
+        }
+        let params = CcittParams {
+            columns: 16,
+            rows: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            decode(&to_bytes(&g3), &params, usize::MAX),
+            Some(expected.clone())
+        );
+
+        // Group 4. First line: horizontal mode (4 white, 8 black), then V0 up to
+        // the end of the line. Second line against changes 4, 12, 16:
+        // VR2 (4 + 2), VL3 (12 - 3), V0 (16), then end-of-block
+        let mut g4 = Vec::new();
+        push_bits(&mut g4, "001");
+        push_bits(&mut g4, code(WHITE_CODES, 4));
+        push_bits(&mut g4, code(BLACK_CODES, 8));
+        push_bits(&mut g4, "1");
+        push_bits(&mut g4, "000011");
+        push_bits(&mut g4, "0000010");
+        push_bits(&mut g4, "1");
+        push_bits(&mut g4, "000000000001000000000001");
+        let params = CcittParams {
+            k: -1,
+            columns: 16,
+            ..Default::default()
+        };
+        let decoded = decode(&to_bytes(&g4), &params, usize::MAX).unwrap();
+        assert_eq!(decoded[..2], expected[..2]);
+        // Second line: white 0..6, black 6..9, white 9..16
+        assert_eq!(decoded[2..], [0b1111_1100, 0b0111_1111]);
+
+        // One bit per V0-coded line, but 128 MiB per decoded row
+        let wide = CcittParams {
+            columns: 1 << 30,
+            ..params
+        };
+        assert_eq!(decode(&[0xFF; 16], &wide, 1 << 20), None);
+        let tall = CcittParams {
+            rows: 60_000,
+            columns: 60_000,
+            ..Default::default()
+        };
+        assert_eq!(decode(&[], &tall, 1 << 20), None);
+    }
+}
@@ -2,7 +2,16 @@
 //!
 //! This module extracts text with position information for structure detection.
 
+use crate::font_metrics::StandardFont;
 use crate::glyph_names::glyph_to_char;
+use crate::graphics::{path_graphics, GraphicItem, PathElement, PathPaint};
+use crate::hidden_text::{
+    luminance, stands_out_from, HiddenReason, MIN_VISIBLE_FONT_SIZE, MIN_VISIBLE_OPACITY,
+    WHITE_LUMINANCE,
+};
+use crate::images::{ColorSpace, ImageInfo};
+use crate::optional_content::{LayerSelection, OptionalContent};
+use crate::page_labels::{labels_from_ranges, page_label_ranges};
 use crate::tounicode::FontCMaps;
 use crate::PdfError;
 use lopdf::{Document, Object, ObjectId};
@@ -37,7 +46,7 @@ struct FontWidthInfo {
 type PageFontWidths = HashMap<String, FontWidthInfo>;
 
 /// Resolve a PDF object reference to an array
-fn resolve_array<'a>(doc: &'a Document, obj: &'a Object) -> Option<&'a Vec<Object>> {
+pub(crate) fn resolve_array<'a>(doc: &'a Document, obj: &'a Object) -> Option<&'a Vec<Object>> {
     match obj {
         Object::Array(arr) => Some(arr),
         Object::Reference(r) => {
@@ -52,7 +61,10 @@ fn resolve_array<'a>(doc: &'a Document, obj: &'a Object) -> Option<&'a Vec<Objec
 }
 
 /// Resolve a PDF object reference to a dictionary
-fn resolve_dict<'a>(doc: &'a Document, obj: &'a Object) -> Option<&'a lopdf::Dictionary> {
+pub(crate) fn resolve_dict<'a>(
+    doc: &'a Document,
+    obj: &'a Object,
+) -> Option<&'a lopdf::Dictionary> {
     match obj {
         Object::Dictionary(d) => Some(d),
         Object::Reference(r) => doc.get_dictionary(*r).ok(),
@@ -60,6 +72,14 @@ fn resolve_dict<'a>(doc: &'a Document, obj: &'a Object) -> Option<&'a lopdf::Dic
     }
 }
 
+/// Resolve a PDF object reference, leaving direct objects as they are
+pub(crate) fn resolve_object<'a>(doc: &'a Document, obj: &'a Object) -> &'a Object {
+    match obj {
+        Object::Reference(r) => doc.get_object(*r).unwrap_or(obj),
+        other => other,
+    }
+}
+
 /// Build font width info for all fonts on a page
 fn build_font_widths(
     doc: &Document,
@@ -85,11 +105,50 @@ fn parse_font_widths(doc: &Document, font_dict: &lopdf::Dictionary) -> Option<Fo
 
     match subtype_name {
         b"Type0" => parse_type0_widths(doc, font_dict),
-        b"Type1" | b"TrueType" | b"MMType1" | b"Type3" => parse_simple_font_widths(doc, font_dict),
+        b"Type1" | b"TrueType" | b"MMType1" => parse_simple_font_widths(doc, font_dict)
+            .or_else(|| parse_standard_font_widths(doc, font_dict)),
+        b"Type3" => parse_simple_font_widths(doc, font_dict),
         _ => None,
     }
 }
 
+/// Widths for non-embedded standard 14 fonts (and aliases like Arial) that
+/// carry no Widths array, using the bundled AFM metrics.
+/// The font's Encoding (base encoding + Differences) decides which glyph each code maps to.
+fn parse_standard_font_widths(
+    doc: &Document,
+    font_dict: &lopdf::Dictionary,
+) -> Option<FontWidthInfo> {
+    let base_font = font_dict.get(b"BaseFont").ok()?.as_name().ok()?;
+    let standard_font = StandardFont::from_base_font(&String::from_utf8_lossy(base_font))?;
+
+    let (base_encoding, differences) = match font_dict.get(b"Encoding") {
+        Ok(Object::Name(name)) => (Some(name.as_slice()), Vec::new()),
+        Ok(enc_obj) => match resolve_dict(doc, enc_obj) {
+            Some(enc_dict) => (
+                enc_dict.get(b"BaseEncoding").and_then(|o| o.as_name()).ok(),
+                parse_differences(doc, enc_dict),
+            ),
+            None => (None, Vec::new()),
+        },
+        Err(_) => (None, Vec::new()),
+    };
+
+    let widths = standard_font.code_widths(base_encoding, &differences);
+    if widths.is_empty() {
+        return None;
+    }
+    let space_width = widths.get(&32).copied().unwrap_or(250);
+
+    Some(FontWidthInfo {
+        widths,
+        default_width: 0,
+        space_width,
+        is_cid: false,
+        units_scale: 0.001,
+    })
+}
+
 /// Parse widths for simple fonts (Type1, TrueType, MMType1, Type3)
 /// Reads FirstChar, LastChar, and Widths array.
 /// For Type3 fonts, reads FontMatrix to determine the correct units_scale.
@@ -426,46 +485,50 @@ fn parse_encoding_dictionary(
     doc: &Document,
     enc_dict: &lopdf::Dictionary,
 ) -> Option<FontEncodingMap> {
-    let differences = enc_dict.get(b"Differences").ok()?;
+    let mut encoding_map = FontEncodingMap::new();
 
-    let diff_array = match differences {
-        Object::Array(arr) => arr.clone(),
-        Object::Reference(obj_ref) => {
-            if let Ok(Object::Array(arr)) = doc.get_object(*obj_ref) {
-                arr.clone()
-            } else {
-                return None;
-            }
+    for (code, glyph_name) in parse_differences(doc, enc_dict) {
+        // Map code to glyph name -> Unicode
+        if let Some(ch) = glyph_to_char(&glyph_name) {
+            encoding_map.insert(code, ch);
         }
-        _ => return None,
+    }
+
+    if encoding_map.is_empty() {
+        None
+    } else {
+        Some(encoding_map)
+    }
+}
+
+/// Read the Differences array of an encoding dictionary as (code, glyph name) pairs
+fn parse_differences(doc: &Document, enc_dict: &lopdf::Dictionary) -> Vec<(u8, String)> {
+    let Some(diff_array) = enc_dict
+        .get(b"Differences")
+        .ok()
+        .and_then(|d| resolve_array(doc, d))
+    else {
+        return Vec::new();
     };
 
-    let mut encoding_map = FontEncodingMap::new();
+    let mut differences = Vec::new();
     let mut current_code: u8 = 0;
 
     for item in diff_array {
         match item {
             Object::Integer(n) => {
                 // This sets the starting code for subsequent glyph names
-                current_code = n as u8;
+                current_code = *n as u8;
             }
             Object::Name(name) => {
-                // Map current code to glyph name -> Unicode
-                let glyph_name = String::from_utf8_lossy(&name).to_string();
-                if let Some(ch) = glyph_to_char(&glyph_name) {
-                    encoding_map.insert(current_code, ch);
-                }
+                differences.push((current_code, String::from_utf8_lossy(name).to_string()));
                 current_code = current_code.wrapping_add(1);
             }
             _ => {}
         }
     }
 
-    if encoding_map.is_empty() {
-        None
-    } else {
-        Some(encoding_map)
-    }
+    differences
 }
 
 /// Type of content item
@@ -476,12 +539,14 @@ pub enum ItemType {
     Text,
     /// Image placeholder
     Image,
-    /// Hyperlink (with URL)
+    /// Hyperlink (with URL, or `#anchor` for links inside the document)
     Link(String),
+    /// Destination of internal links (with its anchor id)
+    Anchor(String),
 }
 
 /// A text item with position information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TextItem {
     /// The text content
     pub text: String,
@@ -499,12 +564,58 @@ pub struct TextItem {
     pub font_size: f32,
     /// Page number (1-indexed)
     pub page: u32,
+    /// Label of the page from `/PageLabels` (e.g. "iv" or "A-2"); `None`
+    /// when the document does not label its pages
+    pub page_label: Option<String>,
     /// Whether the font is bold
     pub is_bold: bool,
     /// Whether the font is italic
     pub is_italic: bool,
     /// Type of item (text, image, link)
     pub item_type: ItemType,
+    /// Subtype of the annotation whose appearance stream produced this item
+    /// (e.g. "FreeText", "Stamp", "Widget"); `None` for page content
+    pub annotation: Option<String>,
+    /// Text markup (highlight, underline, ...) applied by a review annotation
+    pub markup: Option<AnnotationKind>,
+    /// Review comments anchored at this item
+    pub comments: Vec<Comment>,
+    /// Target of the hyperlink whose area covers this item
+    pub link: Option<String>,
+    /// Image properties, for image items
+    pub image: Option<ImageInfo>,
+    /// Baseline angle in degrees, counterclockwise from horizontal
+    pub rotation: f32,
+    /// Fill color as RGB components in 0..=1
+    pub fill_color: [f32; 3],
+    /// Stroke color as RGB components in 0..=1, which colors outlined text
+    pub stroke_color: [f32; 3],
+    /// Fill opacity (ExtGState /ca) for semi-transparent text; `None` when opaque
+    pub opacity: Option<f32>,
+    /// Artifact type when drawn inside an `/Artifact` marked-content section:
+    /// its /Subtype (e.g. "Watermark") or /Type (e.g. "Pagination"), or an
+    /// empty string for untyped artifacts
+    pub artifact: Option<String>,
+    /// Why the text is invisible to a reader, if it is (see [`crate::hidden_text`])
+    pub hidden: Option<HiddenReason>,
+    /// Luminance (0..=1) of the topmost filled shape or image painted under
+    /// the text before it, images counting as dark; `None` on the bare page
+    pub backdrop: Option<f32>,
+}
+
+/// Inline markdown formatting to apply when rendering a [`TextLine`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InlineFormat {
+    /// Wrap bold runs in `**`
+    pub bold: bool,
+    /// Wrap italic runs in `*`
+    pub italic: bool,
+    /// Render review markup and comment footnote references
+    pub annotations: bool,
+    /// Render linked text as `[anchor](url)`
+    pub links: bool,
+    /// Render text linked to a place in the document as `[anchor](#anchor)`
+    pub internal_links: bool,
 }
 
 /// A line of text (grouped text items)
@@ -522,13 +633,33 @@ impl TextLine {
 
     /// Get text with optional bold/italic markdown formatting
     pub fn text_with_formatting(&self, format_bold: bool, format_italic: bool) -> String {
-        if !format_bold && !format_italic {
+        self.text_with_markup(InlineFormat {
+            bold: format_bold,
+            italic: format_italic,
+            ..InlineFormat::default()
+        })
+    }
+
+    /// Get text with the requested inline markdown formatting: bold/italic,
+    /// review markup (`==highlight==`, `~~strikeout~~`, `[^n]` comment references)
+    /// and `[anchor](url)` links
+    pub fn text_with_markup(&self, format: InlineFormat) -> String {
+        if !format.bold
+            && !format.italic
+            && !format.annotations
+            && !format.links
+            && !format.internal_links
+        {
             return self.text_plain();
         }
 
         let mut result = String::new();
         let mut current_bold = false;
         let mut current_italic = false;
+        let mut current_markup: Option<&str> = None;
+        let mut current_link: Option<&str> = None;
+        // Footnote references go after the styled run they belong to
+        let mut pending_refs = String::new();
 
         for (i, item) in self.items.iter().enumerate() {
             let text = item.text.as_str();
@@ -555,8 +686,42 @@ impl TextLine {
             let has_leading_space = text.starts_with(' ');
 
             // Check for style changes
-            let item_bold = format_bold && item.is_bold;
-            let item_italic = format_italic && item.is_italic;
+            let item_bold = format.bold && item.is_bold;
+            let item_italic = format.italic && item.is_italic;
+            let item_markup = if format.annotations {
+                item.markup.and_then(markup_delimiter)
+            } else {
+                None
+            };
+            let item_link = item.link.as_deref().filter(|url| {
+                if url.starts_with('#') {
+                    format.internal_links
+                } else {
+                    format.links
+                }
+            });
+
+            // Links wrap markup, which wraps bold/italic, so changing an outer
+            // style closes the inner ones too
+            let link_changes = current_link != item_link;
+            if link_changes || current_markup != item_markup {
+                if current_italic {
+                    result.push('*');
+                    current_italic = false;
+                }
+                if current_bold {
+                    result.push_str("**");
+                    current_bold = false;
+                }
+                if let Some(delimiter) = current_markup.take() {
+                    result.push_str(delimiter);
+                }
+            }
+            if link_changes {
+                if let Some(url) = current_link.take() {
+                    result.push_str(&format!("]({})", markdown_link_target(url)));
+                }
+            }
 
             // Close previous styles if they change
             if current_italic && !item_italic {
@@ -567,6 +732,8 @@ impl TextLine {
                 result.push_str("**");
                 current_bold = false;
             }
+            result.push_str(&pending_refs);
+            pending_refs.clear();
 
             // Add space: either from spacing logic or preserved from item text
             if needs_space || (has_leading_space && !result.is_empty() && !result.ends_with(' ')) {
@@ -574,6 +741,14 @@ impl TextLine {
             }
 
             // Open new styles
+            if current_link.is_none() && item_link.is_some() {
+                result.push('[');
+                current_link = item_link;
+            }
+            if let (None, Some(delimiter)) = (current_markup, item_markup) {
+                result.push_str(delimiter);
+                current_markup = item_markup;
+            }
             if item_bold && !current_bold {
                 result.push_str("**");
                 current_bold = true;
@@ -583,7 +758,17 @@ impl TextLine {
                 current_italic = true;
             }
 
-            result.push_str(text_trimmed);
+            if current_link.is_some() {
+                result.push_str(&escape_link_text(text_trimmed));
+            } else {
+                result.push_str(text_trimmed);
+            }
+
+            if format.annotations {
+                for comment in &item.comments {
+                    pending_refs.push_str(&format!("[^{}]", comment.id));
+                }
+            }
         }
 
         // Close any remaining open styles
@@ -593,6 +778,13 @@ impl TextLine {
         if current_bold {
             result.push_str("**");
         }
+        if let Some(delimiter) = current_markup {
+            result.push_str(delimiter);
+        }
+        if let Some(url) = current_link {
+            result.push_str(&format!("]({})", markdown_link_target(url)));
+        }
+        result.push_str(&pending_refs);
 
         result
     }
@@ -651,6 +843,29 @@ impl TextLine {
     }
 }
 
+/// Escape characters that would end a markdown link target early
+fn markdown_link_target(url: &str) -> String {
+    url.replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+}
+
+/// Escape text for use inside the brackets of a markdown link or image
+pub(crate) fn escape_link_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+}
+
+/// Markdown delimiter for text markup; underlines have no markdown equivalent
+fn markup_delimiter(kind: AnnotationKind) -> Option<&'static str> {
+    match kind {
+        AnnotationKind::Highlight => Some("=="),
+        AnnotationKind::StrikeOut => Some("~~"),
+        _ => None,
+    }
+}
+
 /// Determine if two adjacent text items should be joined without a space
 /// based on their physical positions on the page and character case.
 /// Uses a hybrid approach: position-based with case-aware thresholds.
@@ -857,36 +1072,205 @@ pub fn extract_text_with_positions<P: AsRef<Path>>(path: P) -> Result<Vec<TextIt
     let font_cmaps = FontCMaps::from_pdf_bytes(&pdf_bytes);
 
     let doc = Document::load_mem(&pdf_bytes)?;
-    extract_positioned_text_from_doc(&doc, &font_cmaps)
+    extract_positioned_text_from_doc(&doc, &font_cmaps, &LayerSelection::Default)
 }
 
 /// Extract text with positions from memory buffer
 pub fn extract_text_with_positions_mem(buffer: &[u8]) -> Result<Vec<TextItem>, PdfError> {
+    extract_text_with_positions_in_layers_mem(buffer, &LayerSelection::Default)
+}
+
+/// Extract text with positions from PDF file, from the selected optional
+/// content layers only
+pub fn extract_text_with_positions_in_layers<P: AsRef<Path>>(
+    path: P,
+    layers: &LayerSelection,
+) -> Result<Vec<TextItem>, PdfError> {
+    let pdf_bytes = std::fs::read(path.as_ref())?;
+    extract_text_with_positions_in_layers_mem(&pdf_bytes, layers)
+}
+
+/// Extract text with positions from memory buffer, from the selected optional
+/// content layers only
+pub fn extract_text_with_positions_in_layers_mem(
+    buffer: &[u8],
+    layers: &LayerSelection,
+) -> Result<Vec<TextItem>, PdfError> {
     // Extract ToUnicode CMaps from raw PDF bytes
     let font_cmaps = FontCMaps::from_pdf_bytes(buffer);
 
     let doc = Document::load_mem(buffer)?;
-    extract_positioned_text_from_doc(&doc, &font_cmaps)
+    extract_positioned_text_from_doc(&doc, &font_cmaps, layers)
 }
 
 /// Extract positioned text from loaded document
 fn extract_positioned_text_from_doc(
     doc: &Document,
     font_cmaps: &FontCMaps,
+    layers: &LayerSelection,
 ) -> Result<Vec<TextItem>, PdfError> {
+    let (items, _, _) = extract_items_and_annotations(doc, font_cmaps, layers, false)?;
+    Ok(items)
+}
+
+/// Extract text with positions, and the vector graphics (ruling lines, boxes
+/// and filled shapes) painted on each page, from a PDF file
+pub fn extract_text_and_graphics<P: AsRef<Path>>(
+    path: P,
+) -> Result<(Vec<TextItem>, Vec<GraphicItem>), PdfError> {
+    extract_text_and_graphics_in_layers(path, &LayerSelection::Default)
+}
+
+/// Extract text with positions, and the vector graphics painted on each page,
+/// from a memory buffer
+pub fn extract_text_and_graphics_mem(
+    buffer: &[u8],
+) -> Result<(Vec<TextItem>, Vec<GraphicItem>), PdfError> {
+    extract_text_and_graphics_in_layers_mem(buffer, &LayerSelection::Default)
+}
+
+/// Extract text with positions and vector graphics from PDF file, from the
+/// selected optional content layers only
+pub fn extract_text_and_graphics_in_layers<P: AsRef<Path>>(
+    path: P,
+    layers: &LayerSelection,
+) -> Result<(Vec<TextItem>, Vec<GraphicItem>), PdfError> {
+    let pdf_bytes = std::fs::read(path.as_ref())?;
+    extract_text_and_graphics_in_layers_mem(&pdf_bytes, layers)
+}
+
+/// Extract text with positions and vector graphics from memory buffer, from
+/// the selected optional content layers only
+pub fn extract_text_and_graphics_in_layers_mem(
+    buffer: &[u8],
+    layers: &LayerSelection,
+) -> Result<(Vec<TextItem>, Vec<GraphicItem>), PdfError> {
+    let font_cmaps = FontCMaps::from_pdf_bytes(buffer);
+
+    let doc = Document::load_mem(buffer)?;
+    let (items, _, graphics) = extract_items_and_annotations(&doc, &font_cmaps, layers, true)?;
+    Ok((items, graphics))
+}
+
+/// Positioned text, vector graphics, page labels and attachments of a document
+pub(crate) type ProcessedContent = (
+    Vec<TextItem>,
+    Vec<GraphicItem>,
+    Vec<String>,
+    Vec<crate::attachments::Attachment>,
+);
+
+/// Extract text with positions from a PDF file; see [`extract_content_mem`]
+pub(crate) fn extract_content<P: AsRef<Path>>(
+    path: P,
+    layers: &LayerSelection,
+    capture_graphics: bool,
+    with_page_labels: bool,
+    with_attachments: bool,
+) -> Result<ProcessedContent, PdfError> {
+    let pdf_bytes = std::fs::read(path.as_ref())?;
+    extract_content_mem(
+        &pdf_bytes,
+        layers,
+        capture_graphics,
+        with_page_labels,
+        with_attachments,
+    )
+}
+
+/// Extract text with positions from memory buffer, from the selected optional
+/// content layers only, in a single load of the document: with the vector
+/// graphics when `capture_graphics` is set, the page labels (see
+/// [`crate::page_labels`]) when `with_page_labels` is set, and the embedded
+/// files (see [`crate::attachments`]) when `with_attachments` is set
+pub(crate) fn extract_content_mem(
+    buffer: &[u8],
+    layers: &LayerSelection,
+    capture_graphics: bool,
+    with_page_labels: bool,
+    with_attachments: bool,
+) -> Result<ProcessedContent, PdfError> {
+    let font_cmaps = FontCMaps::from_pdf_bytes(buffer);
+
+    let doc = Document::load_mem(buffer)?;
+    let (items, _, graphics) =
+        extract_items_and_annotations(&doc, &font_cmaps, layers, capture_graphics)?;
+    let labels = if with_page_labels {
+        crate::page_labels::page_labels(&doc)
+    } else {
+        Vec::new()
+    };
+    let attachments = if with_attachments {
+        crate::attachments::attachments(&doc)
+    } else {
+        Vec::new()
+    };
+    Ok((items, graphics, labels, attachments))
+}
+
+/// Text items, review annotations and vector graphics of a document
+type DocumentContent = (Vec<TextItem>, Vec<Annotation>, Vec<GraphicItem>);
+
+/// Extract positioned text and review annotations from loaded document,
+/// with annotation markup and comments applied to the text; vector graphics
+/// are collected too when `capture_graphics` is set
+fn extract_items_and_annotations(
+    doc: &Document,
+    font_cmaps: &FontCMaps,
+    layers: &LayerSelection,
+    capture_graphics: bool,
+) -> Result<DocumentContent, PdfError> {
     let pages = doc.get_pages();
+    let link_resolver = LinkResolver::new(doc);
+    let optional_content = OptionalContent::new(doc, layers);
     let mut all_items = Vec::new();
+    let mut all_annotations = Vec::new();
+    let mut all_graphics = Vec::new();
+    let mut destinations = Vec::new();
 
     for (page_num, &page_id) in pages.iter() {
-        let items = extract_page_text_items(doc, page_id, *page_num, font_cmaps)?;
+        let (mut items, graphics) = extract_page_content(
+            doc,
+            page_id,
+            *page_num,
+            font_cmaps,
+            &optional_content,
+            capture_graphics,
+        )?;
+        all_graphics.extend(graphics);
+
+        // Resolve review annotations against the page text, numbering them in document order
+        let mut annotations = extract_page_annotations(doc, page_id, *page_num);
+        for annot in &mut annotations {
+            annot.id += all_annotations.len();
+        }
+        apply_annotations(&mut items, &mut annotations);
+        all_annotations.extend(annotations);
+
+        // Attach hyperlinks to their anchor text; links over no text stay standalone
+        let links = collect_page_links(&link_resolver, page_id);
+        let standalone_links = attach_page_links(&mut items, &links, *page_num);
         all_items.extend(items);
+        all_items.extend(standalone_links);
+        destinations.extend(links.iter().filter_map(|link| link.destination));
+    }
 
-        // Extract hyperlinks from page annotations
-        let links = extract_page_links(doc, page_id, *page_num);
-        all_items.extend(links);
+    // Mark where internal links land so they can be given anchors
+    all_items.extend(destination_anchor_items(doc, &destinations));
+
+    // Record the page labels alongside the physical page numbers
+    let label_ranges = page_label_ranges(doc);
+    if !label_ranges.is_empty() {
+        let labels = labels_from_ranges(&label_ranges, pages.len() as u32);
+        for item in &mut all_items {
+            item.page_label = (item.page as usize)
+                .checked_sub(1)
+                .and_then(|index| labels.get(index))
+                .cloned();
+        }
     }
 
-    Ok(all_items)
+    Ok((all_items, all_annotations, all_graphics))
 }
 
 /// Multiply two 2D transformation matrices
@@ -905,885 +1289,2164 @@ fn multiply_matrices(m1: &[f32; 6], m2: &[f32; 6]) -> [f32; 6] {
     ]
 }
 
-/// Extract text items from a single page
-fn extract_page_text_items(
-    doc: &Document,
-    page_id: ObjectId,
-    page_num: u32,
-    font_cmaps: &FontCMaps,
-) -> Result<Vec<TextItem>, PdfError> {
-    use lopdf::content::Content;
+/// Maximum nesting depth for Form XObjects invoked from a content stream
+pub(crate) const MAX_FORM_DEPTH: usize = 12;
 
-    let mut items = Vec::new();
+/// Identity transformation matrix
+const IDENTITY_MATRIX: [f32; 6] = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
 
-    // Get fonts for encoding
-    let fonts = doc.get_page_fonts(page_id).unwrap_or_default();
+/// Read a 6-element matrix from content stream operands or a /Matrix array
+fn matrix_from_objects(objects: &[Object]) -> Option<[f32; 6]> {
+    if objects.len() < 6 {
+        return None;
+    }
+    let mut matrix = IDENTITY_MATRIX;
+    for (i, obj) in objects.iter().take(6).enumerate() {
+        matrix[i] = get_number(obj)?;
+    }
+    Some(matrix)
+}
+
+/// Axis-aligned rectangle in page space (PDF user units, origin bottom-left)
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rect {
+    x_min: f32,
+    y_min: f32,
+    x_max: f32,
+    y_max: f32,
+}
 
-    // Build font encoding maps from Differences arrays
-    let font_encodings = build_font_encodings(doc, &fonts);
+impl Rect {
+    /// Build a rectangle from a PDF rectangle array [x1 y1 x2 y2] in any corner order
+    fn from_objects(doc: &Document, obj: &Object) -> Option<Self> {
+        let array = resolve_array(doc, obj)?;
+        if array.len() < 4 {
+            return None;
+        }
+        let x1 = get_number(&array[0])?;
+        let y1 = get_number(&array[1])?;
+        let x2 = get_number(&array[2])?;
+        let y2 = get_number(&array[3])?;
+        Some(Rect {
+            x_min: x1.min(x2),
+            y_min: y1.min(y2),
+            x_max: x1.max(x2),
+            y_max: y1.max(y2),
+        })
+    }
 
-    // Build font width info for accurate text positioning
-    let font_widths = build_font_widths(doc, &fonts);
+    /// Bounding box of a [x1 y1 x2 y2 x3 y3 x4 y4] quadrilateral
+    fn from_quad(quad: &[f32; 8]) -> Self {
+        let xs = [quad[0], quad[2], quad[4], quad[6]];
+        let ys = [quad[1], quad[3], quad[5], quad[7]];
+        Rect {
+            x_min: xs.iter().copied().fold(f32::INFINITY, f32::min),
+            y_min: ys.iter().copied().fold(f32::INFINITY, f32::min),
+            x_max: xs.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+            y_max: ys.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+        }
+    }
 
-    // Build maps of font resource names to their base font names and ToUnicode object refs
-    let mut font_base_names: std::collections::HashMap<String, String> =
-        std::collections::HashMap::new();
-    let mut font_tounicode_refs: std::collections::HashMap<String, u32> =
-        std::collections::HashMap::new();
-    for (font_name, font_dict) in &fonts {
-        let resource_name = String::from_utf8_lossy(font_name).to_string();
-        if let Ok(base_font) = font_dict.get(b"BaseFont") {
-            if let Ok(name) = base_font.as_name() {
+    /// Bounding box of this rectangle after transformation by `matrix`
+    fn transform(&self, matrix: &[f32; 6]) -> Self {
+        let corners = [
+            (self.x_min, self.y_min),
+            (self.x_max, self.y_min),
+            (self.x_min, self.y_max),
+            (self.x_max, self.y_max),
+        ];
+        let mut out = Rect {
+            x_min: f32::INFINITY,
+            y_min: f32::INFINITY,
+            x_max: f32::NEG_INFINITY,
+            y_max: f32::NEG_INFINITY,
+        };
+        for (x, y) in corners {
+            let tx = x * matrix[0] + y * matrix[2] + matrix[4];
+            let ty = x * matrix[1] + y * matrix[3] + matrix[5];
+            out.x_min = out.x_min.min(tx);
+            out.y_min = out.y_min.min(ty);
+            out.x_max = out.x_max.max(tx);
+            out.y_max = out.y_max.max(ty);
+        }
+        out
+    }
+
+    /// Intersection of two rectangles (may be empty, i.e. min > max)
+    fn intersect(&self, other: &Rect) -> Self {
+        Rect {
+            x_min: self.x_min.max(other.x_min),
+            y_min: self.y_min.max(other.y_min),
+            x_max: self.x_max.min(other.x_max),
+            y_max: self.y_max.min(other.y_max),
+        }
+    }
+
+    /// Whether a point lies inside the rectangle, allowing `tolerance` units of slack
+    fn contains_point(&self, x: f32, y: f32, tolerance: f32) -> bool {
+        x >= self.x_min - tolerance
+            && x <= self.x_max + tolerance
+            && y >= self.y_min - tolerance
+            && y <= self.y_max + tolerance
+    }
+}
+
+/// Clip a horizontal run of text to the clip rectangle. Runs outside it are
+/// dropped; runs crossing its left or right edge keep only the characters
+/// inside, assuming evenly wide characters. Returns the visible text with its
+/// new start and width.
+fn clip_text_run(
+    clip: &Rect,
+    text: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    font_size: f32,
+) -> Option<(String, f32, f32)> {
+    // Glyphs extend from the baseline up to about the cap height
+    let (bottom, top) = (y, y + font_size * 0.7);
+    if top < clip.y_min - 1.0 || bottom > clip.y_max + 1.0 {
+        return None;
+    }
+    if width <= 0.0 {
+        return clip.contains_point(x, y, 1.0).then_some((text, x, width));
+    }
+    if x + width < clip.x_min - 1.0 || x > clip.x_max + 1.0 {
+        return None;
+    }
+    if x >= clip.x_min - 1.0 && x + width <= clip.x_max + 1.0 {
+        return Some((text, x, width));
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let advance = width / chars.len() as f32;
+    let inside = |i: usize| {
+        let center = x + (i as f32 + 0.5) * advance;
+        center >= clip.x_min && center <= clip.x_max
+    };
+    let first = (0..chars.len()).find(|&i| inside(i))?;
+    let last = (0..chars.len()).rev().find(|&i| inside(i))?;
+    let visible: String = chars[first..=last].iter().collect();
+    if visible.trim().is_empty() {
+        return None;
+    }
+    Some((
+        visible,
+        x + first as f32 * advance,
+        (last - first + 1) as f32 * advance,
+    ))
+}
+
+/// Resources visible to a content stream: fonts with their decoding and
+/// width information, XObjects by resource name, and marked-content properties
+struct ContentResources<'a> {
+    fonts: std::collections::BTreeMap<Vec<u8>, &'a lopdf::Dictionary>,
+    font_encodings: PageFontEncodings,
+    font_widths: PageFontWidths,
+    font_base_names: HashMap<String, String>,
+    font_tounicode_refs: HashMap<String, u32>,
+    xobjects: HashMap<String, XObjectType>,
+    /// Property lists (/Properties) referenced by BDC, e.g. optional content groups
+    properties: HashMap<Vec<u8>, &'a Object>,
+    /// Graphics state parameter dictionaries (/ExtGState) referenced by gs
+    ext_gstates: HashMap<Vec<u8>, &'a lopdf::Dictionary>,
+    /// Named color spaces (/ColorSpace) selected by cs and CS
+    color_spaces: HashMap<Vec<u8>, &'a Object>,
+}
+
+impl<'a> ContentResources<'a> {
+    /// Resources of a page, including those inherited from the page tree
+    fn for_page(doc: &'a Document, page_id: ObjectId) -> Self {
+        let fonts = doc.get_page_fonts(page_id).unwrap_or_default();
+
+        let mut xobjects = HashMap::new();
+        let mut properties = HashMap::new();
+        let mut ext_gstates = HashMap::new();
+        let mut color_spaces = HashMap::new();
+        if let Ok((direct, inherited)) = doc.get_page_resources(page_id) {
+            // Inherited resource dictionaries come first so the page's own entries win
+            for res_id in inherited.iter().rev() {
+                if let Ok(res) = doc.get_dictionary(*res_id) {
+                    collect_xobjects(doc, res, &mut xobjects);
+                    collect_resource_entries(doc, res, b"Properties", &mut properties);
+                    collect_resource_entries(doc, res, b"ColorSpace", &mut color_spaces);
+                    collect_ext_gstates(doc, res, &mut ext_gstates);
+                }
+            }
+            if let Some(res) = direct {
+                collect_xobjects(doc, res, &mut xobjects);
+                collect_resource_entries(doc, res, b"Properties", &mut properties);
+                collect_resource_entries(doc, res, b"ColorSpace", &mut color_spaces);
+                collect_ext_gstates(doc, res, &mut ext_gstates);
+            }
+        }
+
+        Self::new(doc, fonts, xobjects, properties, ext_gstates, color_spaces)
+    }
+
+    /// Resources declared by a Form XObject's /Resources dictionary
+    fn from_dict(doc: &'a Document, resources: &'a lopdf::Dictionary) -> Self {
+        let mut fonts = std::collections::BTreeMap::new();
+        if let Some(font_dict) = resources
+            .get(b"Font")
+            .ok()
+            .and_then(|o| resolve_dict(doc, o))
+        {
+            for (name, value) in font_dict.iter() {
+                if let Some(dict) = resolve_dict(doc, value) {
+                    fonts.insert(name.clone(), dict);
+                }
+            }
+        }
+
+        let mut xobjects = HashMap::new();
+        collect_xobjects(doc, resources, &mut xobjects);
+        let mut properties = HashMap::new();
+        collect_resource_entries(doc, resources, b"Properties", &mut properties);
+        let mut ext_gstates = HashMap::new();
+        collect_ext_gstates(doc, resources, &mut ext_gstates);
+        let mut color_spaces = HashMap::new();
+        collect_resource_entries(doc, resources, b"ColorSpace", &mut color_spaces);
+
+        Self::new(doc, fonts, xobjects, properties, ext_gstates, color_spaces)
+    }
+
+    fn new(
+        doc: &'a Document,
+        fonts: std::collections::BTreeMap<Vec<u8>, &'a lopdf::Dictionary>,
+        xobjects: HashMap<String, XObjectType>,
+        properties: HashMap<Vec<u8>, &'a Object>,
+        ext_gstates: HashMap<Vec<u8>, &'a lopdf::Dictionary>,
+        color_spaces: HashMap<Vec<u8>, &'a Object>,
+    ) -> Self {
+        // Build font encoding maps from Differences arrays
+        let font_encodings = build_font_encodings(doc, &fonts);
+
+        // Build font width info for accurate text positioning
+        let font_widths = build_font_widths(doc, &fonts);
+
+        // Build maps of font resource names to their base font names and ToUnicode object refs
+        let mut font_base_names = HashMap::new();
+        let mut font_tounicode_refs = HashMap::new();
+        for (font_name, font_dict) in &fonts {
+            let resource_name = String::from_utf8_lossy(font_name).to_string();
+            if let Ok(name) = font_dict.get(b"BaseFont").and_then(Object::as_name) {
                 let base_name = String::from_utf8_lossy(name).to_string();
                 font_base_names.insert(resource_name.clone(), base_name);
             }
-        }
-        // Track ToUnicode object reference
-        if let Ok(tounicode) = font_dict.get(b"ToUnicode") {
-            if let Ok(obj_ref) = tounicode.as_reference() {
+            if let Ok(obj_ref) = font_dict.get(b"ToUnicode").and_then(Object::as_reference) {
                 font_tounicode_refs.insert(resource_name, obj_ref.0);
             }
         }
+
+        ContentResources {
+            fonts,
+            font_encodings,
+            font_widths,
+            font_base_names,
+            font_tounicode_refs,
+            xobjects,
+            properties,
+            ext_gstates,
+            color_spaces,
+        }
     }
+}
 
-    // Get XObjects (images) from page resources
-    let xobjects = get_page_xobjects(doc, page_id);
+/// An open marked-content section (BMC/BDC)
+#[derive(Debug, Clone)]
+struct MarkedSection {
+    /// Whether its optional content is visible
+    visible: bool,
+    /// Artifact type of an `/Artifact` section (see [`TextItem::artifact`])
+    artifact: Option<String>,
+}
 
-    // Get content
-    let content_data = doc
-        .get_page_content(page_id)
-        .map_err(|e| PdfError::Parse(e.to_string()))?;
+/// Graphics state saved and restored by the q/Q operators
+#[derive(Debug, Clone)]
+struct GraphicsState {
+    /// Current transformation matrix
+    ctm: [f32; 6],
+    /// Bounding box of the clipping region in page space, if any (from `W`/`W*`
+    /// clipping paths and Form XObject bounding boxes)
+    clip: Option<Rect>,
+    font: String,
+    font_size: f32,
+    /// Character spacing (Tc), in unscaled text space units
+    char_spacing: f32,
+    /// Word spacing (Tw), applied to single-byte code 32
+    word_spacing: f32,
+    /// Horizontal scaling (Tz) as a fraction (1.0 = 100%)
+    horizontal_scale: f32,
+    /// Text leading (TL); `None` falls back to 1.2 × font size for T*
+    leading: Option<f32>,
+    /// Nonstroking color space; `None` for patterns and unsupported spaces
+    fill_space: Option<ColorSpace>,
+    /// Nonstroking color as RGB
+    fill_color: [f32; 3],
+    /// Stroking color space; `None` for patterns and unsupported spaces
+    stroke_space: Option<ColorSpace>,
+    /// Stroking color as RGB
+    stroke_color: [f32; 3],
+    /// Nonstroking alpha constant (ExtGState /ca)
+    fill_alpha: f32,
+    /// Text rendering mode (Tr)
+    render_mode: i64,
+    /// Line width (w), in user space units
+    line_width: f32,
+}
 
-    let content = Content::decode(&content_data).map_err(|e| PdfError::Parse(e.to_string()))?;
+impl Default for GraphicsState {
+    fn default() -> Self {
+        GraphicsState {
+            ctm: IDENTITY_MATRIX,
+            clip: None,
+            font: String::new(),
+            font_size: 12.0,
+            char_spacing: 0.0,
+            word_spacing: 0.0,
+            horizontal_scale: 1.0,
+            leading: None,
+            fill_space: Some(ColorSpace::Gray),
+            fill_color: [0.0; 3],
+            stroke_space: Some(ColorSpace::Gray),
+            stroke_color: [0.0; 3],
+            fill_alpha: 1.0,
+            render_mode: 0,
+            line_width: 1.0,
+        }
+    }
+}
+
+/// Content stream interpreter shared by pages and (nested) Form XObjects
+struct ContentInterpreter<'a> {
+    doc: &'a Document,
+    font_cmaps: &'a FontCMaps,
+    optional_content: &'a OptionalContent,
+    page_num: u32,
+    state: GraphicsState,
+    state_stack: Vec<GraphicsState>,
+    text_matrix: [f32; 6],
+    line_matrix: [f32; 6],
+    in_text_block: bool,
+    /// Open marked-content sections (BMC/BDC)
+    marked_content: Vec<MarkedSection>,
+    /// The path under construction, in page space
+    path: Vec<PathElement>,
+    /// Whether the path under construction becomes a clipping path (`W`, `W*`)
+    pending_clip: bool,
+    /// Forms currently being executed, for cycle detection
+    form_stack: Vec<ObjectId>,
+    /// Subtype of the annotation whose appearance is being rendered, if any
+    annotation: Option<String>,
+    items: Vec<TextItem>,
+    /// Filled shapes and images painted so far, as their visible bounds and
+    /// luminance, to find what text is drawn over
+    backdrops: Vec<(Rect, f32)>,
+    /// Whether painted paths are collected into `graphics`
+    capture_graphics: bool,
+    graphics: Vec<GraphicItem>,
+}
+
+impl<'a> ContentInterpreter<'a> {
+    fn new(
+        doc: &'a Document,
+        font_cmaps: &'a FontCMaps,
+        optional_content: &'a OptionalContent,
+        page_num: u32,
+    ) -> Self {
+        ContentInterpreter {
+            doc,
+            font_cmaps,
+            optional_content,
+            page_num,
+            state: GraphicsState::default(),
+            state_stack: Vec::new(),
+            text_matrix: IDENTITY_MATRIX,
+            line_matrix: IDENTITY_MATRIX,
+            in_text_block: false,
+            marked_content: Vec::new(),
+            path: Vec::new(),
+            pending_clip: false,
+            form_stack: Vec::new(),
+            annotation: None,
+            items: Vec::new(),
+            backdrops: Vec::new(),
+            capture_graphics: false,
+            graphics: Vec::new(),
+        }
+    }
 
-    // Graphics state tracking
-    let mut ctm = [1.0f32, 0.0, 0.0, 1.0, 0.0, 0.0]; // Current Transformation Matrix
-    let mut ctm_stack: Vec<[f32; 6]> = Vec::new();
-
-    // Text state tracking
-    let mut current_font = String::new();
-    let mut current_font_size: f32 = 12.0;
-    let mut text_matrix = [1.0f32, 0.0, 0.0, 1.0, 0.0, 0.0];
-    let mut line_matrix = [1.0f32, 0.0, 0.0, 1.0, 0.0, 0.0];
-    let mut in_text_block = false;
-
-    for op in &content.operations {
-        match op.operator.as_str() {
-            "q" => {
-                // Save graphics state
-                ctm_stack.push(ctm);
-            }
-            "Q" => {
-                // Restore graphics state
-                if let Some(saved) = ctm_stack.pop() {
-                    ctm = saved;
+    /// Run a decoded content stream against the given resources
+    fn execute(&mut self, operations: &[lopdf::content::Operation], res: &ContentResources) {
+        for op in operations {
+            let operands = &op.operands;
+            match op.operator.as_str() {
+                "q" => self.state_stack.push(self.state.clone()),
+                "Q" => {
+                    if let Some(saved) = self.state_stack.pop() {
+                        self.state = saved;
+                    }
                 }
-            }
-            "cm" => {
-                // Concatenate matrix to CTM
-                if op.operands.len() >= 6 {
-                    let new_matrix = [
-                        get_number(&op.operands[0]).unwrap_or(1.0),
-                        get_number(&op.operands[1]).unwrap_or(0.0),
-                        get_number(&op.operands[2]).unwrap_or(0.0),
-                        get_number(&op.operands[3]).unwrap_or(1.0),
-                        get_number(&op.operands[4]).unwrap_or(0.0),
-                        get_number(&op.operands[5]).unwrap_or(0.0),
-                    ];
-                    ctm = multiply_matrices(&new_matrix, &ctm);
+                "cm" => {
+                    if let Some(matrix) = matrix_from_objects(operands) {
+                        self.state.ctm = multiply_matrices(&matrix, &self.state.ctm);
+                    }
                 }
-            }
-            "BT" => {
-                // Begin text block
-                in_text_block = true;
-                text_matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
-                line_matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
-            }
-            "ET" => {
-                // End text block
-                in_text_block = false;
-            }
-            "Tf" => {
-                // Set font and size
-                if op.operands.len() >= 2 {
-                    if let Ok(name) = op.operands[0].as_name() {
-                        current_font = String::from_utf8_lossy(name).to_string();
+                "m" | "l" | "c" | "v" | "y" | "re" | "h" => {
+                    let coords: Vec<f32> = operands.iter().filter_map(get_number).collect();
+                    self.add_path_element(op.operator.as_str(), &coords);
+                }
+                "w" => {
+                    if let Some(width) = operands.first().and_then(get_number) {
+                        self.state.line_width = width.abs();
+                    }
+                }
+                "W" | "W*" => self.pending_clip = true,
+                "n" | "S" | "s" | "f" | "F" | "f*" | "B" | "B*" | "b" | "b*" => {
+                    self.end_path(op.operator.as_str())
+                }
+                "BT" => {
+                    self.in_text_block = true;
+                    self.text_matrix = IDENTITY_MATRIX;
+                    self.line_matrix = IDENTITY_MATRIX;
+                }
+                "ET" => self.in_text_block = false,
+                "Tf" if operands.len() >= 2 => {
+                    if let Ok(name) = operands[0].as_name() {
+                        self.state.font = String::from_utf8_lossy(name).to_string();
                     }
-                    if let Ok(size) = op.operands[1].as_f32() {
-                        current_font_size = size;
-                    } else if let Ok(size) = op.operands[1].as_i64() {
-                        current_font_size = size as f32;
+                    if let Some(size) = get_number(&operands[1]) {
+                        self.state.font_size = size;
                     }
                 }
-            }
-            "Td" | "TD" => {
-                // Move text position
-                if op.operands.len() >= 2 {
-                    let tx = get_number(&op.operands[0]).unwrap_or(0.0);
-                    let ty = get_number(&op.operands[1]).unwrap_or(0.0);
-                    line_matrix[4] += tx;
-                    line_matrix[5] += ty;
-                    text_matrix = line_matrix;
+                "Tc" => {
+                    if let Some(v) = operands.first().and_then(get_number) {
+                        self.state.char_spacing = v;
+                    }
                 }
-            }
-            "Tm" => {
-                // Set text matrix
-                if op.operands.len() >= 6 {
-                    for (i, operand) in op.operands.iter().take(6).enumerate() {
-                        text_matrix[i] =
-                            get_number(operand).unwrap_or(if i == 0 || i == 3 { 1.0 } else { 0.0 });
+                "Tw" => {
+                    if let Some(v) = operands.first().and_then(get_number) {
+                        self.state.word_spacing = v;
                     }
-                    line_matrix = text_matrix;
                 }
-            }
-            "T*" => {
-                // Move to start of next line
-                line_matrix[5] -= current_font_size * 1.2; // Approximate line height
-                text_matrix = line_matrix;
-            }
-            "Tj" => {
-                // Show text string
-                if in_text_block && !op.operands.is_empty() {
-                    if let Some(text) = extract_text_from_operand(
-                        &op.operands[0],
-                        doc,
-                        &fonts,
-                        &current_font,
-                        font_cmaps,
-                        &font_base_names,
-                        &font_tounicode_refs,
-                        &font_encodings,
-                    ) {
-                        if !text.trim().is_empty() {
-                            let rendered_size =
-                                effective_font_size(current_font_size, &text_matrix);
-                            // Transform position through CTM
-                            let combined = multiply_matrices(&text_matrix, &ctm);
-                            let (x, y) = (combined[4], combined[5]);
-                            // Compute width from font widths if available
-                            let width = if let Some(font_info) = font_widths.get(&current_font) {
-                                if let Some(raw_bytes) = get_operand_bytes(&op.operands[0]) {
-                                    let w_ts = compute_string_width_ts(
-                                        raw_bytes,
-                                        font_info,
-                                        current_font_size,
-                                    );
-                                    // Advance text matrix by string width
-                                    text_matrix[4] += w_ts * text_matrix[0];
-                                    text_matrix[5] += w_ts * text_matrix[1];
-                                    // Transform width through text matrix and CTM
-                                    (w_ts * (text_matrix[0] * ctm[0] + text_matrix[1] * ctm[2]))
-                                        .abs()
-                                } else {
-                                    0.0
-                                }
-                            } else {
-                                0.0
-                            };
-                            // Detect bold/italic from font name
-                            let base_font = font_base_names
-                                .get(&current_font)
-                                .map(|s| s.as_str())
-                                .unwrap_or(&current_font);
-                            items.push(TextItem {
-                                text: expand_ligatures(&text),
-                                x,
-                                y,
-                                width,
-                                height: rendered_size,
-                                font: current_font.clone(),
-                                font_size: rendered_size,
-                                page: page_num,
-                                is_bold: is_bold_font(base_font),
-                                is_italic: is_italic_font(base_font),
-                                item_type: ItemType::Text,
-                            });
-                        }
+                "Tz" => {
+                    if let Some(v) = operands.first().and_then(get_number) {
+                        self.state.horizontal_scale = v / 100.0;
                     }
                 }
-            }
-            "TJ" => {
-                // Show text with positioning
-                if in_text_block && !op.operands.is_empty() {
-                    if let Ok(array) = op.operands[0].as_array() {
-                        let font_info = font_widths.get(&current_font);
-
-                        // Compute space threshold based on font metrics when available
-                        let space_threshold = if let Some(font_info) = font_info {
-                            // Use 40% of the font's space width (in thousandths of text space)
-                            let space_em = font_info.space_width as f32 * font_info.units_scale;
-                            let threshold = space_em * 1000.0 * 0.4;
-                            // Clamp to reasonable range: at least 80, at most 200
-                            threshold.clamp(80.0, 200.0)
-                        } else {
-                            120.0 // fallback threshold
-                        };
-
-                        let mut combined_text = String::new();
-                        let mut total_width_ts: f32 = 0.0;
-                        for element in array {
-                            match element {
-                                Object::Integer(n) => {
-                                    let n_val = *n as f32;
-                                    // Track displacement for total width
-                                    total_width_ts += -n_val / 1000.0 * current_font_size;
-                                    if n_val < -space_threshold
-                                        && !combined_text.is_empty()
-                                        && !combined_text.ends_with(' ')
-                                    {
-                                        combined_text.push(' ');
-                                    }
-                                    continue;
-                                }
-                                Object::Real(n) => {
-                                    total_width_ts += -(*n) / 1000.0 * current_font_size;
-                                    if *n < -space_threshold
-                                        && !combined_text.is_empty()
-                                        && !combined_text.ends_with(' ')
-                                    {
-                                        combined_text.push(' ');
-                                    }
-                                    continue;
-                                }
-                                _ => {}
-                            }
-                            // Compute string width for total
-                            if let Some(fi) = font_info {
-                                if let Some(raw_bytes) = get_operand_bytes(element) {
-                                    total_width_ts +=
-                                        compute_string_width_ts(raw_bytes, fi, current_font_size);
-                                }
-                            }
-                            if let Some(text) = extract_text_from_operand(
-                                element,
-                                doc,
-                                &fonts,
-                                &current_font,
-                                font_cmaps,
-                                &font_base_names,
-                                &font_tounicode_refs,
-                                &font_encodings,
-                            ) {
-                                combined_text.push_str(&text);
-                            }
-                        }
-                        if !combined_text.trim().is_empty() {
-                            let rendered_size =
-                                effective_font_size(current_font_size, &text_matrix);
-                            let combined = multiply_matrices(&text_matrix, &ctm);
-                            let (x, y) = (combined[4], combined[5]);
-                            // Compute accurate width if font widths available
-                            let width = if font_info.is_some() {
-                                (total_width_ts
-                                    * (text_matrix[0] * ctm[0] + text_matrix[1] * ctm[2]))
-                                    .abs()
-                            } else {
-                                0.0
-                            };
-                            let base_font = font_base_names
-                                .get(&current_font)
-                                .map(|s| s.as_str())
-                                .unwrap_or(&current_font);
-                            items.push(TextItem {
-                                text: expand_ligatures(&combined_text),
-                                x,
-                                y,
-                                width,
-                                height: rendered_size,
-                                font: current_font.clone(),
-                                font_size: rendered_size,
-                                page: page_num,
-                                is_bold: is_bold_font(base_font),
-                                is_italic: is_italic_font(base_font),
-                                item_type: ItemType::Text,
-                            });
-                            // Advance text matrix by total width
-                            if font_info.is_some() {
-                                text_matrix[4] += total_width_ts * text_matrix[0];
-                                text_matrix[5] += total_width_ts * text_matrix[1];
-                            }
-                        }
+                "Tr" => {
+                    if let Some(mode) = operands.first().and_then(|o| o.as_i64().ok()) {
+                        self.state.render_mode = mode;
                     }
                 }
-            }
-            "'" => {
-                // Move to next line and show text
-                line_matrix[5] -= current_font_size * 1.2;
-                text_matrix = line_matrix;
-                if !op.operands.is_empty() {
-                    if let Some(text) = extract_text_from_operand(
-                        &op.operands[0],
-                        doc,
-                        &fonts,
-                        &current_font,
-                        font_cmaps,
-                        &font_base_names,
-                        &font_tounicode_refs,
-                        &font_encodings,
-                    ) {
-                        if !text.trim().is_empty() {
-                            let rendered_size =
-                                effective_font_size(current_font_size, &text_matrix);
-                            // Transform position through CTM
-                            let combined = multiply_matrices(&text_matrix, &ctm);
-                            let (x, y) = (combined[4], combined[5]);
-                            // Detect bold/italic from font name
-                            let base_font = font_base_names
-                                .get(&current_font)
-                                .map(|s| s.as_str())
-                                .unwrap_or(&current_font);
-                            items.push(TextItem {
-                                text: expand_ligatures(&text),
-                                x,
-                                y,
-                                width: 0.0,
-                                height: rendered_size,
-                                font: current_font.clone(),
-                                font_size: rendered_size,
-                                page: page_num,
-                                is_bold: is_bold_font(base_font),
-                                is_italic: is_italic_font(base_font),
-                                item_type: ItemType::Text,
-                            });
+                "TL" => {
+                    if let Some(v) = operands.first().and_then(get_number) {
+                        self.state.leading = Some(v);
+                    }
+                }
+                "Td" | "TD" if operands.len() >= 2 => {
+                    let tx = get_number(&operands[0]).unwrap_or(0.0);
+                    let ty = get_number(&operands[1]).unwrap_or(0.0);
+                    if op.operator == "TD" {
+                        self.state.leading = Some(-ty);
+                    }
+                    self.move_text_position(tx, ty);
+                }
+                "Tm" => {
+                    if let Some(matrix) = matrix_from_objects(operands) {
+                        self.text_matrix = matrix;
+                        self.line_matrix = matrix;
+                    }
+                }
+                "T*" => self.next_line(),
+                "Tj" | "TJ" | "'" | "\"" if self.in_text_block => {
+                    let text_operand = match op.operator.as_str() {
+                        "'" => {
+                            self.next_line();
+                            operands.first()
+                        }
+                        "\"" if operands.len() >= 3 => {
+                            self.state.word_spacing = get_number(&operands[0]).unwrap_or(0.0);
+                            self.state.char_spacing = get_number(&operands[1]).unwrap_or(0.0);
+                            self.next_line();
+                            operands.get(2)
                         }
+                        "\"" => None,
+                        _ => operands.first(),
+                    };
+                    if let Some(operand) = text_operand {
+                        self.show_text(operand, res);
                     }
                 }
-            }
-            "Do" => {
-                // XObject invocation - could be an image or form
-                if !op.operands.is_empty() {
-                    if let Ok(name) = op.operands[0].as_name() {
+                "Do" => {
+                    if let Some(name) = operands.first().and_then(|o| o.as_name().ok()) {
                         let xobj_name = String::from_utf8_lossy(name).to_string();
-
-                        if let Some(xobj_type) = xobjects.get(&xobj_name) {
-                            match xobj_type {
-                                XObjectType::Image => {
-                                    // Get position from CTM
-                                    let (x, y) = (ctm[4], ctm[5]);
-                                    // Get dimensions from CTM scale factors
-                                    let width = ctm[0].abs();
-                                    let height = ctm[3].abs();
-
-                                    items.push(TextItem {
-                                        text: format!("[Image: {}]", xobj_name),
-                                        x,
-                                        y,
-                                        width,
-                                        height,
-                                        font: String::new(),
-                                        font_size: 0.0,
-                                        page: page_num,
-                                        is_bold: false,
-                                        is_italic: false,
-                                        item_type: ItemType::Image,
-                                    });
-                                }
-                                XObjectType::Form(form_id) => {
-                                    // Extract text from Form XObject
-                                    let form_items = extract_form_xobject_text(
-                                        doc, *form_id, page_num, font_cmaps, &ctm,
-                                    );
-                                    items.extend(form_items);
-                                }
-                            }
-                        }
+                        self.invoke_xobject(&xobj_name, res);
+                    }
+                }
+                "BMC" => {
+                    let is_artifact =
+                        operands.first().and_then(|o| o.as_name().ok()) == Some(b"Artifact");
+                    self.marked_content.push(MarkedSection {
+                        visible: true,
+                        artifact: is_artifact.then(String::new),
+                    });
+                }
+                "BDC" => {
+                    let section = MarkedSection {
+                        visible: self.marked_content_visible(operands, res),
+                        artifact: self.artifact_type(operands, res),
+                    };
+                    self.marked_content.push(section);
+                }
+                "g" | "G" => self.set_device_color(&op.operator, ColorSpace::Gray, operands),
+                "rg" | "RG" => self.set_device_color(&op.operator, ColorSpace::Rgb, operands),
+                "k" | "K" => self.set_device_color(&op.operator, ColorSpace::Cmyk, operands),
+                "cs" | "CS" => {
+                    let space = operands
+                        .first()
+                        .and_then(|o| o.as_name().ok())
+                        .and_then(|name| self.named_color_space(name, res));
+                    let color = space
+                        .as_ref()
+                        .map_or([0.0; 3], |cs| cs.to_rgb(&cs.initial_values()));
+                    if op.operator == "cs" {
+                        (self.state.fill_space, self.state.fill_color) = (space, color);
+                    } else {
+                        (self.state.stroke_space, self.state.stroke_color) = (space, color);
+                    }
+                }
+                "sc" | "scn" | "SC" | "SCN" => {
+                    let fill = op.operator.starts_with('s');
+                    let space = if fill {
+                        &self.state.fill_space
+                    } else {
+                        &self.state.stroke_space
+                    };
+                    let values: Vec<f32> = operands.iter().filter_map(get_number).collect();
+                    // Pattern colors (scn /P0) have no single color to record
+                    let color = space
+                        .as_ref()
+                        .filter(|cs| values.len() >= cs.components())
+                        .map(|cs| cs.to_rgb(&values));
+                    match (color, fill) {
+                        (Some(color), true) => self.state.fill_color = color,
+                        (Some(color), false) => self.state.stroke_color = color,
+                        (None, _) => {}
+                    }
+                }
+                "gs" => {
+                    let params = operands
+                        .first()
+                        .and_then(|o| o.as_name().ok())
+                        .and_then(|name| res.ext_gstates.get(name));
+                    if let Some(alpha) = params
+                        .and_then(|gs| gs.get(b"ca").ok())
+                        .and_then(get_number)
+                    {
+                        self.state.fill_alpha = alpha.clamp(0.0, 1.0);
                     }
                 }
+                "EMC" => {
+                    self.marked_content.pop();
+                }
+                _ => {}
             }
-            _ => {}
         }
     }
 
-    Ok(items)
+    /// Whether a BDC section is visible: `/OC` sections follow the state of their
+    /// optional content group or membership dictionary, other tags are always shown
+    fn marked_content_visible(&self, operands: &[Object], res: &ContentResources) -> bool {
+        if operands.first().and_then(|o| o.as_name().ok()) != Some(b"OC") {
+            return true;
+        }
+        match operands.get(1) {
+            Some(Object::Name(name)) => res
+                .properties
+                .get(name)
+                .is_none_or(|oc| self.optional_content.is_visible(self.doc, oc)),
+            Some(oc @ Object::Dictionary(_)) => self.optional_content.is_visible(self.doc, oc),
+            _ => true,
+        }
+    }
+
+    /// Map a point from user space to page space
+    fn to_page_space(&self, x: f32, y: f32) -> (f32, f32) {
+        let ctm = &self.state.ctm;
+        (
+            x * ctm[0] + y * ctm[2] + ctm[4],
+            x * ctm[1] + y * ctm[3] + ctm[5],
+        )
+    }
+
+    /// Add a path construction operator, with user space operands, to the
+    /// path under construction
+    fn add_path_element(&mut self, operator: &str, coords: &[f32]) {
+        // `v` repeats the current point as its first control point
+        let current = self
+            .path
+            .iter()
+            .rev()
+            .find_map(|e| e.points().last().copied());
+        let points: Vec<(f32, f32)> = coords
+            .chunks_exact(2)
+            .map(|p| self.to_page_space(p[0], p[1]))
+            .collect();
+        let element = match (operator, &points[..], coords) {
+            ("m", [p], _) => PathElement::MoveTo(*p),
+            ("l", [p], _) => PathElement::LineTo(*p),
+            ("c", [c1, c2, p], _) => PathElement::CurveTo([*c1, *c2, *p]),
+            ("v", [c2, p], _) => PathElement::CurveTo([current.unwrap_or(*c2), *c2, *p]),
+            ("y", [c1, p], _) => PathElement::CurveTo([*c1, *p, *p]),
+            ("re", _, &[x, y, w, h]) => PathElement::Rect([
+                self.to_page_space(x, y),
+                self.to_page_space(x + w, y),
+                self.to_page_space(x + w, y + h),
+                self.to_page_space(x, y + h),
+            ]),
+            ("h", _, _) => PathElement::Close,
+            _ => return,
+        };
+        self.path.push(element);
+    }
+
+    /// Finish the current path with a painting operator, intersecting the clip
+    /// with it when `W`/`W*` preceded, and capturing it when painted
+    fn end_path(&mut self, operator: &str) {
+        let path = std::mem::take(&mut self.path);
+        let points = path.iter().flat_map(|e| e.points().iter().copied());
+        let bounds = points.fold(None, |bounds: Option<Rect>, (x, y)| {
+            Some(match bounds {
+                Some(b) => Rect {
+                    x_min: b.x_min.min(x),
+                    y_min: b.y_min.min(y),
+                    x_max: b.x_max.max(x),
+                    y_max: b.y_max.max(y),
+                },
+                None => Rect {
+                    x_min: x,
+                    y_min: y,
+                    x_max: x,
+                    y_max: y,
+                },
+            })
+        });
+        let Some(bounds) = bounds else {
+            self.pending_clip = false;
+            return;
+        };
+
+        // A path is painted with the clip in effect before its own `W`
+        let visible = self.state.clip.is_none_or(|clip| {
+            let overlap = clip.intersect(&bounds);
+            overlap.x_min <= overlap.x_max && overlap.y_min <= overlap.y_max
+        });
+        let filled = matches!(operator, "f" | "F" | "f*" | "B" | "B*" | "b" | "b*");
+        if filled
+            && visible
+            && !self.in_hidden_content()
+            && self.state.fill_alpha >= MIN_VISIBLE_OPACITY
+        {
+            let shown = match self.state.clip {
+                Some(clip) => clip.intersect(&bounds),
+                None => bounds,
+            };
+            self.backdrops
+                .push((shown, luminance(self.state.fill_color)));
+        }
+        if self.capture_graphics && operator != "n" && visible && !self.in_hidden_content() {
+            let ctm = &self.state.ctm;
+            let scale = (ctm[0] * ctm[3] - ctm[1] * ctm[2]).abs().sqrt();
+            let paint = PathPaint {
+                page: self.page_num,
+                stroked: matches!(operator, "S" | "s" | "B" | "B*" | "b" | "b*"),
+                filled,
+                line_width: self.state.line_width * scale,
+                stroke_color: self.state.stroke_color,
+                fill_color: self.state.fill_color,
+            };
+            let mut path = path;
+            if matches!(operator, "s" | "b" | "b*") {
+                path.push(PathElement::Close);
+            }
+            self.graphics.extend(path_graphics(&path, &paint));
+        }
+
+        if self.pending_clip {
+            self.state.clip = Some(match self.state.clip {
+                Some(clip) => clip.intersect(&bounds),
+                None => bounds,
+            });
+        }
+        self.pending_clip = false;
+    }
+
+    /// Set the fill (lowercase operator) or stroke color in a device color space
+    fn set_device_color(&mut self, operator: &str, space: ColorSpace, operands: &[Object]) {
+        let values: Vec<f32> = operands.iter().filter_map(get_number).collect();
+        if values.len() != space.components() {
+            return;
+        }
+        let color = space.to_rgb(&values);
+        if operator.starts_with(|c: char| c.is_ascii_lowercase()) {
+            (self.state.fill_space, self.state.fill_color) = (Some(space), color);
+        } else {
+            (self.state.stroke_space, self.state.stroke_color) = (Some(space), color);
+        }
+    }
+
+    /// Resolve the operand of cs/CS: a device space or a /ColorSpace resource
+    fn named_color_space(&self, name: &[u8], res: &ContentResources) -> Option<ColorSpace> {
+        let obj = match res.color_spaces.get(name) {
+            Some(obj) => (*obj).clone(),
+            None => Object::Name(name.to_vec()),
+        };
+        ColorSpace::from_object(self.doc, &obj, 0).ok()
+    }
+
+    /// Luminance of the topmost filled shape or image painted so far under a
+    /// run of text starting at (x, y)
+    fn backdrop_under(&self, x: f32, y: f32, width: f32, size: f32) -> Option<f32> {
+        self.backdrops
+            .iter()
+            .rev()
+            .find(|(bounds, _)| {
+                bounds.contains_point(x, y, 0.0)
+                    && bounds.contains_point(x + width, y + size * 0.5, 0.0)
+            })
+            .map(|&(_, luminance)| luminance)
+    }
+
+    /// Why text drawn with the current state over `backdrop` would be
+    /// invisible, if it would be
+    fn hidden_reason(&self, rendered_size: f32, backdrop: Option<f32>) -> Option<HiddenReason> {
+        // Modes 1 and 5 stroke glyph outlines without filling them
+        let filled = !matches!(self.state.render_mode, 1 | 5);
+        let fill_color = self.state.fill_color;
+        if matches!(self.state.render_mode, 3 | 7) {
+            Some(HiddenReason::InvisibleRenderMode)
+        } else if filled && self.state.fill_alpha < MIN_VISIBLE_OPACITY {
+            Some(HiddenReason::Transparent)
+        } else if filled
+            && luminance(fill_color) >= WHITE_LUMINANCE
+            && !stands_out_from(fill_color, backdrop)
+        {
+            Some(HiddenReason::WhiteText)
+        } else if rendered_size < MIN_VISIBLE_FONT_SIZE {
+            Some(HiddenReason::TinyFont)
+        } else {
+            None
+        }
+    }
+
+    /// Artifact type of a BDC section tagged `/Artifact`
+    fn artifact_type(&self, operands: &[Object], res: &ContentResources) -> Option<String> {
+        if operands.first().and_then(|o| o.as_name().ok()) != Some(b"Artifact") {
+            return None;
+        }
+        let properties = match operands.get(1) {
+            Some(Object::Name(name)) => res
+                .properties
+                .get(name)
+                .and_then(|o| resolve_dict(self.doc, o)),
+            Some(Object::Dictionary(dict)) => Some(dict),
+            _ => None,
+        };
+        let kind = properties.and_then(|p| {
+            p.get(b"Subtype")
+                .or_else(|_| p.get(b"Type"))
+                .and_then(Object::as_name)
+                .ok()
+        });
+        Some(kind.map_or_else(String::new, |k| String::from_utf8_lossy(k).to_string()))
+    }
+
+    /// Whether the current content is inside a hidden optional content section
+    fn in_hidden_content(&self) -> bool {
+        self.marked_content.iter().any(|section| !section.visible)
+    }
+
+    /// Artifact type of the innermost enclosing `/Artifact` section
+    fn current_artifact(&self) -> Option<String> {
+        self.marked_content
+            .iter()
+            .rev()
+            .find_map(|section| section.artifact.clone())
+    }
+
+    /// Whether an XObject or annotation with an optional /OC entry is visible
+    fn oc_entry_visible(&self, dict: &lopdf::Dictionary) -> bool {
+        match dict.get(b"OC") {
+            Ok(oc) => self.optional_content.is_visible(self.doc, oc),
+            Err(_) => true,
+        }
+    }
+
+    /// Translate the line matrix by (tx, ty) in text space and start a new line there
+    fn move_text_position(&mut self, tx: f32, ty: f32) {
+        let translation = [1.0, 0.0, 0.0, 1.0, tx, ty];
+        self.line_matrix = multiply_matrices(&translation, &self.line_matrix);
+        self.text_matrix = self.line_matrix;
+    }
+
+    /// Move to the start of the next line (T*)
+    fn next_line(&mut self) {
+        let leading = self.state.leading.unwrap_or(self.state.font_size * 1.2);
+        self.move_text_position(0.0, -leading);
+    }
+
+    /// Horizontal displacement (in text space) of a string shown with the current text state
+    fn string_advance(&self, bytes: &[u8], font_info: &FontWidthInfo) -> f32 {
+        let glyph_count = if font_info.is_cid {
+            bytes.len() / 2
+        } else {
+            bytes.len()
+        };
+        // Word spacing only applies to the single-byte code 32
+        let space_count = if font_info.is_cid {
+            0
+        } else {
+            bytes.iter().filter(|&&b| b == b' ').count()
+        };
+        (compute_string_width_ts(bytes, font_info, self.state.font_size)
+            + glyph_count as f32 * self.state.char_spacing
+            + space_count as f32 * self.state.word_spacing)
+            * self.state.horizontal_scale
+    }
+
+    /// Show a string (Tj, ', ") or an array of strings and adjustments (TJ)
+    fn show_text(&mut self, operand: &Object, res: &ContentResources) {
+        let elements = match operand {
+            Object::Array(array) => array.as_slice(),
+            other => std::slice::from_ref(other),
+        };
+        let font_size = self.state.font_size;
+        let font_info = res.font_widths.get(&self.state.font);
+
+        // Compute space threshold based on font metrics when available
+        let space_threshold = if let Some(fi) = font_info {
+            // Use 40% of the font's space width (in thousandths of text space)
+            let space_em = fi.space_width as f32 * fi.units_scale;
+            let threshold = space_em * 1000.0 * 0.4;
+            // Clamp to reasonable range: at least 80, at most 200
+            threshold.clamp(80.0, 200.0)
+        } else {
+            120.0 // fallback threshold
+        };
+
+        let mut combined_text = String::new();
+        let mut total_width_ts: f32 = 0.0;
+        for element in elements {
+            if let Some(adjust) = get_number(element) {
+                // Track displacement for total width
+                total_width_ts += -adjust / 1000.0 * font_size * self.state.horizontal_scale;
+                if adjust < -space_threshold
+                    && !combined_text.is_empty()
+                    && !combined_text.ends_with(' ')
+                {
+                    combined_text.push(' ');
+                }
+                continue;
+            }
+            if let (Some(fi), Some(raw_bytes)) = (font_info, get_operand_bytes(element)) {
+                total_width_ts += self.string_advance(raw_bytes, fi);
+            }
+            if let Some(text) = extract_text_from_operand(
+                element,
+                self.doc,
+                &res.fonts,
+                &self.state.font,
+                self.font_cmaps,
+                &res.font_base_names,
+                &res.font_tounicode_refs,
+                &res.font_encodings,
+            ) {
+                combined_text.push_str(&text);
+            }
+        }
+
+        if !combined_text.trim().is_empty() && !self.in_hidden_content() {
+            // Transform position through the text matrix and CTM
+            let combined = multiply_matrices(&self.text_matrix, &self.state.ctm);
+            let (x, y) = (combined[4], combined[5]);
+            let rendered_size = effective_font_size(font_size, &combined);
+            // Compute accurate width if font widths available
+            let width = if font_info.is_some() {
+                (total_width_ts * combined[0]).abs()
+            } else {
+                0.0
+            };
+            let text = expand_ligatures(&combined_text);
+            let horizontal = combined[1].abs() < 1e-3 && combined[0] > 0.0;
+            let clipped = match self.state.clip {
+                None => Some((text, x, width)),
+                Some(clip) if horizontal => clip_text_run(&clip, text, x, y, width, rendered_size),
+                Some(clip) => clip.contains_point(x, y, 1.0).then_some((text, x, width)),
+            };
+            if let Some((text, x, width)) = clipped {
+                // Detect bold/italic from font name
+                let base_font = res
+                    .font_base_names
+                    .get(&self.state.font)
+                    .map(|s| s.as_str())
+                    .unwrap_or(&self.state.font);
+                let backdrop = self.backdrop_under(x, y, width, rendered_size);
+                self.items.push(TextItem {
+                    text,
+                    x,
+                    y,
+                    width,
+                    height: rendered_size,
+                    font: self.state.font.clone(),
+                    font_size: rendered_size,
+                    page: self.page_num,
+                    page_label: None,
+                    is_bold: is_bold_font(base_font),
+                    is_italic: is_italic_font(base_font),
+                    item_type: ItemType::Text,
+                    annotation: self.annotation.clone(),
+                    markup: None,
+                    comments: Vec::new(),
+                    link: None,
+                    image: None,
+                    rotation: combined[1].atan2(combined[0]).to_degrees(),
+                    fill_color: self.state.fill_color,
+                    stroke_color: self.state.stroke_color,
+                    opacity: (self.state.fill_alpha < 1.0).then_some(self.state.fill_alpha),
+                    artifact: self.current_artifact(),
+                    hidden: self.hidden_reason(rendered_size, backdrop),
+                    backdrop,
+                });
+            }
+        }
+
+        // Advance text matrix by total width
+        if font_info.is_some() {
+            self.text_matrix[4] += total_width_ts * self.text_matrix[0];
+            self.text_matrix[5] += total_width_ts * self.text_matrix[1];
+        }
+    }
+
+    /// Handle the Do operator for an image or Form XObject
+    fn invoke_xobject(&mut self, name: &str, res: &ContentResources) {
+        if self.in_hidden_content() {
+            return;
+        }
+        let xobject_id = match res.xobjects.get(name) {
+            Some(XObjectType::Image(id) | XObjectType::Form(id)) => *id,
+            None => return,
+        };
+        if let Ok(Object::Stream(stream)) = self.doc.get_object(xobject_id) {
+            if !self.oc_entry_visible(&stream.dict) {
+                return;
+            }
+        }
+        match res.xobjects.get(name) {
+            Some(XObjectType::Image(_)) => {
+                // Images occupy the unit square in their own space; the bounding box
+                // of its transform also covers rotated and skewed placements
+                let bounds = Rect {
+                    x_min: 0.0,
+                    y_min: 0.0,
+                    x_max: 1.0,
+                    y_max: 1.0,
+                }
+                .transform(&self.state.ctm);
+                // Only the part of the image inside the clip is shown
+                let bounds = match self.state.clip {
+                    Some(clip) => clip.intersect(&bounds),
+                    None => bounds,
+                };
+                if bounds.x_min > bounds.x_max || bounds.y_min > bounds.y_max {
+                    return;
+                }
+                let image = match self.doc.get_object(xobject_id) {
+                    Ok(Object::Stream(stream)) => {
+                        Some(ImageInfo::from_stream(self.doc, name, xobject_id, stream))
+                    }
+                    _ => None,
+                };
+                self.items.push(TextItem {
+                    text: format!("[Image: {}]", name),
+                    x: bounds.x_min,
+                    y: bounds.y_min,
+                    width: bounds.x_max - bounds.x_min,
+                    height: bounds.y_max - bounds.y_min,
+                    font: String::new(),
+                    font_size: 0.0,
+                    page: self.page_num,
+                    page_label: None,
+                    is_bold: false,
+                    is_italic: false,
+                    item_type: ItemType::Image,
+                    annotation: self.annotation.clone(),
+                    markup: None,
+                    comments: Vec::new(),
+                    link: None,
+                    image,
+                    rotation: 0.0,
+                    fill_color: [0.0; 3],
+                    stroke_color: [0.0; 3],
+                    opacity: None,
+                    artifact: self.current_artifact(),
+                    hidden: None,
+                    backdrop: None,
+                });
+                // Image colors are unknown; text drawn over them counts as on a dark fill
+                self.backdrops.push((bounds, 0.0));
+            }
+            Some(XObjectType::Form(form_id)) => self.run_form(*form_id, res),
+            None => {}
+        }
+    }
+
+    /// Execute a Form XObject in a nested graphics state, applying its /Matrix
+    /// and clipping to its /BBox
+    fn run_form(&mut self, form_id: ObjectId, parent_res: &ContentResources) {
+        use lopdf::content::Content;
+
+        if self.form_stack.len() >= MAX_FORM_DEPTH || self.form_stack.contains(&form_id) {
+            return;
+        }
+        let doc = self.doc;
+        let Ok(Object::Stream(stream)) = doc.get_object(form_id) else {
+            return;
+        };
+        // Uncompressed streams have no filter to decode
+        let content_data = if stream.dict.has(b"Filter") {
+            match stream.decompressed_content() {
+                Ok(data) => data,
+                Err(_) => return,
+            }
+        } else {
+            stream.content.clone()
+        };
+        let Ok(content) = Content::decode(&content_data) else {
+            return;
+        };
+
+        let form_matrix = stream
+            .dict
+            .get(b"Matrix")
+            .ok()
+            .and_then(|o| resolve_array(doc, o))
+            .and_then(|a| matrix_from_objects(a))
+            .unwrap_or(IDENTITY_MATRIX);
+
+        // Forms run inside an implicit q/Q; text state carried in BT/ET does not leak out
+        let saved_state = self.state.clone();
+        let saved_stack_len = self.state_stack.len();
+        let saved_text = (self.text_matrix, self.line_matrix, self.in_text_block);
+        let saved_marked_len = self.marked_content.len();
+
+        self.state.ctm = multiply_matrices(&form_matrix, &self.state.ctm);
+        if let Some(bbox) = stream
+            .dict
+            .get(b"BBox")
+            .ok()
+            .and_then(|o| Rect::from_objects(doc, o))
+        {
+            let page_bbox = bbox.transform(&self.state.ctm);
+            self.state.clip = Some(match self.state.clip {
+                Some(clip) => clip.intersect(&page_bbox),
+                None => page_bbox,
+            });
+        }
+
+        self.form_stack.push(form_id);
+        match stream
+            .dict
+            .get(b"Resources")
+            .ok()
+            .and_then(|o| resolve_dict(doc, o))
+        {
+            Some(resources) => {
+                let form_res = ContentResources::from_dict(doc, resources);
+                self.execute(&content.operations, &form_res);
+            }
+            // Forms without their own resources use those of the invoking stream
+            None => self.execute(&content.operations, parent_res),
+        }
+        self.form_stack.pop();
+
+        self.state = saved_state;
+        self.state_stack.truncate(saved_stack_len);
+        self.marked_content.truncate(saved_marked_len);
+        (self.text_matrix, self.line_matrix, self.in_text_block) = saved_text;
+    }
+
+    /// Render the normal appearance streams of the page's printed annotations
+    /// (FreeText, stamps, filled form widgets, ...), placed on the annotation /Rect
+    fn run_annotation_appearances(&mut self, page_id: ObjectId, res: &ContentResources) {
+        let doc = self.doc;
+        let Ok(annotations) = doc.get_page_annotations(page_id) else {
+            return;
+        };
+
+        let page_state = std::mem::take(&mut self.state);
+        for annot in annotations {
+            let subtype = annot
+                .get(b"Subtype")
+                .and_then(Object::as_name)
+                .map(|n| String::from_utf8_lossy(n).to_string())
+                .unwrap_or_default();
+            // Links are reported by extract_page_links; popups only show their parent's text
+            if subtype == "Link"
+                || subtype == "Popup"
+                || !is_annotation_printed(annot)
+                || !self.oc_entry_visible(annot)
+            {
+                continue;
+            }
+            let Some(rect) = annot
+                .get(b"Rect")
+                .ok()
+                .and_then(|o| Rect::from_objects(doc, o))
+            else {
+                continue;
+            };
+            let Some(ap_id) = annotation_appearance(doc, annot) else {
+                continue;
+            };
+            let Ok(Object::Stream(appearance)) = doc.get_object(ap_id) else {
+                continue;
+            };
+            let Some(bbox) = appearance
+                .dict
+                .get(b"BBox")
+                .ok()
+                .and_then(|o| Rect::from_objects(doc, o))
+            else {
+                continue;
+            };
+            let matrix = appearance
+                .dict
+                .get(b"Matrix")
+                .ok()
+                .and_then(|o| resolve_array(doc, o))
+                .and_then(|a| matrix_from_objects(a))
+                .unwrap_or(IDENTITY_MATRIX);
+
+            // Map the transformed BBox onto the annotation rectangle (PDF 32000-1, 12.5.5);
+            // run_form then applies the appearance /Matrix on top of this
+            let placed = bbox.transform(&matrix);
+            let (box_w, box_h) = (placed.x_max - placed.x_min, placed.y_max - placed.y_min);
+            if box_w <= 0.0 || box_h <= 0.0 {
+                continue;
+            }
+            let sx = (rect.x_max - rect.x_min) / box_w;
+            let sy = (rect.y_max - rect.y_min) / box_h;
+            self.state = GraphicsState {
+                ctm: [
+                    sx,
+                    0.0,
+                    0.0,
+                    sy,
+                    rect.x_min - placed.x_min * sx,
+                    rect.y_min - placed.y_min * sy,
+                ],
+                ..GraphicsState::default()
+            };
+            self.annotation = Some(subtype);
+            self.run_form(ap_id, res);
+            self.annotation = None;
+        }
+        self.state = page_state;
+    }
+}
+
+/// Annotation flags (/F) that suppress on-screen display: Hidden (bit 2) and NoView (bit 6)
+const ANNOT_FLAGS_NOT_VIEWED: i64 = 0x02 | 0x20;
+
+/// Annotation flag (/F) Print (bit 3): the annotation is printed with the page
+const ANNOT_FLAG_PRINT: i64 = 0x04;
+
+/// Whether an annotation is shown when viewing the page
+fn is_annotation_visible(annot: &lopdf::Dictionary) -> bool {
+    let flags = annot.get(b"F").and_then(Object::as_i64).unwrap_or(0);
+    flags & ANNOT_FLAGS_NOT_VIEWED == 0
+}
+
+/// Whether an annotation's appearance is part of the page content: shown when
+/// viewing and printed with the page. Screen-only annotations (rollover
+/// widgets, unprinted notes) are left out.
+fn is_annotation_printed(annot: &lopdf::Dictionary) -> bool {
+    let flags = annot.get(b"F").and_then(Object::as_i64).unwrap_or(0);
+    is_annotation_visible(annot) && flags & ANNOT_FLAG_PRINT != 0
+}
+
+/// Object id of an annotation's normal appearance stream (/AP /N), selecting the
+/// current appearance state (/AS) when /N is a dictionary of states
+fn annotation_appearance(doc: &Document, annot: &lopdf::Dictionary) -> Option<ObjectId> {
+    let ap = annot.get(b"AP").ok().and_then(|o| resolve_dict(doc, o))?;
+    let normal = ap.get(b"N").ok()?;
+    let states = match normal {
+        Object::Reference(id) => match doc.get_object(*id).ok()? {
+            Object::Stream(_) => return Some(*id),
+            Object::Dictionary(dict) => dict,
+            _ => return None,
+        },
+        Object::Dictionary(dict) => dict,
+        _ => return None,
+    };
+    let state = annot.get(b"AS").and_then(Object::as_name).ok()?;
+    states.get(state).and_then(Object::as_reference).ok()
+}
+
+/// Extract text items from a single page, and its vector graphics when
+/// `capture_graphics` is set
+fn extract_page_content(
+    doc: &Document,
+    page_id: ObjectId,
+    page_num: u32,
+    font_cmaps: &FontCMaps,
+    optional_content: &OptionalContent,
+    capture_graphics: bool,
+) -> Result<(Vec<TextItem>, Vec<GraphicItem>), PdfError> {
+    use lopdf::content::Content;
+
+    let resources = ContentResources::for_page(doc, page_id);
+
+    // Get content
+    let content_data = doc
+        .get_page_content(page_id)
+        .map_err(|e| PdfError::Parse(e.to_string()))?;
+
+    let content = Content::decode(&content_data).map_err(|e| PdfError::Parse(e.to_string()))?;
+
+    let mut interpreter = ContentInterpreter::new(doc, font_cmaps, optional_content, page_num);
+    interpreter.capture_graphics = capture_graphics;
+    interpreter.execute(&content.operations, &resources);
+    interpreter.run_annotation_appearances(page_id, &resources);
+
+    let mut items = interpreter.items;
+    let crop_box = inherited_page_box(doc, page_id, b"CropBox")
+        .or_else(|| inherited_page_box(doc, page_id, b"MediaBox"));
+    flag_hidden_placement(&mut items, crop_box);
+    Ok((items, interpreter.graphics))
+}
+
+/// Image items for the images a page's content draws, in drawing order.
+/// Images in hidden optional content are left out, as in text extraction.
+pub(crate) fn page_image_items(doc: &Document, page_id: ObjectId, page_num: u32) -> Vec<TextItem> {
+    let optional_content = OptionalContent::new(doc, &LayerSelection::Default);
+    match extract_page_content(
+        doc,
+        page_id,
+        page_num,
+        &FontCMaps::default(),
+        &optional_content,
+        false,
+    ) {
+        Ok((items, _)) => items
+            .into_iter()
+            .filter(|item| item.item_type == ItemType::Image)
+            .collect(),
+        Err(e) => {
+            log::debug!("failed to read images on page {}: {}", page_num, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Flag text outside the page's visible area, or covered by an image drawn later
+fn flag_hidden_placement(items: &mut [TextItem], crop_box: Option<Rect>) {
+    let images: Vec<(usize, Rect)> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item.item_type == ItemType::Image)
+        .map(|(idx, item)| {
+            let bounds = Rect {
+                x_min: item.x,
+                y_min: item.y,
+                x_max: item.x + item.width,
+                y_max: item.y + item.height,
+            };
+            (idx, bounds)
+        })
+        .collect();
+
+    for (idx, item) in items.iter_mut().enumerate() {
+        if item.item_type != ItemType::Text || item.hidden.is_some() {
+            continue;
+        }
+        let (x_start, x_end, y) = (item.x, item.x + item.width, item.y);
+        if crop_box.is_some_and(|crop| {
+            !crop.contains_point(x_start, y, 1.0) && !crop.contains_point(x_end, y, 1.0)
+        }) {
+            item.hidden = Some(HiddenReason::OutsideCropBox);
+        } else if images.iter().any(|(image_idx, bounds)| {
+            *image_idx > idx
+                && bounds.contains_point(x_start, y, 0.0)
+                && bounds.contains_point(x_end, y + item.font_size * 0.5, 0.0)
+        }) {
+            item.hidden = Some(HiddenReason::BehindImage);
+        }
+    }
+}
+
+/// Helper to get f32 from Object
+pub(crate) fn get_number(obj: &Object) -> Option<f32> {
+    match obj {
+        Object::Integer(i) => Some(*i as f32),
+        Object::Real(r) => Some(*r),
+        _ => None,
+    }
+}
+
+/// XObject info - either Image or Form
+#[derive(Debug)]
+enum XObjectType {
+    Image(ObjectId),
+    Form(ObjectId),
+}
+
+/// Collect XObjects from a resource dictionary, categorized by type
+fn collect_xobjects(
+    doc: &Document,
+    resources: &lopdf::Dictionary,
+    xobject_types: &mut HashMap<String, XObjectType>,
+) {
+    let Some(xobjects) = resources
+        .get(b"XObject")
+        .ok()
+        .and_then(|o| resolve_dict(doc, o))
+    else {
+        return;
+    };
+
+    for (name, value) in xobjects.iter() {
+        let name_str = String::from_utf8_lossy(name).to_string();
+
+        // Check XObject subtype
+        if let Ok(obj_ref) = value.as_reference() {
+            if let Ok(Object::Stream(stream)) = doc.get_object(obj_ref) {
+                match stream.dict.get(b"Subtype").and_then(Object::as_name) {
+                    Ok(b"Image") => {
+                        xobject_types.insert(name_str, XObjectType::Image(obj_ref));
+                    }
+                    Ok(b"Form") => {
+                        xobject_types.insert(name_str, XObjectType::Form(obj_ref));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Collect the entries of a named resource category (/Properties, /ColorSpace)
+fn collect_resource_entries<'a>(
+    doc: &'a Document,
+    resources: &'a lopdf::Dictionary,
+    category: &[u8],
+    entries: &mut HashMap<Vec<u8>, &'a Object>,
+) {
+    if let Some(dict) = resources
+        .get(category)
+        .ok()
+        .and_then(|o| resolve_dict(doc, o))
+    {
+        for (name, value) in dict.iter() {
+            entries.insert(name.clone(), value);
+        }
+    }
+}
+
+/// Collect the graphics state parameter dictionaries of a resource dictionary
+fn collect_ext_gstates<'a>(
+    doc: &'a Document,
+    resources: &'a lopdf::Dictionary,
+    ext_gstates: &mut HashMap<Vec<u8>, &'a lopdf::Dictionary>,
+) {
+    if let Some(dict) = resources
+        .get(b"ExtGState")
+        .ok()
+        .and_then(|o| resolve_dict(doc, o))
+    {
+        for (name, value) in dict.iter() {
+            if let Some(gs) = resolve_dict(doc, value) {
+                ext_gstates.insert(name.clone(), gs);
+            }
+        }
+    }
+}
+
+/// A position inside the document that internal links point at
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Destination {
+    /// Page number (1-indexed)
+    page: u32,
+    /// Top of the destination view in PDF coordinates, when the destination has one
+    y: Option<f32>,
+}
+
+impl Destination {
+    /// Markdown anchor id for this destination
+    fn anchor(&self) -> String {
+        match self.y {
+            Some(y) => format!("page-{}-{}", self.page, y.round() as i32),
+            None => format!("page-{}", self.page),
+        }
+    }
+}
+
+/// Maximum depth of a name tree walked while collecting named destinations
+const MAX_NAME_TREE_DEPTH: usize = 32;
+
+/// Resolves link annotation targets: external URIs, explicit destinations,
+/// named destinations and GoTo/GoToR actions
+struct LinkResolver<'a> {
+    doc: &'a Document,
+    page_numbers: HashMap<ObjectId, u32>,
+    named_dests: HashMap<Vec<u8>, &'a Object>,
+}
+
+impl<'a> LinkResolver<'a> {
+    fn new(doc: &'a Document) -> Self {
+        let page_numbers = doc.get_pages().into_iter().map(|(n, id)| (id, n)).collect();
+
+        let mut named_dests = HashMap::new();
+        if let Ok(catalog) = doc.catalog() {
+            // Legacy PDF 1.1 /Dests dictionary, keyed by name
+            if let Some(dests) = catalog
+                .get(b"Dests")
+                .ok()
+                .and_then(|o| resolve_dict(doc, o))
+            {
+                for (name, value) in dests.iter() {
+                    named_dests.insert(name.clone(), value);
+                }
+            }
+            // /Names /Dests name tree, keyed by string
+            if let Some(tree) = catalog
+                .get(b"Names")
+                .ok()
+                .and_then(|o| resolve_dict(doc, o))
+                .and_then(|names| names.get(b"Dests").ok())
+                .and_then(|o| resolve_dict(doc, o))
+            {
+                collect_name_tree(doc, tree, &mut named_dests, 0);
+            }
+        }
+
+        LinkResolver {
+            doc,
+            page_numbers,
+            named_dests,
+        }
+    }
+
+    /// Link target of a link annotation: a URL for external targets, or an
+    /// internal destination
+    fn resolve(&self, annot: &lopdf::Dictionary) -> Option<(String, Option<Destination>)> {
+        if let Some(action) = annot.get(b"A").ok().and_then(|o| resolve_dict(self.doc, o)) {
+            return self.resolve_action(action);
+        }
+        let dest = self.destination(annot.get(b"Dest").ok()?)?;
+        Some((format!("#{}", dest.anchor()), Some(dest)))
+    }
+
+    fn resolve_action(&self, action: &lopdf::Dictionary) -> Option<(String, Option<Destination>)> {
+        match action.get(b"S").and_then(Object::as_name).ok()? {
+            b"URI" => {
+                let uri = action.get(b"URI").and_then(Object::as_str).ok()?;
+                Some((String::from_utf8_lossy(uri).to_string(), None))
+            }
+            b"GoTo" => {
+                let dest = self.destination(action.get(b"D").ok()?)?;
+                Some((format!("#{}", dest.anchor()), Some(dest)))
+            }
+            b"GoToR" => {
+                // Destinations in another file use PDF open parameters
+                let file = self.file_spec(action.get(b"F").ok()?)?;
+                let fragment = match action.get(b"D").ok().map(|o| self.deref(o)) {
+                    Some(Object::Array(array)) => array
+                        .first()
+                        .and_then(|o| o.as_i64().ok())
+                        .map(|index| format!("#page={}", index + 1)),
+                    Some(Object::Name(name)) | Some(Object::String(name, _)) => {
+                        Some(format!("#nameddest={}", String::from_utf8_lossy(name)))
+                    }
+                    _ => None,
+                };
+                Some((format!("{}{}", file, fragment.unwrap_or_default()), None))
+            }
+            _ => None,
+        }
+    }
+
+    /// Resolve an explicit destination array, or a name or string naming one
+    fn destination(&self, obj: &Object) -> Option<Destination> {
+        let array = match self.deref(obj) {
+            Object::Name(name) | Object::String(name, _) => {
+                self.destination_array(self.named_dests.get(name)?)?
+            }
+            other => self.destination_array(other)?,
+        };
+
+        let page = match array.first()? {
+            Object::Reference(id) => *self.page_numbers.get(id)?,
+            // Some writers use a 0-based page index instead of a page reference
+            Object::Integer(index) => u32::try_from(*index).ok()? + 1,
+            _ => return None,
+        };
+        let kind = array.get(1).and_then(|o| o.as_name().ok());
+        let top_index = match kind {
+            Some(b"XYZ") => Some(3),
+            Some(b"FitH") | Some(b"FitBH") => Some(2),
+            Some(b"FitR") => Some(5),
+            _ => None,
+        };
+        let y = top_index
+            .and_then(|i| array.get(i))
+            .and_then(|o| get_number(self.deref(o)));
+        Some(Destination { page, y })
+    }
+
+    /// A destination value: an array, or a dictionary holding it under /D
+    fn destination_array(&self, obj: &'a Object) -> Option<&'a Vec<Object>> {
+        match self.deref(obj) {
+            Object::Array(array) => Some(array),
+            Object::Dictionary(dict) => match self.deref(dict.get(b"D").ok()?) {
+                Object::Array(array) => Some(array),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// File name from a file specification string or dictionary
+    fn file_spec(&self, obj: &Object) -> Option<String> {
+        match self.deref(obj) {
+            Object::Dictionary(dict) => dict
+                .get(b"UF")
+                .or_else(|_| dict.get(b"F"))
+                .ok()
+                .and_then(|o| decode_text_string(self.doc, o)),
+            other => decode_text_string(self.doc, other),
+        }
+    }
+
+    fn deref<'o>(&self, obj: &'o Object) -> &'o Object
+    where
+        'a: 'o,
+    {
+        match obj {
+            Object::Reference(id) => self.doc.get_object(*id).unwrap_or(obj),
+            other => other,
+        }
+    }
+}
+
+/// Collect the key/value pairs of a name tree (PDF 32000-1, 7.9.6)
+pub(crate) fn collect_name_tree<'a>(
+    doc: &'a Document,
+    node: &'a lopdf::Dictionary,
+    entries: &mut HashMap<Vec<u8>, &'a Object>,
+    depth: usize,
+) {
+    if depth >= MAX_NAME_TREE_DEPTH {
+        return;
+    }
+    if let Some(names) = node.get(b"Names").ok().and_then(|o| resolve_array(doc, o)) {
+        for pair in names.chunks_exact(2) {
+            if let Ok(key) = pair[0].as_str() {
+                entries.insert(key.to_vec(), &pair[1]);
+            }
+        }
+    }
+    if let Some(kids) = node.get(b"Kids").ok().and_then(|o| resolve_array(doc, o)) {
+        for kid in kids {
+            if let Some(kid) = resolve_dict(doc, kid) {
+                collect_name_tree(doc, kid, entries, depth + 1);
+            }
+        }
+    }
+}
+
+/// Top edge of a page's MediaBox
+fn page_top(doc: &Document, page_id: ObjectId) -> f32 {
+    inherited_page_box(doc, page_id, b"MediaBox").map_or(792.0, |media_box| media_box.y_max)
+    // US Letter
+}
+
+/// A page boundary box (/MediaBox, /CropBox), following inheritance through the page tree
+fn inherited_page_box(doc: &Document, page_id: ObjectId, key: &[u8]) -> Option<Rect> {
+    let mut node = doc.get_dictionary(page_id).ok();
+    for _ in 0..MAX_NAME_TREE_DEPTH {
+        let dict = node?;
+        if let Some(page_box) = dict.get(key).ok().and_then(|o| Rect::from_objects(doc, o)) {
+            return Some(page_box);
+        }
+        node = dict
+            .get(b"Parent")
+            .and_then(Object::as_reference)
+            .ok()
+            .and_then(|id| doc.get_dictionary(id).ok());
+    }
+    None
+}
+
+/// Anchor items marking the destinations of internal links, so markdown can
+/// emit anchors for `[text](#anchor)` links to point at
+fn destination_anchor_items(doc: &Document, destinations: &[Destination]) -> Vec<TextItem> {
+    let pages = doc.get_pages();
+    let mut seen = std::collections::HashSet::new();
+    destinations
+        .iter()
+        .filter(|dest| seen.insert(dest.anchor()))
+        .filter_map(|dest| {
+            let page_id = pages.get(&dest.page)?;
+            Some(TextItem {
+                text: dest.anchor(),
+                y: dest.y.unwrap_or_else(|| page_top(doc, *page_id)),
+                page: dest.page,
+                item_type: ItemType::Anchor(dest.anchor()),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// A link annotation with its target and the page regions it covers
+struct PageLink {
+    /// External URL, or `#anchor` for internal destinations
+    url: String,
+    /// Resolved target of internal links
+    destination: Option<Destination>,
+    rect: Rect,
+    regions: Vec<Rect>,
+}
+
+/// Collect the page's link annotations that have a resolvable target
+fn collect_page_links(resolver: &LinkResolver, page_id: ObjectId) -> Vec<PageLink> {
+    let doc = resolver.doc;
+    let Ok(annots) = doc.get_page_annotations(page_id) else {
+        return Vec::new();
+    };
+
+    annots
+        .into_iter()
+        .filter(|annot| annot.get(b"Subtype").and_then(Object::as_name).ok() == Some(b"Link"))
+        .filter_map(|annot| {
+            let rect = Rect::from_objects(doc, annot.get(b"Rect").ok()?)?;
+            let (url, destination) = resolver.resolve(annot)?;
+            let quads = parse_quad_points(doc, annot);
+            let regions = if quads.is_empty() {
+                vec![rect]
+            } else {
+                quads.iter().map(Rect::from_quad).collect()
+            };
+            Some(PageLink {
+                url,
+                destination,
+                rect,
+                regions,
+            })
+        })
+        .collect()
+}
+
+/// Standalone item for a link annotation, positioned at its rectangle
+fn link_item(link: &PageLink, page_num: u32) -> TextItem {
+    TextItem {
+        text: link.url.clone(),
+        x: link.rect.x_min,
+        y: link.rect.y_min,
+        width: link.rect.x_max - link.rect.x_min,
+        height: link.rect.y_max - link.rect.y_min,
+        page: page_num,
+        item_type: ItemType::Link(link.url.clone()),
+        ..Default::default()
+    }
+}
+
+/// Extract hyperlinks from page annotations
+pub fn extract_page_links(doc: &Document, page_id: ObjectId, page_num: u32) -> Vec<TextItem> {
+    collect_page_links(&LinkResolver::new(doc), page_id)
+        .iter()
+        .map(|link| link_item(link, page_num))
+        .collect()
+}
+
+/// Attach links to the anchor text under their /Rect or /QuadPoints: the covered
+/// text is split into its own items tagged with [`TextItem::link`]. Returns
+/// standalone link items for links with no text underneath.
+fn attach_page_links(
+    items: &mut Vec<TextItem>,
+    links: &[PageLink],
+    page_num: u32,
+) -> Vec<TextItem> {
+    let mut standalone = Vec::new();
+    for link in links {
+        let covered = split_covered_items(items, page_num, &link.regions);
+        if covered.is_empty() {
+            standalone.push(link_item(link, page_num));
+            continue;
+        }
+        for idx in covered {
+            // Overlapping links: the first one listed keeps the text
+            if items[idx].link.is_none() {
+                items[idx].link = Some(link.url.clone());
+            }
+        }
+    }
+    standalone
+}
+
+/// Kind of review annotation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationKind {
+    /// Sticky note comment
+    Text,
+    /// Highlighted text
+    Highlight,
+    /// Underlined text
+    Underline,
+    /// Struck-out text
+    StrikeOut,
+    /// Squiggly-underlined text
+    Squiggly,
+    /// Insertion mark
+    Caret,
+    /// Freehand drawing
+    Ink,
+}
+
+impl AnnotationKind {
+    fn from_subtype(subtype: &[u8]) -> Option<Self> {
+        match subtype {
+            b"Text" => Some(AnnotationKind::Text),
+            b"Highlight" => Some(AnnotationKind::Highlight),
+            b"Underline" => Some(AnnotationKind::Underline),
+            b"StrikeOut" => Some(AnnotationKind::StrikeOut),
+            b"Squiggly" => Some(AnnotationKind::Squiggly),
+            b"Caret" => Some(AnnotationKind::Caret),
+            b"Ink" => Some(AnnotationKind::Ink),
+            _ => None,
+        }
+    }
+
+    /// Whether this kind marks up the text under its QuadPoints
+    pub fn is_text_markup(self) -> bool {
+        matches!(
+            self,
+            AnnotationKind::Highlight
+                | AnnotationKind::Underline
+                | AnnotationKind::StrikeOut
+                | AnnotationKind::Squiggly
+        )
+    }
+}
+
+/// A review annotation (comment or text markup) on a page
+#[derive(Debug, Clone)]
+pub struct Annotation {
+    /// 1-based position of the annotation in document order
+    pub id: usize,
+    /// Kind of annotation
+    pub kind: AnnotationKind,
+    /// Page number (1-indexed)
+    pub page: u32,
+    /// X position of the annotation rectangle
+    pub x: f32,
+    /// Y position of the annotation rectangle (bottom edge)
+    pub y: f32,
+    /// Width of the annotation rectangle
+    pub width: f32,
+    /// Height of the annotation rectangle
+    pub height: f32,
+    /// Comment text (/Contents)
+    pub contents: Option<String>,
+    /// Author (/T)
+    pub author: Option<String>,
+    /// Last modification date (/M), as the raw PDF date string
+    pub modified: Option<String>,
+    /// Marked regions as [x1 y1 x2 y2 x3 y3 x4 y4] quadrilaterals (/QuadPoints)
+    pub quad_points: Vec<[f32; 8]>,
+    /// Extracted text covered by the annotation
+    pub text: String,
 }
 
-/// Helper to get f32 from Object
-fn get_number(obj: &Object) -> Option<f32> {
-    match obj {
-        Object::Integer(i) => Some(*i as f32),
-        Object::Real(r) => Some(*r),
-        _ => None,
+impl Annotation {
+    /// Page-space regions whose text the annotation covers
+    fn regions(&self) -> Vec<Rect> {
+        if !self.quad_points.is_empty() {
+            return self.quad_points.iter().map(Rect::from_quad).collect();
+        }
+        match self.kind {
+            AnnotationKind::Text | AnnotationKind::Caret => Vec::new(),
+            _ => vec![Rect {
+                x_min: self.x,
+                y_min: self.y,
+                x_max: self.x + self.width,
+                y_max: self.y + self.height,
+            }],
+        }
     }
 }
 
-/// Get XObject names that are images from page resources
-/// XObject info - either Image or Form
-#[derive(Debug)]
-enum XObjectType {
-    Image,
-    Form(ObjectId),
+/// A review comment attached to the text it refers to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    /// Id of the annotation the comment comes from
+    pub id: usize,
+    /// Author of the comment
+    pub author: Option<String>,
+    /// Comment text
+    pub text: String,
 }
 
-/// Get XObjects from page resources, categorized by type
-fn get_page_xobjects(
-    doc: &Document,
-    page_id: ObjectId,
-) -> std::collections::HashMap<String, XObjectType> {
-    let mut xobject_types = std::collections::HashMap::new();
-
-    // Try to get the page dictionary
-    if let Ok(page_dict) = doc.get_dictionary(page_id) {
-        // Get Resources dictionary
-        let resources = if let Ok(res_ref) = page_dict.get(b"Resources") {
-            if let Ok(obj_ref) = res_ref.as_reference() {
-                doc.get_dictionary(obj_ref).ok()
-            } else {
-                res_ref.as_dict().ok()
-            }
-        } else {
-            None
-        };
-
-        if let Some(resources) = resources {
-            // Get XObject dictionary from Resources
-            if let Ok(xobjects_ref) = resources.get(b"XObject") {
-                let xobjects = if let Ok(obj_ref) = xobjects_ref.as_reference() {
-                    doc.get_dictionary(obj_ref).ok()
-                } else {
-                    xobjects_ref.as_dict().ok()
-                };
+/// Extract review annotations with the text they cover from a PDF file
+pub fn extract_annotations<P: AsRef<Path>>(path: P) -> Result<Vec<Annotation>, PdfError> {
+    let buffer = std::fs::read(path.as_ref())?;
+    extract_annotations_mem(&buffer)
+}
 
-                if let Some(xobjects) = xobjects {
-                    for (name, value) in xobjects.iter() {
-                        let name_str = String::from_utf8_lossy(name).to_string();
-
-                        // Check XObject subtype
-                        if let Ok(obj_ref) = value.as_reference() {
-                            if let Ok(Object::Stream(stream)) = doc.get_object(obj_ref) {
-                                if let Ok(subtype) = stream.dict.get(b"Subtype") {
-                                    if let Ok(subtype_name) = subtype.as_name() {
-                                        if subtype_name == b"Image" {
-                                            xobject_types.insert(name_str, XObjectType::Image);
-                                        } else if subtype_name == b"Form" {
-                                            xobject_types
-                                                .insert(name_str, XObjectType::Form(obj_ref));
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
+/// Extract review annotations with the text they cover from a PDF in memory
+pub fn extract_annotations_mem(buffer: &[u8]) -> Result<Vec<Annotation>, PdfError> {
+    let font_cmaps = FontCMaps::from_pdf_bytes(buffer);
 
-    xobject_types
+    let doc = Document::load_mem(buffer)?;
+    let (_, annotations, _) =
+        extract_items_and_annotations(&doc, &font_cmaps, &LayerSelection::Default, false)?;
+    Ok(annotations)
 }
 
-/// Extract text items from a Form XObject
-fn extract_form_xobject_text(
+/// Extract review annotations (comments, highlights, underlines, strikeouts,
+/// carets and ink) from a page. Covered text is filled in by [`apply_annotations`];
+/// ids are 1-based within the page.
+pub fn extract_page_annotations(
     doc: &Document,
-    form_id: ObjectId,
+    page_id: ObjectId,
     page_num: u32,
-    font_cmaps: &FontCMaps,
-    parent_ctm: &[f32; 6],
-) -> Vec<TextItem> {
-    use lopdf::content::Content;
-
-    let mut items = Vec::new();
-
-    // Get the Form XObject stream
-    let Ok(Object::Stream(stream)) = doc.get_object(form_id) else {
-        return items;
+) -> Vec<Annotation> {
+    let mut annotations = Vec::new();
+    let Ok(annots) = doc.get_page_annotations(page_id) else {
+        return annotations;
     };
 
-    // Decompress the content stream
-    let Ok(content_data) = stream.decompressed_content() else {
-        return items;
-    };
+    for annot in annots {
+        let Some(kind) = annot
+            .get(b"Subtype")
+            .and_then(Object::as_name)
+            .ok()
+            .and_then(AnnotationKind::from_subtype)
+        else {
+            continue;
+        };
+        if !is_annotation_visible(annot) {
+            continue;
+        }
+        let Some(rect) = annot
+            .get(b"Rect")
+            .ok()
+            .and_then(|o| Rect::from_objects(doc, o))
+        else {
+            continue;
+        };
 
-    // Decode the content stream
-    let Ok(content) = Content::decode(&content_data) else {
-        return items;
-    };
+        let quad_points = parse_quad_points(doc, annot);
 
-    // Get fonts from the Form's Resources
-    let form_fonts = get_form_fonts(doc, &stream.dict);
-    let font_encodings = build_font_encodings(doc, &form_fonts);
+        let text_entry = |key: &[u8]| {
+            annot
+                .get(key)
+                .ok()
+                .and_then(|o| decode_text_string(doc, o))
+                .filter(|s| !s.trim().is_empty())
+        };
 
-    // Build font width info for the form
-    let font_widths = build_font_widths(doc, &form_fonts);
+        annotations.push(Annotation {
+            id: annotations.len() + 1,
+            kind,
+            page: page_num,
+            x: rect.x_min,
+            y: rect.y_min,
+            width: rect.x_max - rect.x_min,
+            height: rect.y_max - rect.y_min,
+            contents: text_entry(b"Contents"),
+            author: text_entry(b"T"),
+            modified: text_entry(b"M"),
+            quad_points,
+            text: String::new(),
+        });
+    }
 
-    // Build font base names and ToUnicode refs for the form
-    let mut font_base_names: std::collections::HashMap<String, String> =
-        std::collections::HashMap::new();
-    let mut font_tounicode_refs: std::collections::HashMap<String, u32> =
-        std::collections::HashMap::new();
+    annotations
+}
 
-    for (font_name, font_dict) in &form_fonts {
-        let resource_name = String::from_utf8_lossy(font_name).to_string();
-        if let Ok(base_font) = font_dict.get(b"BaseFont") {
-            if let Ok(name) = base_font.as_name() {
-                let base_name = String::from_utf8_lossy(name).to_string();
-                font_base_names.insert(resource_name.clone(), base_name);
+/// Resolve the text each annotation covers and apply it to the extracted items:
+/// text under markup annotations is split out into its own items tagged with
+/// [`TextItem::markup`], and annotation contents are attached as [`Comment`]s to
+/// the covered text (or, for notes and carets, the nearest text).
+pub fn apply_annotations(items: &mut Vec<TextItem>, annotations: &mut [Annotation]) {
+    for annot in annotations.iter_mut() {
+        let covered = split_covered_items(items, annot.page, &annot.regions());
+
+        if annot.kind.is_text_markup() {
+            for &idx in &covered {
+                items[idx].markup = Some(annot.kind);
             }
         }
-        if let Ok(tounicode) = font_dict.get(b"ToUnicode") {
-            if let Ok(obj_ref) = tounicode.as_reference() {
-                font_tounicode_refs.insert(resource_name, obj_ref.0);
+        annot.text = join_covered_text(items, &covered);
+
+        if let Some(contents) = &annot.contents {
+            let target = covered
+                .iter()
+                .copied()
+                .max_by(|&a, &b| {
+                    // Last covered item in reading order
+                    items[b]
+                        .y
+                        .partial_cmp(&items[a].y)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                        .then(
+                            items[a]
+                                .x
+                                .partial_cmp(&items[b].x)
+                                .unwrap_or(std::cmp::Ordering::Equal),
+                        )
+                })
+                .or_else(|| nearest_text_item(items, annot));
+            if let Some(idx) = target {
+                items[idx].comments.push(Comment {
+                    id: annot.id,
+                    author: annot.author.clone(),
+                    text: contents.split_whitespace().collect::<Vec<_>>().join(" "),
+                });
             }
         }
     }
+}
 
-    // Process the content stream
-    let mut current_font = String::new();
-    let mut current_font_size: f32 = 12.0;
-    let mut text_matrix = [1.0f32, 0.0, 0.0, 1.0, 0.0, 0.0];
-    let mut in_text_block = false;
-
-    for op in &content.operations {
-        match op.operator.as_str() {
-            "BT" => {
-                in_text_block = true;
-                text_matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
-            }
-            "ET" => {
-                in_text_block = false;
-            }
-            "Tf" => {
-                if op.operands.len() >= 2 {
-                    if let Ok(name) = op.operands[0].as_name() {
-                        current_font = String::from_utf8_lossy(name).to_string();
-                    }
-                    current_font_size = get_number(&op.operands[1]).unwrap_or(12.0);
-                }
-            }
-            "Td" | "TD" => {
-                if op.operands.len() >= 2 {
-                    let tx = get_number(&op.operands[0]).unwrap_or(0.0);
-                    let ty = get_number(&op.operands[1]).unwrap_or(0.0);
-                    text_matrix[4] += tx;
-                    text_matrix[5] += ty;
-                }
-            }
-            "Tm" => {
-                if op.operands.len() >= 6 {
-                    for (i, operand) in op.operands.iter().take(6).enumerate() {
-                        text_matrix[i] =
-                            get_number(operand).unwrap_or(if i == 0 || i == 3 { 1.0 } else { 0.0 });
-                    }
-                }
-            }
-            "Tj" => {
-                if in_text_block && !op.operands.is_empty() {
-                    if let Some(text) = extract_text_from_operand(
-                        &op.operands[0],
-                        doc,
-                        &form_fonts,
-                        &current_font,
-                        font_cmaps,
-                        &font_base_names,
-                        &font_tounicode_refs,
-                        &font_encodings,
-                    ) {
-                        if !text.trim().is_empty() {
-                            let rendered_size =
-                                effective_font_size(current_font_size, &text_matrix);
-                            let combined = multiply_matrices(&text_matrix, parent_ctm);
-                            let (x, y) = (combined[4], combined[5]);
-                            // Compute width from font widths if available
-                            let width = if let Some(font_info) = font_widths.get(&current_font) {
-                                if let Some(raw_bytes) = get_operand_bytes(&op.operands[0]) {
-                                    let w_ts = compute_string_width_ts(
-                                        raw_bytes,
-                                        font_info,
-                                        current_font_size,
-                                    );
-                                    text_matrix[4] += w_ts * text_matrix[0];
-                                    text_matrix[5] += w_ts * text_matrix[1];
-                                    (w_ts
-                                        * (text_matrix[0] * parent_ctm[0]
-                                            + text_matrix[1] * parent_ctm[2]))
-                                        .abs()
-                                } else {
-                                    0.0
-                                }
-                            } else {
-                                0.0
-                            };
-                            let base_font = font_base_names
-                                .get(&current_font)
-                                .map(|s| s.as_str())
-                                .unwrap_or(&current_font);
-                            items.push(TextItem {
-                                text: expand_ligatures(&text),
-                                x,
-                                y,
-                                width,
-                                height: rendered_size,
-                                font: current_font.clone(),
-                                font_size: rendered_size,
-                                page: page_num,
-                                is_bold: is_bold_font(base_font),
-                                is_italic: is_italic_font(base_font),
-                                item_type: ItemType::Text,
-                            });
-                        }
-                    }
-                }
-            }
-            "TJ" => {
-                if in_text_block && !op.operands.is_empty() {
-                    if let Ok(array) = op.operands[0].as_array() {
-                        let font_info = font_widths.get(&current_font);
-
-                        // Compute space threshold based on font metrics when available
-                        let space_threshold = if let Some(fi) = font_info {
-                            let space_em = fi.space_width as f32 * fi.units_scale;
-                            let threshold = space_em * 1000.0 * 0.4;
-                            threshold.clamp(80.0, 200.0)
-                        } else {
-                            120.0
-                        };
+/// Split the page's text items at the region boundaries and return the indices of
+/// the items that now lie inside the regions
+fn split_covered_items(items: &mut Vec<TextItem>, page: u32, regions: &[Rect]) -> Vec<usize> {
+    let mut covered: Vec<usize> = Vec::new();
 
-                        let mut combined_text = String::new();
-                        let mut total_width_ts: f32 = 0.0;
-                        for element in array {
-                            match element {
-                                Object::Integer(n) => {
-                                    let n_val = *n as f32;
-                                    total_width_ts += -n_val / 1000.0 * current_font_size;
-                                    if n_val < -space_threshold
-                                        && !combined_text.is_empty()
-                                        && !combined_text.ends_with(' ')
-                                    {
-                                        combined_text.push(' ');
-                                    }
-                                    continue;
-                                }
-                                Object::Real(n) => {
-                                    total_width_ts += -(*n) / 1000.0 * current_font_size;
-                                    if *n < -space_threshold
-                                        && !combined_text.is_empty()
-                                        && !combined_text.ends_with(' ')
-                                    {
-                                        combined_text.push(' ');
-                                    }
-                                    continue;
-                                }
-                                _ => {}
-                            }
-                            if let Some(fi) = font_info {
-                                if let Some(raw_bytes) = get_operand_bytes(element) {
-                                    total_width_ts +=
-                                        compute_string_width_ts(raw_bytes, fi, current_font_size);
-                                }
-                            }
-                            if let Some(text) = extract_text_from_operand(
-                                element,
-                                doc,
-                                &form_fonts,
-                                &current_font,
-                                font_cmaps,
-                                &font_base_names,
-                                &font_tounicode_refs,
-                                &font_encodings,
-                            ) {
-                                combined_text.push_str(&text);
-                            }
-                        }
-                        if !combined_text.trim().is_empty() {
-                            let rendered_size =
-                                effective_font_size(current_font_size, &text_matrix);
-                            let combined_mat = multiply_matrices(&text_matrix, parent_ctm);
-                            let (x, y) = (combined_mat[4], combined_mat[5]);
-                            let width = if font_info.is_some() {
-                                (total_width_ts
-                                    * (text_matrix[0] * parent_ctm[0]
-                                        + text_matrix[1] * parent_ctm[2]))
-                                    .abs()
-                            } else {
-                                0.0
-                            };
-                            let base_font = font_base_names
-                                .get(&current_font)
-                                .map(|s| s.as_str())
-                                .unwrap_or(&current_font);
-                            items.push(TextItem {
-                                text: expand_ligatures(&combined_text),
-                                x,
-                                y,
-                                width,
-                                height: rendered_size,
-                                font: current_font.clone(),
-                                font_size: rendered_size,
-                                page: page_num,
-                                is_bold: is_bold_font(base_font),
-                                is_italic: is_italic_font(base_font),
-                                item_type: ItemType::Text,
-                            });
-                            if font_info.is_some() {
-                                text_matrix[4] += total_width_ts * text_matrix[0];
-                                text_matrix[5] += total_width_ts * text_matrix[1];
-                            }
-                        }
-                    }
-                }
-            }
-            _ => {}
+    let mut i = 0;
+    while i < items.len() && !regions.is_empty() {
+        let item = &items[i];
+        if item.page != page || item.item_type != ItemType::Text {
+            i += 1;
+            continue;
         }
+        let Some((start, end)) = covered_char_range(item, regions) else {
+            i += 1;
+            continue;
+        };
+        let (before, mid, after) = split_text_item(item, start, end);
+        let mut pieces = Vec::with_capacity(3);
+        let covered_idx = i + usize::from(before.is_some());
+        pieces.extend(before);
+        pieces.push(mid);
+        pieces.extend(after);
+        let count = pieces.len();
+        items.splice(i..=i, pieces);
+        // Indices of earlier covered items are unaffected; later ones shift
+        covered.push(covered_idx);
+        i += count;
     }
 
-    items
+    covered
 }
 
-/// Get fonts from a Form XObject's Resources
-fn get_form_fonts<'a>(
-    doc: &'a Document,
-    form_dict: &lopdf::Dictionary,
-) -> std::collections::BTreeMap<Vec<u8>, &'a lopdf::Dictionary> {
-    let mut fonts = std::collections::BTreeMap::new();
-
-    // Get Resources from Form dictionary
-    let resources = if let Ok(res_ref) = form_dict.get(b"Resources") {
-        if let Ok(obj_ref) = res_ref.as_reference() {
-            doc.get_dictionary(obj_ref).ok()
-        } else {
-            res_ref.as_dict().ok()
+/// Range of characters (start inclusive, end exclusive) of a text item whose
+/// centers fall inside any of the regions, trimmed of surrounding whitespace
+fn covered_char_range(item: &TextItem, regions: &[Rect]) -> Option<(usize, usize)> {
+    let chars: Vec<char> = item.text.chars().collect();
+    if chars.is_empty() {
+        return None;
+    }
+    // The baseline sits below the glyph center; about a third of the font size up is mid-glyph
+    let center_y = item.y + item.height * 0.3;
+    let char_width = effective_width(item) / chars.len() as f32;
+
+    let mut range: Option<(usize, usize)> = None;
+    for region in regions {
+        if center_y < region.y_min - 1.0 || center_y > region.y_max + 1.0 {
+            continue;
         }
-    } else {
-        return fonts;
-    };
+        for (k, c) in chars.iter().enumerate() {
+            if c.is_whitespace() {
+                continue;
+            }
+            let center_x = item.x + (k as f32 + 0.5) * char_width;
+            if center_x >= region.x_min && center_x <= region.x_max {
+                range = Some(match range {
+                    Some((s, e)) => (s.min(k), e.max(k + 1)),
+                    None => (k, k + 1),
+                });
+            }
+        }
+    }
+    range
+}
 
-    let Some(resources) = resources else {
-        return fonts;
+/// Split a text item into the parts before, inside and after a character range.
+/// Whitespace at a split point is kept as leading whitespace of the following part
+/// so word boundaries survive.
+fn split_text_item(
+    item: &TextItem,
+    start: usize,
+    end: usize,
+) -> (Option<TextItem>, TextItem, Option<TextItem>) {
+    let chars: Vec<char> = item.text.chars().collect();
+    let char_width = effective_width(item) / chars.len() as f32;
+    let piece = |from: usize, to: usize, text: String| TextItem {
+        text,
+        x: item.x + from as f32 * char_width,
+        width: (to - from) as f32 * char_width,
+        comments: Vec::new(),
+        ..item.clone()
     };
 
-    // Get Font dictionary
-    let font_dict = if let Ok(font_ref) = resources.get(b"Font") {
-        if let Ok(obj_ref) = font_ref.as_reference() {
-            doc.get_dictionary(obj_ref).ok()
-        } else {
-            font_ref.as_dict().ok()
+    let before_text: String = chars[..start].iter().collect();
+    let mut mid_text: String = chars[start..end].iter().collect();
+    let before = if before_text.trim().is_empty() {
+        if !before_text.is_empty() {
+            mid_text.insert(0, ' ');
         }
+        None
     } else {
-        return fonts;
+        if before_text.ends_with(char::is_whitespace) {
+            mid_text.insert(0, ' ');
+        }
+        Some(piece(0, start, before_text.trim_end().to_string()))
     };
-
-    let Some(font_dict) = font_dict else {
-        return fonts;
+    let after = (end < chars.len())
+        .then(|| chars[end..].iter().collect::<String>())
+        .filter(|text| !text.trim().is_empty())
+        .map(|text| TextItem {
+            comments: item.comments.clone(),
+            ..piece(end, chars.len(), text)
+        });
+    let mid = TextItem {
+        comments: if after.is_none() {
+            item.comments.clone()
+        } else {
+            Vec::new()
+        },
+        ..piece(start, end, mid_text)
     };
+    (before, mid, after)
+}
 
-    // Collect fonts
-    for (name, value) in font_dict.iter() {
-        if let Ok(obj_ref) = value.as_reference() {
-            if let Ok(dict) = doc.get_dictionary(obj_ref) {
-                fonts.insert(name.clone(), dict);
+/// Join the text of covered items in reading order
+fn join_covered_text(items: &[TextItem], covered: &[usize]) -> String {
+    let mut ordered: Vec<&TextItem> = covered.iter().map(|&i| &items[i]).collect();
+    ordered.sort_by(|a, b| {
+        b.y.partial_cmp(&a.y)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal))
+    });
+
+    let mut text = String::new();
+    let mut prev: Option<&TextItem> = None;
+    for item in ordered {
+        let piece = item.text.trim();
+        if let Some(prev) = prev {
+            let same_line = (prev.y - item.y).abs() < prev.font_size * 0.5;
+            let gap = item.x - (prev.x + effective_width(prev));
+            if !same_line || gap > prev.font_size * 0.15 || item.text.starts_with(' ') {
+                text.push(' ');
             }
         }
+        text.push_str(piece);
+        prev = Some(item);
     }
-
-    fonts
+    text
 }
 
-/// Extract hyperlinks from page annotations
-pub fn extract_page_links(doc: &Document, page_id: ObjectId, page_num: u32) -> Vec<TextItem> {
-    let mut links = Vec::new();
-
-    // Try to get the page dictionary
-    if let Ok(page_dict) = doc.get_dictionary(page_id) {
-        // Get Annots array
-        let annots = if let Ok(annots_ref) = page_dict.get(b"Annots") {
-            if let Ok(obj_ref) = annots_ref.as_reference() {
-                doc.get_object(obj_ref)
-                    .ok()
-                    .and_then(|o| o.as_array().ok().cloned())
+/// Index of the text item closest to an annotation's rectangle, preferring the
+/// nearest line and then the nearest item on it
+fn nearest_text_item(items: &[TextItem], annot: &Annotation) -> Option<usize> {
+    let center_y = annot.y + annot.height / 2.0;
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item.page == annot.page && item.item_type == ItemType::Text)
+        .map(|(i, item)| {
+            let dy = (item.y + item.height * 0.3 - center_y).abs();
+            let item_right = item.x + effective_width(item);
+            let dx = if item_right < annot.x {
+                annot.x - item_right
+            } else if item.x > annot.x + annot.width {
+                item.x - (annot.x + annot.width)
             } else {
-                annots_ref.as_array().ok().cloned()
-            }
-        } else {
-            None
-        };
-
-        if let Some(annots) = annots {
-            for annot_ref in annots {
-                // Get annotation dictionary
-                let annot_dict = if let Ok(obj_ref) = annot_ref.as_reference() {
-                    doc.get_dictionary(obj_ref).ok()
-                } else {
-                    annot_ref.as_dict().ok()
-                };
+                0.0
+            };
+            (i, dy * 4.0 + dx)
+        })
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(i, _)| i)
+}
 
-                if let Some(annot_dict) = annot_dict {
-                    // Check if this is a Link annotation
-                    if let Ok(subtype) = annot_dict.get(b"Subtype") {
-                        if let Ok(subtype_name) = subtype.as_name() {
-                            if subtype_name != b"Link" {
-                                continue;
-                            }
-                        }
+/// Parse an annotation's /QuadPoints into 8-number quadrilaterals
+fn parse_quad_points(doc: &Document, annot: &lopdf::Dictionary) -> Vec<[f32; 8]> {
+    annot
+        .get(b"QuadPoints")
+        .ok()
+        .and_then(|o| resolve_array(doc, o))
+        .map(|values| {
+            values
+                .chunks_exact(8)
+                .filter_map(|chunk| {
+                    let mut quad = [0.0f32; 8];
+                    for (slot, value) in quad.iter_mut().zip(chunk) {
+                        *slot = get_number(value)?;
                     }
+                    Some(quad)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
 
-                    // Get the Rect (position)
-                    let rect = if let Ok(rect_obj) = annot_dict.get(b"Rect") {
-                        if let Ok(rect_array) = rect_obj.as_array() {
-                            if rect_array.len() >= 4 {
-                                let x1 = get_number(&rect_array[0]).unwrap_or(0.0);
-                                let y1 = get_number(&rect_array[1]).unwrap_or(0.0);
-                                let x2 = get_number(&rect_array[2]).unwrap_or(0.0);
-                                let y2 = get_number(&rect_array[3]).unwrap_or(0.0);
-                                Some((x1, y1, x2 - x1, y2 - y1))
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    };
-
-                    // Get the action (A dictionary) or Dest
-                    let uri = extract_link_uri(doc, annot_dict);
-
-                    if let (Some((x, y, width, height)), Some(url)) = (rect, uri) {
-                        links.push(TextItem {
-                            text: url.clone(),
-                            x,
-                            y,
-                            width,
-                            height,
-                            font: String::new(),
-                            font_size: 0.0,
-                            page: page_num,
-                            is_bold: false,
-                            is_italic: false,
-                            item_type: ItemType::Link(url),
-                        });
-                    }
-                }
+/// Decode a PDF text string (PDF 32000-1, 7.9.2.2): UTF-16BE or UTF-8 with
+/// a byte order mark, otherwise PDFDocEncoding
+pub(crate) fn decode_text_string(doc: &Document, obj: &Object) -> Option<String> {
+    let obj = match obj {
+        Object::Reference(id) => doc.get_object(*id).ok()?,
+        other => other,
+    };
+    match obj {
+        Object::String(bytes, _) => {
+            // Handle UTF-16BE encoding (BOM: 0xFE 0xFF)
+            if bytes.len() >= 2 && bytes[0] == 0xFE && bytes[1] == 0xFF {
+                let utf16: Vec<u16> = bytes[2..]
+                    .chunks_exact(2)
+                    .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]))
+                    .collect();
+                Some(String::from_utf16_lossy(&utf16))
+            } else if let Some(utf8) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+                Some(String::from_utf8_lossy(utf8).to_string())
+            } else {
+                Some(bytes.iter().map(|&b| pdf_doc_char(b)).collect())
             }
         }
+        _ => None,
     }
-
-    links
 }
 
-/// Extract URI from a link annotation
-fn extract_link_uri(doc: &Document, annot_dict: &lopdf::Dictionary) -> Option<String> {
-    // Try to get the A (Action) dictionary
-    if let Ok(action_ref) = annot_dict.get(b"A") {
-        let action_dict = if let Ok(obj_ref) = action_ref.as_reference() {
-            doc.get_dictionary(obj_ref).ok()
-        } else {
-            action_ref.as_dict().ok()
-        };
-
-        if let Some(action_dict) = action_dict {
-            // Check for URI action
-            if let Ok(uri_obj) = action_dict.get(b"URI") {
-                if let Ok(uri_str) = uri_obj.as_str() {
-                    return Some(String::from_utf8_lossy(uri_str).to_string());
-                }
-            }
-        }
+/// Character of a PDFDocEncoding byte (PDF 32000-1, Annex D.2). The encoding
+/// matches Latin-1 except for the ranges below.
+fn pdf_doc_char(byte: u8) -> char {
+    const CHARS_18: [char; 8] = ['˘', 'ˇ', 'ˆ', '˙', '˝', '˛', '˚', '˜'];
+    const CHARS_80: [char; 32] = [
+        '•', '†', '‡', '…', '—', '–', 'ƒ', '⁄', '‹', '›', '−', '‰', '„', '“', '”', '‘', '’', '‚',
+        '™', 'ﬁ', 'ﬂ', 'Ł', 'Œ', 'Š', 'Ÿ', 'Ž', 'ı', 'ł', 'œ', 'š', 'ž', '\u{FFFD}',
+    ];
+    match byte {
+        0x18..=0x1F => CHARS_18[(byte - 0x18) as usize],
+        0x80..=0x9F => CHARS_80[(byte - 0x80) as usize],
+        0xA0 => '€',
+        0xAD => '\u{FFFD}',
+        _ => byte as char,
     }
-
-    // Try Dest (named destination) - less common for external links
-    // We'll skip this for now as it requires looking up named destinations
-
-    None
 }
 
 /// Compute effective font size from base size and text matrix
@@ -2441,7 +4104,7 @@ fn group_single_column(items: Vec<TextItem>) -> Vec<TextLine> {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
     #[test]
@@ -2459,6 +4122,7 @@ mod tests {
                 is_bold: false,
                 is_italic: false,
                 item_type: ItemType::Text,
+                ..Default::default()
             },
             TextItem {
                 text: "World".into(),
@@ -2472,6 +4136,7 @@ mod tests {
                 is_bold: false,
                 is_italic: false,
                 item_type: ItemType::Text,
+                ..Default::default()
             },
             TextItem {
                 text: "Next line".into(),
@@ -2485,6 +4150,7 @@ mod tests {
                 is_bold: false,
                 is_italic: false,
                 item_type: ItemType::Text,
+                ..Default::default()
             },
         ];
 
@@ -2538,6 +4204,7 @@ mod tests {
                 is_bold: false,
                 is_italic: false,
                 item_type: ItemType::Text,
+                ..Default::default()
             },
             TextItem {
                 text: "Prague".into(),
@@ -2551,6 +4218,7 @@ mod tests {
                 is_bold: false,
                 is_italic: false,
                 item_type: ItemType::Text,
+                ..Default::default()
             },
             TextItem {
                 text: "Rules".into(),
@@ -2564,6 +4232,7 @@ mod tests {
                 is_bold: false,
                 is_italic: false,
                 item_type: ItemType::Text,
+                ..Default::default()
             },
         ];
 
@@ -2588,6 +4257,7 @@ mod tests {
                 is_bold: false,
                 is_italic: false,
                 item_type: ItemType::Text,
+                ..Default::default()
             },
             TextItem {
                 text: "A".into(),
@@ -2601,6 +4271,7 @@ mod tests {
                 is_bold: false,
                 is_italic: false,
                 item_type: ItemType::Text,
+                ..Default::default()
             },
             TextItem {
                 text: "V".into(),
@@ -2614,6 +4285,7 @@ mod tests {
                 is_bold: false,
                 is_italic: false,
                 item_type: ItemType::Text,
+                ..Default::default()
             },
         ];
 
@@ -2638,6 +4310,7 @@ mod tests {
                 is_bold: false,
                 is_italic: false,
                 item_type: ItemType::Text,
+                ..Default::default()
             },
             TextItem {
                 text: "履行義務".into(),
@@ -2651,6 +4324,7 @@ mod tests {
                 is_bold: false,
                 is_italic: false,
                 item_type: ItemType::Text,
+                ..Default::default()
             },
             TextItem {
                 text: "を識別す".into(),
@@ -2664,6 +4338,7 @@ mod tests {
                 is_bold: false,
                 is_italic: false,
                 item_type: ItemType::Text,
+                ..Default::default()
             },
         ];
 
@@ -2671,4 +4346,951 @@ mod tests {
         assert_eq!(lines.len(), 1);
         assert_eq!(lines[0].text(), "である履行義務を識別す");
     }
+
+    #[test]
+    fn test_standard_font_widths_without_widths_array() {
+        use lopdf::dictionary;
+
+        let doc = Document::new();
+        let font = dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        };
+        let info = parse_font_widths(&doc, &font).expect("standard font metrics");
+        assert_eq!(info.widths.get(&(b'A' as u16)), Some(&667));
+        assert_eq!(info.space_width, 278);
+
+        // "Hello" at 10pt in Helvetica: (722 + 556 + 222 + 222 + 556) / 1000 * 10
+        let w = compute_string_width_ts(b"Hello", &info, 10.0);
+        assert!((w - 22.78).abs() < 0.01);
+
+        // An explicit Widths array still takes precedence
+        let font = dictionary! {
+            "Subtype" => "TrueType",
+            "BaseFont" => "Arial",
+            "FirstChar" => 65,
+            "LastChar" => 65,
+            "Widths" => vec![500.into()],
+        };
+        let info = parse_font_widths(&doc, &font).unwrap();
+        assert_eq!(info.widths.get(&65), Some(&500));
+    }
+
+    /// Extract text items from a single page
+    fn extract_page_text_items(
+        doc: &Document,
+        page_id: ObjectId,
+        page_num: u32,
+        font_cmaps: &FontCMaps,
+        optional_content: &OptionalContent,
+    ) -> Result<Vec<TextItem>, PdfError> {
+        extract_page_content(doc, page_id, page_num, font_cmaps, optional_content, false)
+            .map(|(items, _)| items)
+    }
+
+    /// Add a one-page tree with the given content stream and extra page entries
+    pub(crate) fn add_single_page(
+        doc: &mut Document,
+        content: &[u8],
+        entries: lopdf::Dictionary,
+    ) -> ObjectId {
+        use lopdf::{dictionary, Stream};
+
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.to_vec()));
+        let pages_id = doc.new_object_id();
+        let mut page = dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+            "Contents" => content_id,
+        };
+        page.extend(&entries);
+        let page_id = doc.add_object(page);
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        page_id
+    }
+
+    #[test]
+    fn test_text_operators_outside_text_object_are_ignored() {
+        use lopdf::dictionary;
+
+        let mut doc = Document::with_version("1.5");
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let page_id = add_single_page(
+            &mut doc,
+            b"(A) Tj (B) ' 0 0 (C) \" BT /F1 10 Tf 72 700 Td (D) Tj 12 TL (E) ' 1 0 (F) \" ET",
+            dictionary! {
+                "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+            },
+        );
+
+        let items = extract_page_text_items(
+            &doc,
+            page_id,
+            1,
+            &FontCMaps::default(),
+            &OptionalContent::default(),
+        )
+        .unwrap();
+        let texts: Vec<&str> = items.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(texts, vec!["D", "E", "F"]);
+        assert!((items[1].y - 688.0).abs() < 0.01);
+        assert!((items[2].y - 676.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_nested_form_xobjects_apply_matrix_and_bbox() {
+        use lopdf::{dictionary, Stream};
+
+        let mut doc = Document::with_version("1.5");
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let inner_id = doc.new_object_id();
+        let outer_id = doc.new_object_id();
+        // Inner form: one string inside the outer BBox, one outside, and a cycle back
+        doc.objects.insert(
+            inner_id,
+            Object::Stream(Stream::new(
+                dictionary! {
+                    "Type" => "XObject",
+                    "Subtype" => "Form",
+                    "BBox" => vec![0.into(), 0.into(), 100.into(), 100.into()],
+                },
+                b"BT /F1 10 Tf 0 20 Td (Inner) Tj ET BT /F1 10 Tf 0 80 Td (Clipped) Tj ET /Fm1 Do"
+                    .to_vec(),
+            )),
+        );
+        doc.objects.insert(
+            outer_id,
+            Object::Stream(Stream::new(
+                dictionary! {
+                    "Type" => "XObject",
+                    "Subtype" => "Form",
+                    "Matrix" => vec![1.into(), 0.into(), 0.into(), 1.into(), 10.into(), 0.into()],
+                    "BBox" => vec![0.into(), 0.into(), 200.into(), 50.into()],
+                },
+                b"BT /F1 10 Tf (Outer) Tj ET /Fm2 Do /Fm1 Do".to_vec(),
+            )),
+        );
+        let page_id = add_single_page(
+            &mut doc,
+            b"q 2 0 0 2 100 100 cm /Fm1 Do Q",
+            dictionary! {
+                "Resources" => dictionary! {
+                    "Font" => dictionary! { "F1" => font_id },
+                    "XObject" => dictionary! { "Fm1" => outer_id, "Fm2" => inner_id },
+                },
+            },
+        );
+
+        let items = extract_page_text_items(
+            &doc,
+            page_id,
+            1,
+            &FontCMaps::default(),
+            &OptionalContent::default(),
+        )
+        .unwrap();
+        let texts: Vec<&str> = items.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(texts, vec!["Outer", "Inner"]);
+
+        // Outer form: page CTM (scale 2, offset 100,100) combined with form Matrix (10,0)
+        assert!((items[0].x - 120.0).abs() < 0.01);
+        assert!((items[0].y - 100.0).abs() < 0.01);
+        assert!((items[0].font_size - 20.0).abs() < 0.01);
+        // Inner form inherits the outer transform
+        assert!((items[1].x - 120.0).abs() < 0.01);
+        assert!((items[1].y - 140.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_annotation_appearance_text() {
+        use lopdf::{dictionary, Stream};
+
+        let mut doc = Document::with_version("1.5");
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let appearance = |doc: &mut Document, text: &str| {
+            doc.add_object(Stream::new(
+                dictionary! {
+                    "Type" => "XObject",
+                    "Subtype" => "Form",
+                    "BBox" => vec![0.into(), 0.into(), 100.into(), 20.into()],
+                    "Resources" => dictionary! {
+                        "Font" => dictionary! { "Helv" => font_id },
+                    },
+                },
+                format!("BT /Helv 10 Tf 2 5 Td ({}) Tj ET", text).into_bytes(),
+            ))
+        };
+        let free_text_ap = appearance(&mut doc, "Reviewer note");
+        let hidden_ap = appearance(&mut doc, "Hidden note");
+        let screen_ap = appearance(&mut doc, "Screen only");
+        let on_ap = appearance(&mut doc, "Approved");
+        let off_ap = appearance(&mut doc, "Rejected");
+        let annots = vec![
+            // BBox (100x20) scaled onto a 200x40 Rect at (300, 500)
+            Object::Dictionary(dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "FreeText",
+                "Rect" => vec![300.into(), 500.into(), 500.into(), 540.into()],
+                "F" => 4,
+                "AP" => dictionary! { "N" => free_text_ap },
+            }),
+            Object::Dictionary(dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "FreeText",
+                "Rect" => vec![0.into(), 0.into(), 100.into(), 20.into()],
+                "F" => 2,
+                "AP" => dictionary! { "N" => hidden_ap },
+            }),
+            // Shown on screen but not printed: not part of the page
+            Object::Dictionary(dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "FreeText",
+                "Rect" => vec![0.into(), 200.into(), 100.into(), 220.into()],
+                "AP" => dictionary! { "N" => screen_ap },
+            }),
+            // Appearance chosen by state
+            Object::Dictionary(dictionary! {
+                "Type" => "Annot",
+                "Subtype" => "Widget",
+                "Rect" => vec![50.into(), 100.into(), 150.into(), 120.into()],
+                "F" => 4,
+                "AS" => "On",
+                "AP" => dictionary! { "N" => dictionary! { "On" => on_ap, "Off" => off_ap } },
+            }),
+        ];
+        let page_id = add_single_page(&mut doc, b"", dictionary! { "Annots" => annots });
+
+        let items = extract_page_text_items(
+            &doc,
+            page_id,
+            1,
+            &FontCMaps::default(),
+            &OptionalContent::default(),
+        )
+        .unwrap();
+        let texts: Vec<&str> = items.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(texts, vec!["Reviewer note", "Approved"]);
+
+        assert_eq!(items[0].annotation.as_deref(), Some("FreeText"));
+        assert!((items[0].x - 304.0).abs() < 0.01);
+        assert!((items[0].y - 510.0).abs() < 0.01);
+        assert!((items[0].font_size - 20.0).abs() < 0.01);
+        assert_eq!(items[1].annotation.as_deref(), Some("Widget"));
+        assert!((items[1].x - 52.0).abs() < 0.01);
+        assert!((items[1].y - 105.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_apply_annotations_splits_covered_text() {
+        let mut items = vec![TextItem {
+            text: "The quick brown fox".into(),
+            x: 100.0,
+            y: 700.0,
+            width: 95.0, // 5pt per character
+            height: 10.0,
+            font: "F1".into(),
+            font_size: 10.0,
+            page: 1,
+            ..Default::default()
+        }];
+        let annotation = |id, kind, x, y, width, height| Annotation {
+            id,
+            kind,
+            page: 1,
+            x,
+            y,
+            width,
+            height,
+            contents: None,
+            author: None,
+            modified: None,
+            quad_points: Vec::new(),
+            text: String::new(),
+        };
+        // Highlight over "quick" (characters 4..9) via QuadPoints
+        let mut highlight = annotation(1, AnnotationKind::Highlight, 119.0, 697.0, 27.0, 12.0);
+        highlight.quad_points = vec![[119.0, 709.0, 146.0, 709.0, 119.0, 697.0, 146.0, 697.0]];
+        highlight.contents = Some("Too informal".into());
+        highlight.author = Some("Reviewer".into());
+        // Sticky note in the right margin of the same line
+        let mut note = annotation(2, AnnotationKind::Text, 500.0, 695.0, 20.0, 20.0);
+        note.contents = Some("Check\nthis".into());
+        let mut annotations = vec![highlight, note];
+
+        apply_annotations(&mut items, &mut annotations);
+
+        let texts: Vec<&str> = items.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(texts, vec!["The", " quick", " brown fox"]);
+        assert_eq!(annotations[0].text, "quick");
+        assert_eq!(items[1].markup, Some(AnnotationKind::Highlight));
+        assert!((items[1].x - 120.0).abs() < 0.01);
+        assert_eq!(items[1].comments[0].id, 1);
+        assert_eq!(items[2].comments[0].text, "Check this");
+
+        let line = TextLine {
+            y: 700.0,
+            page: 1,
+            items,
+        };
+        assert_eq!(line.text(), "The quick brown fox");
+        assert_eq!(
+            line.text_with_markup(InlineFormat {
+                annotations: true,
+                ..InlineFormat::default()
+            }),
+            "The ==quick==[^1] brown fox[^2]"
+        );
+    }
+
+    #[test]
+    fn test_links_attach_to_anchor_text() {
+        let mut items = vec![TextItem {
+            text: "Read the Rust docs".into(),
+            x: 100.0,
+            y: 700.0,
+            width: 90.0, // 5pt per character
+            height: 10.0,
+            font: "F1".into(),
+            font_size: 10.0,
+            page: 1,
+            ..Default::default()
+        }];
+        let rect = |x_min, y_min, x_max, y_max| Rect {
+            x_min,
+            y_min,
+            x_max,
+            y_max,
+        };
+        let links = vec![
+            // Covers "Rust docs" (characters 9..18)
+            PageLink {
+                url: "https://en.wikipedia.org/wiki/Rust_(programming_language)".into(),
+                destination: None,
+                rect: rect(144.0, 698.0, 192.0, 712.0),
+                regions: vec![rect(144.0, 698.0, 192.0, 712.0)],
+            },
+            // Nothing underneath
+            PageLink {
+                url: "https://example.com".into(),
+                destination: None,
+                rect: rect(400.0, 100.0, 450.0, 150.0),
+                regions: vec![rect(400.0, 100.0, 450.0, 150.0)],
+            },
+        ];
+
+        let standalone = attach_page_links(&mut items, &links, 1);
+
+        assert_eq!(standalone.len(), 1);
+        assert_eq!(
+            standalone[0].item_type,
+            ItemType::Link("https://example.com".into())
+        );
+        let texts: Vec<&str> = items.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(texts, vec!["Read the", " Rust docs"]);
+        assert!(items[0].link.is_none());
+
+        let line = TextLine {
+            y: 700.0,
+            page: 1,
+            items,
+        };
+        assert_eq!(
+            line.text_with_markup(InlineFormat {
+                links: true,
+                ..InlineFormat::default()
+            }),
+            "Read the [Rust docs](https://en.wikipedia.org/wiki/Rust_%28programming_language%29)"
+        );
+        assert_eq!(line.text(), "Read the Rust docs");
+    }
+
+    #[test]
+    fn test_link_text_is_escaped() {
+        let line = TextLine {
+            y: 700.0,
+            page: 1,
+            items: vec![TextItem {
+                text: "[asn1 decode simple der], page 22,".into(),
+                link: Some("#page-25-681".into()),
+                ..Default::default()
+            }],
+        };
+        assert_eq!(
+            line.text_with_markup(InlineFormat {
+                internal_links: true,
+                ..InlineFormat::default()
+            }),
+            "[\\[asn1 decode simple der\\], page 22,](#page-25-681)"
+        );
+    }
+
+    #[test]
+    fn test_internal_links_resolve_named_destinations() {
+        use crate::markdown::{to_markdown_from_items, MarkdownOptions};
+        use lopdf::{dictionary, Stream};
+
+        let mut doc = Document::with_version("1.5");
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let resources = dictionary! { "Font" => dictionary! { "F1" => font_id } };
+        let pages_id = doc.new_object_id();
+        let page2_content = doc.add_object(Stream::new(
+            dictionary! {},
+            b"BT /F1 10 Tf 72 600 Td (Appendix text) Tj ET".to_vec(),
+        ));
+        let page2_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+            "Contents" => page2_content,
+            "Resources" => resources.clone(),
+        });
+        // "See appendix" spans x 72..132 at 5pt per character
+        let page1_content = doc.add_object(Stream::new(
+            dictionary! {},
+            b"BT /F1 10 Tf 72 700 Td (See appendix) Tj ET".to_vec(),
+        ));
+        let link = dictionary! {
+            "Type" => "Annot",
+            "Subtype" => "Link",
+            "Rect" => vec![90.into(), 695.into(), 135.into(), 712.into()],
+            "Dest" => Object::string_literal("appendix"),
+        };
+        let page1_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+            "Contents" => page1_content,
+            "Resources" => resources,
+            "Annots" => vec![Object::Dictionary(link)],
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page1_id.into(), page2_id.into()],
+                "Count" => 2,
+            }),
+        );
+        let dest = vec![
+            page2_id.into(),
+            "XYZ".into(),
+            Object::Null,
+            620.into(),
+            Object::Null,
+        ];
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+            "Names" => dictionary! {
+                "Dests" => dictionary! {
+                    "Names" => vec![Object::string_literal("appendix"), Object::Array(dest)],
+                },
+            },
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let items =
+            extract_positioned_text_from_doc(&doc, &FontCMaps::default(), &LayerSelection::Default)
+                .unwrap();
+        let linked: Vec<&TextItem> = items.iter().filter(|i| i.link.is_some()).collect();
+        assert_eq!(linked.len(), 1);
+        assert_eq!(linked[0].text.trim(), "appendix");
+        assert_eq!(linked[0].link.as_deref(), Some("#page-2-620"));
+        assert!(items
+            .iter()
+            .any(|i| i.item_type == ItemType::Anchor("page-2-620".into()) && i.page == 2));
+
+        // A destination nothing in the text links to gets no anchor
+        let mut with_unlinked = items.clone();
+        with_unlinked.push(TextItem {
+            y: 300.0,
+            page: 2,
+            item_type: ItemType::Anchor("page-2-300".into()),
+            ..Default::default()
+        });
+        let options = MarkdownOptions {
+            internal_links: true,
+            ..Default::default()
+        };
+        let md = to_markdown_from_items(with_unlinked, options);
+        assert!(!md.contains("page-2-300"), "{}", md);
+        assert!(md.contains("See [appendix](#page-2-620)"), "{}", md);
+        let anchor = md
+            .find("<a id=\"page-2-620\"></a>")
+            .expect("anchor emitted");
+        assert!(anchor < md.find("Appendix text").unwrap());
+
+        let md = to_markdown_from_items(items, MarkdownOptions::default());
+        assert!(md.contains("See appendix"), "{}", md);
+        assert!(!md.contains("<a id="));
+    }
+
+    #[test]
+    fn test_hidden_optional_content_is_skipped() {
+        use lopdf::{dictionary, Stream};
+
+        let mut doc = Document::with_version("1.5");
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let print_id = doc.add_object(dictionary! {
+            "Type" => "OCG",
+            "Name" => Object::string_literal("Print"),
+        });
+        let notes_id = doc.add_object(dictionary! {
+            "Type" => "OCG",
+            "Name" => Object::string_literal("Notes"),
+        });
+        let form_id = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Form",
+                "BBox" => vec![0.into(), 0.into(), 200.into(), 50.into()],
+                "OC" => print_id,
+            },
+            b"BT /F1 10 Tf (Crop marks) Tj ET".to_vec(),
+        ));
+        let page_id = add_single_page(
+            &mut doc,
+            b"BT /F1 12 Tf 72 700 Td (Body) Tj ET \
+              /OC /MC0 BDC BT /F1 12 Tf 72 680 Td (Print only) Tj ET EMC \
+              /OC /MC1 BDC /Span BMC BT /F1 12 Tf 72 660 Td (Note) Tj ET EMC EMC \
+              q 1 0 0 1 72 600 cm /Fm1 Do Q",
+            dictionary! {
+                "Resources" => dictionary! {
+                    "Font" => dictionary! { "F1" => font_id },
+                    "XObject" => dictionary! { "Fm1" => form_id },
+                    "Properties" => dictionary! { "MC0" => print_id, "MC1" => notes_id },
+                },
+            },
+        );
+        doc.catalog_mut().unwrap().set(
+            "OCProperties",
+            dictionary! {
+                "OCGs" => vec![print_id.into(), notes_id.into()],
+                "D" => dictionary! { "OFF" => vec![print_id.into()] },
+            },
+        );
+
+        let extract = |selection: &LayerSelection| -> Vec<String> {
+            let oc = OptionalContent::new(&doc, selection);
+            extract_page_text_items(&doc, page_id, 1, &FontCMaps::default(), &oc)
+                .unwrap()
+                .into_iter()
+                .map(|i| i.text)
+                .collect()
+        };
+        assert_eq!(extract(&LayerSelection::Default), vec!["Body", "Note"]);
+        assert_eq!(
+            extract(&LayerSelection::All),
+            vec!["Body", "Print only", "Note", "Crop marks"]
+        );
+        assert_eq!(
+            extract(&LayerSelection::Only(vec!["Print".to_string()])),
+            vec!["Body", "Print only", "Crop marks"]
+        );
+    }
+
+    #[test]
+    fn test_rotated_image_bounding_box_and_info() {
+        use lopdf::{dictionary, Stream};
+
+        let mut doc = Document::with_version("1.5");
+        let image_id = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 4,
+                "Height" => 2,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+            },
+            vec![0; 8],
+        ));
+        // 200x100 image rotated by 90 degrees, drawn with its origin at (300, 400)
+        let page_id = add_single_page(
+            &mut doc,
+            b"q 0 200 -100 0 300 400 cm /Im1 Do Q",
+            dictionary! {
+                "Resources" => dictionary! {
+                    "XObject" => dictionary! { "Im1" => image_id },
+                },
+            },
+        );
+
+        let items = extract_page_text_items(
+            &doc,
+            page_id,
+            1,
+            &FontCMaps::default(),
+            &OptionalContent::default(),
+        )
+        .unwrap();
+        assert_eq!(items.len(), 1);
+        let image = &items[0];
+        assert_eq!(image.item_type, ItemType::Image);
+        assert_eq!(image.text, "[Image: Im1]");
+        assert_eq!((image.x, image.y), (200.0, 400.0));
+        assert_eq!((image.width, image.height), (100.0, 200.0));
+
+        let info = image.image.as_ref().expect("image info");
+        assert_eq!(info.name, "Im1");
+        assert_eq!((info.pixel_width, info.pixel_height), (4, 2));
+        assert_eq!(info.color_space.as_deref(), Some("DeviceGray"));
+        assert_eq!(info.filter, None);
+        assert_eq!(info.hash(&doc).map(|h| h.len()), Some(64));
+    }
+
+    #[test]
+    fn test_watermark_attributes() {
+        use lopdf::dictionary;
+
+        let mut doc = Document::with_version("1.5");
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let page_id = add_single_page(
+            &mut doc,
+            b"BT /F1 12 Tf 72 700 Td (Body) Tj ET \
+              /Artifact <</Type /Pagination /Subtype /Watermark>> BDC \
+              q /GS1 gs 0.8 g BT /F1 60 Tf 0.7071 0.7071 -0.7071 0.7071 150 200 Tm (DRAFT) Tj ET Q \
+              EMC \
+              BT 0 0 0 1 k /F1 12 Tf 72 680 Td (Black) Tj ET",
+            dictionary! {
+                "Resources" => dictionary! {
+                    "Font" => dictionary! { "F1" => font_id },
+                    "ExtGState" => dictionary! { "GS1" => dictionary! { "ca" => 0.3 } },
+                },
+            },
+        );
+
+        let items = extract_page_text_items(
+            &doc,
+            page_id,
+            1,
+            &FontCMaps::default(),
+            &OptionalContent::default(),
+        )
+        .unwrap();
+        assert_eq!(items.len(), 3);
+
+        let body = &items[0];
+        assert_eq!((body.rotation, body.fill_color), (0.0, [0.0; 3]));
+        assert_eq!((body.opacity, body.artifact.as_deref()), (None, None));
+
+        let draft = &items[1];
+        assert_eq!(draft.text, "DRAFT");
+        assert!((draft.rotation - 45.0).abs() < 0.01);
+        assert_eq!(draft.fill_color, [0.8; 3]);
+        assert_eq!(draft.opacity, Some(0.3));
+        assert_eq!(draft.artifact.as_deref(), Some("Watermark"));
+
+        // Fill state is restored by Q and the artifact section is closed
+        let black = &items[2];
+        assert_eq!(black.fill_color, [0.0; 3]);
+        assert_eq!((black.opacity, black.artifact.as_deref()), (None, None));
+    }
+
+    #[test]
+    fn test_hidden_text_is_flagged() {
+        use lopdf::{dictionary, Stream};
+
+        let mut doc = Document::with_version("1.5");
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let image_id = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 1,
+                "Height" => 1,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+            },
+            vec![0],
+        ));
+        let page_id = add_single_page(
+            &mut doc,
+            b"BT /F1 12 Tf 72 700 Td (Visible) Tj ET \
+              BT 3 Tr /F1 12 Tf 72 680 Td (Render mode) Tj 0 Tr ET \
+              q 1 g BT /F1 12 Tf 72 660 Td (White) Tj ET Q \
+              q 0.1 g 60 500 300 30 re f 1 g BT /F1 12 Tf 72 510 Td (On banner) Tj ET Q \
+              q 1 g 60 450 300 30 re f BT /F1 12 Tf 72 460 Td (On white box) Tj ET Q \
+              BT /F1 0.5 Tf 72 640 Td (Tiny) Tj ET \
+              BT /F1 12 Tf 72 900 Td (Off page) Tj ET \
+              BT /F1 12 Tf 72 300 Td (Covered) Tj ET \
+              q 300 0 0 100 50 250 cm /Im1 Do Q",
+            dictionary! {
+                "CropBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+                "Resources" => dictionary! {
+                    "Font" => dictionary! { "F1" => font_id },
+                    "XObject" => dictionary! { "Im1" => image_id },
+                },
+            },
+        );
+
+        let items = extract_page_text_items(
+            &doc,
+            page_id,
+            1,
+            &FontCMaps::default(),
+            &OptionalContent::default(),
+        )
+        .unwrap();
+        let flags: Vec<(&str, Option<HiddenReason>)> = items
+            .iter()
+            .filter(|i| i.item_type == ItemType::Text)
+            .map(|i| (i.text.as_str(), i.hidden))
+            .collect();
+        assert_eq!(
+            flags,
+            vec![
+                ("Visible", None),
+                ("Render mode", Some(HiddenReason::InvisibleRenderMode)),
+                ("White", Some(HiddenReason::WhiteText)),
+                ("On banner", None),
+                ("On white box", Some(HiddenReason::WhiteText)),
+                ("Tiny", Some(HiddenReason::TinyFont)),
+                ("Off page", Some(HiddenReason::OutsideCropBox)),
+                ("Covered", Some(HiddenReason::BehindImage)),
+            ]
+        );
+        // White text on a dark banner shows; the banner is what it is drawn over
+        let banner = items.iter().find(|i| i.text == "On banner").unwrap();
+        assert!((banner.backdrop.unwrap() - 0.1).abs() < 0.01);
+        assert_eq!(items[0].backdrop, None);
+    }
+
+    #[test]
+    fn test_fill_and_stroke_colors() {
+        use lopdf::{dictionary, Stream};
+
+        let mut doc = Document::with_version("1.5");
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let icc_id = doc.add_object(Stream::new(dictionary! { "N" => 3 }, Vec::new()));
+        let page_id = add_single_page(
+            &mut doc,
+            b"BT /F1 12 Tf \
+              1 0 0 rg 0 0 1 RG 72 700 Td (Red) Tj \
+              /CS0 cs 0 1 0 sc 0 -20 Td (ICC green) Tj \
+              /CS1 cs 1 scn 0 -20 Td (Palette) Tj \
+              /CS2 cs 0 -20 Td (Spot) Tj \
+              0 1 1 0 k 0 -20 Td (CMYK red) Tj \
+              /Pattern cs /P0 scn 0 -20 Td (Pattern) Tj ET",
+            dictionary! {
+                "Resources" => dictionary! {
+                    "Font" => dictionary! { "F1" => font_id },
+                    "ColorSpace" => dictionary! {
+                        "CS0" => vec!["ICCBased".into(), icc_id.into()],
+                        "CS1" => vec![
+                            "Indexed".into(),
+                            "DeviceRGB".into(),
+                            1.into(),
+                            Object::String(vec![0, 0, 0, 255, 128, 0], lopdf::StringFormat::Hexadecimal),
+                        ],
+                        "CS2" => vec![
+                            "Separation".into(),
+                            "Spot".into(),
+                            "DeviceCMYK".into(),
+                            Object::Null,
+                        ],
+                    },
+                },
+            },
+        );
+
+        let items = extract_page_text_items(
+            &doc,
+            page_id,
+            1,
+            &FontCMaps::default(),
+            &OptionalContent::default(),
+        )
+        .unwrap();
+        let colors: Vec<(&str, [f32; 3])> = items
+            .iter()
+            .map(|i| (i.text.as_str(), i.fill_color))
+            .collect();
+        let orange = [1.0, 128.0 / 255.0, 0.0];
+        assert_eq!(
+            colors,
+            vec![
+                ("Red", [1.0, 0.0, 0.0]),
+                ("ICC green", [0.0, 1.0, 0.0]),
+                ("Palette", orange),
+                // Selecting a separation sets full tint
+                ("Spot", [0.0; 3]),
+                ("CMYK red", [1.0, 0.0, 0.0]),
+                // Patterns have no single color; selecting one resets to black
+                ("Pattern", [0.0; 3]),
+            ]
+        );
+        assert!(items.iter().all(|i| i.stroke_color == [0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn test_clipping_paths() {
+        use lopdf::{dictionary, Stream};
+
+        let mut doc = Document::with_version("1.5");
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Courier",
+        });
+        let image_id = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 1,
+                "Height" => 1,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+            },
+            vec![0],
+        ));
+        // Courier glyphs are 6pt wide at 10pt; the clip spans x 100..300
+        let page_id = add_single_page(
+            &mut doc,
+            b"q 100 400 200 300 re W n \
+              BT /F1 10 Tf 120 600 Td (Inside) Tj ET \
+              BT /F1 10 Tf 120 300 Td (Below) Tj ET \
+              BT /F1 10 Tf 70 500 Td (ABCDEFGHIJ) Tj ET \
+              q 100 0 0 50 250 450 cm /Im1 Do Q \
+              Q BT /F1 10 Tf 120 300 Td (Restored) Tj ET",
+            dictionary! {
+                "Resources" => dictionary! {
+                    "Font" => dictionary! { "F1" => font_id },
+                    "XObject" => dictionary! { "Im1" => image_id },
+                },
+            },
+        );
+
+        let items = extract_page_text_items(
+            &doc,
+            page_id,
+            1,
+            &FontCMaps::default(),
+            &OptionalContent::default(),
+        )
+        .unwrap();
+        let texts: Vec<&str> = items.iter().map(|i| i.text.as_str()).collect();
+        assert_eq!(texts, vec!["Inside", "FGHIJ", "[Image: Im1]", "Restored"]);
+
+        // Characters left of x 100 are cut
+        assert_eq!(items[1].x, 100.0);
+        assert!((items[1].width - 30.0).abs() < 0.01);
+        // The image is cut at the right edge of the clip
+        assert_eq!((items[2].x, items[2].width), (250.0, 50.0));
+    }
+
+    #[test]
+    fn test_vector_graphics() {
+        use crate::graphics::GraphicKind;
+        use lopdf::dictionary;
+
+        let mut doc = Document::with_version("1.5");
+        let page_id = add_single_page(
+            &mut doc,
+            b"0.9 g 72 600 200 20 re f \
+              q 2 0 0 2 0 0 cm 0.5 w 1 0 0 RG 36 250 m 136 250 l S Q \
+              q 0 1 -1 0 400 100 cm 0 0 m 50 0 l S Q \
+              100 100 m 110 120 130 120 140 100 c f \
+              q 0 0 10 10 re W n 500 500 m 600 500 l S Q \
+              0 0 m 612 0 l n",
+            dictionary! {},
+        );
+
+        let (items, graphics) = extract_page_content(
+            &doc,
+            page_id,
+            1,
+            &FontCMaps::default(),
+            &OptionalContent::default(),
+            true,
+        )
+        .unwrap();
+        assert!(items.is_empty());
+        // The clipped-away line and the unpainted path are not captured
+        assert_eq!(graphics.len(), 4);
+
+        let shade = &graphics[0];
+        assert_eq!(shade.kind, GraphicKind::Rect);
+        assert!(shade.filled && !shade.stroked);
+        assert_eq!(
+            (shade.x1, shade.y1, shade.x2, shade.y2),
+            (72.0, 600.0, 272.0, 620.0)
+        );
+        assert!((shade.fill_color[0] - 0.9).abs() < 0.01);
+
+        // Scaled by the CTM, width included
+        let rule = &graphics[1];
+        assert_eq!(rule.kind, GraphicKind::Line);
+        assert_eq!(
+            (rule.x1, rule.y1, rule.x2, rule.y2),
+            (72.0, 500.0, 272.0, 500.0)
+        );
+        assert_eq!(rule.line_width, 1.0);
+        assert_eq!(rule.stroke_color, [1.0, 0.0, 0.0]);
+        assert!(rule.is_horizontal_rule(1.0));
+
+        // Rotated a quarter turn: a vertical line
+        let rotated = &graphics[2];
+        assert!(rotated.is_vertical_rule(1.0));
+        assert!((rotated.height() - 50.0).abs() < 0.01);
+
+        assert_eq!(graphics[3].kind, GraphicKind::Shape);
+        assert_eq!((graphics[3].width(), graphics[3].height()), (40.0, 20.0));
+
+        // Plain text extraction does not collect graphics
+        let (_, graphics) = extract_page_content(
+            &doc,
+            page_id,
+            1,
+            &FontCMaps::default(),
+            &OptionalContent::default(),
+            false,
+        )
+        .unwrap();
+        assert!(graphics.is_empty());
+    }
 }
@@ -0,0 +1,690 @@
+//! Built-in metrics for the standard 14 PDF fonts
+//!
+//! Non-embedded standard fonts (Helvetica, Times-Roman, Courier, Symbol, ...)
+//! often carry no `/Widths` array; viewers fall back on the Adobe Core14 AFM
+//! files instead. This module bundles the advance widths from those files so
+//! glyph widths can be computed for such fonts and their common aliases
+//! (Arial, TimesNewRoman, CourierNew).
+
+use crate::extractor::{is_bold_font, is_italic_font};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+/// One of the standard 14 fonts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StandardFont {
+    Courier,
+    CourierBold,
+    CourierOblique,
+    CourierBoldOblique,
+    Helvetica,
+    HelveticaBold,
+    HelveticaOblique,
+    HelveticaBoldOblique,
+    TimesRoman,
+    TimesBold,
+    TimesItalic,
+    TimesBoldItalic,
+    Symbol,
+    ZapfDingbats,
+}
+
+/// Font family of a standard font, used while resolving aliases
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Family {
+    Courier,
+    Helvetica,
+    Times,
+}
+
+impl StandardFont {
+    /// Resolve a `/BaseFont` name to a standard font.
+    ///
+    /// Accepts the canonical names ("Helvetica-BoldOblique"), subset-prefixed
+    /// names ("ABCDEF+Helvetica"), and the usual Windows aliases ("Arial,Bold",
+    /// "ArialMT", "TimesNewRomanPS-ItalicMT", "CourierNew").
+    pub fn from_base_font(base_font: &str) -> Option<Self> {
+        // Strip a subset tag like "ABCDEF+"
+        let name = match base_font.split_once('+') {
+            Some((tag, rest)) if tag.len() == 6 && tag.chars().all(|c| c.is_ascii_uppercase()) => {
+                rest
+            }
+            _ => base_font,
+        };
+        let lower: String = name
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_lowercase();
+
+        if lower.starts_with("symbol") {
+            return Some(StandardFont::Symbol);
+        }
+        if lower.starts_with("zapfdingbats") || lower == "dingbats" {
+            return Some(StandardFont::ZapfDingbats);
+        }
+
+        // Condensed/narrow variants have different metrics; don't guess
+        if lower.contains("narrow") || lower.contains("condensed") || lower.contains("black") {
+            return None;
+        }
+
+        let family = if lower.starts_with("courier") {
+            Family::Courier
+        } else if lower.starts_with("helvetica") || lower.starts_with("arial") {
+            Family::Helvetica
+        } else if lower.starts_with("times") {
+            Family::Times
+        } else {
+            return None;
+        };
+
+        let bold = is_bold_font(name);
+        let italic = is_italic_font(name);
+        Some(match (family, bold, italic) {
+            (Family::Courier, false, false) => StandardFont::Courier,
+            (Family::Courier, true, false) => StandardFont::CourierBold,
+            (Family::Courier, false, true) => StandardFont::CourierOblique,
+            (Family::Courier, true, true) => StandardFont::CourierBoldOblique,
+            (Family::Helvetica, false, false) => StandardFont::Helvetica,
+            (Family::Helvetica, true, false) => StandardFont::HelveticaBold,
+            (Family::Helvetica, false, true) => StandardFont::HelveticaOblique,
+            (Family::Helvetica, true, true) => StandardFont::HelveticaBoldOblique,
+            (Family::Times, false, false) => StandardFont::TimesRoman,
+            (Family::Times, true, false) => StandardFont::TimesBold,
+            (Family::Times, false, true) => StandardFont::TimesItalic,
+            (Family::Times, true, true) => StandardFont::TimesBoldItalic,
+        })
+    }
+
+    /// Canonical PostScript name of the font
+    pub fn name(self) -> &'static str {
+        match self {
+            StandardFont::Courier => "Courier",
+            StandardFont::CourierBold => "Courier-Bold",
+            StandardFont::CourierOblique => "Courier-Oblique",
+            StandardFont::CourierBoldOblique => "Courier-BoldOblique",
+            StandardFont::Helvetica => "Helvetica",
+            StandardFont::HelveticaBold => "Helvetica-Bold",
+            StandardFont::HelveticaOblique => "Helvetica-Oblique",
+            StandardFont::HelveticaBoldOblique => "Helvetica-BoldOblique",
+            StandardFont::TimesRoman => "Times-Roman",
+            StandardFont::TimesBold => "Times-Bold",
+            StandardFont::TimesItalic => "Times-Italic",
+            StandardFont::TimesBoldItalic => "Times-BoldItalic",
+            StandardFont::Symbol => "Symbol",
+            StandardFont::ZapfDingbats => "ZapfDingbats",
+        }
+    }
+
+    /// Whether the font uses its own built-in encoding (Symbol, ZapfDingbats)
+    pub fn is_symbolic(self) -> bool {
+        matches!(self, StandardFont::Symbol | StandardFont::ZapfDingbats)
+    }
+
+    /// Advance width of a glyph in 1000ths of an em
+    pub fn glyph_width(self, glyph: &str) -> Option<u16> {
+        match self {
+            StandardFont::Symbol => SYMBOL_GLYPHS
+                .iter()
+                .find(|(_, _, name)| *name == glyph)
+                .map(|(_, w, _)| *w),
+            StandardFont::ZapfDingbats => ZAPF_DINGBATS_GLYPHS
+                .iter()
+                .find(|(_, _, name)| *name == glyph)
+                .map(|(_, w, _)| *w),
+            _ => {
+                let idx = *LATIN_GLYPH_INDEX.get(glyph)?;
+                Some(match self.latin_widths() {
+                    Some(widths) => widths[idx],
+                    None => 600, // Courier is fixed-pitch
+                })
+            }
+        }
+    }
+
+    /// Build a character code → width map for this font.
+    ///
+    /// `base_encoding` is the `/Encoding` (or `/BaseEncoding`) name, if any;
+    /// `differences` are the code → glyph name overrides from a `/Differences`
+    /// array. Without a base encoding the font's built-in encoding is used.
+    pub fn code_widths(
+        self,
+        base_encoding: Option<&[u8]>,
+        differences: &[(u8, String)],
+    ) -> HashMap<u16, u16> {
+        let mut widths = HashMap::new();
+
+        match self {
+            StandardFont::Symbol | StandardFont::ZapfDingbats => {
+                // Symbolic fonts ignore named base encodings
+                let glyphs: &[(u8, u16, &str)] = if self == StandardFont::Symbol {
+                    &SYMBOL_GLYPHS
+                } else {
+                    &ZAPF_DINGBATS_GLYPHS
+                };
+                for &(code, w, _) in glyphs {
+                    widths.insert(code as u16, w);
+                }
+            }
+            _ => {
+                let encoding: Vec<(u8, &str)> = match base_encoding {
+                    Some(b"WinAnsiEncoding") => WIN_ANSI_ENCODING.to_vec(),
+                    Some(b"MacRomanEncoding") => MAC_ROMAN_ENCODING.to_vec(),
+                    _ => LATIN_GLYPHS
+                        .iter()
+                        .filter(|(code, _)| *code >= 0)
+                        .map(|&(code, name)| (code as u8, name))
+                        .collect(),
+                };
+                for (code, glyph) in encoding {
+                    if let Some(w) = self.glyph_width(glyph) {
+                        widths.insert(code as u16, w);
+                    }
+                }
+            }
+        }
+
+        for (code, glyph) in differences {
+            match self.glyph_width(glyph) {
+                Some(w) => widths.insert(*code as u16, w),
+                None => widths.remove(&(*code as u16)),
+            };
+        }
+
+        widths
+    }
+
+    /// Width table for the proportional Latin fonts (None for Courier and symbolic fonts)
+    fn latin_widths(self) -> Option<&'static [u16; 315]> {
+        match self {
+            StandardFont::Helvetica | StandardFont::HelveticaOblique => Some(&HELVETICA_WIDTHS),
+            StandardFont::HelveticaBold | StandardFont::HelveticaBoldOblique => {
+                Some(&HELVETICA_BOLD_WIDTHS)
+            }
+            StandardFont::TimesRoman => Some(&TIMES_ROMAN_WIDTHS),
+            StandardFont::TimesBold => Some(&TIMES_BOLD_WIDTHS),
+            StandardFont::TimesItalic => Some(&TIMES_ITALIC_WIDTHS),
+            StandardFont::TimesBoldItalic => Some(&TIMES_BOLD_ITALIC_WIDTHS),
+            _ => None,
+        }
+    }
+}
+
+/// Glyph name → index into the Latin width tables
+static LATIN_GLYPH_INDEX: LazyLock<HashMap<&'static str, usize>> = LazyLock::new(|| {
+    LATIN_GLYPHS
+        .iter()
+        .enumerate()
+        .map(|(idx, (_, name))| (*name, idx))
+        .collect()
+});
+
+/// Glyph names shared by the Latin core fonts (Courier, Helvetica, Times),
+/// paired with their code in the built-in StandardEncoding (-1 = unencoded).
+/// The per-font width tables below are indexed in the same order.
+#[rustfmt::skip]
+static LATIN_GLYPHS: [(i16, &str); 315] = [
+    (32, "space"), (33, "exclam"), (34, "quotedbl"), (35, "numbersign"), (36, "dollar"), (37, "percent"),
+    (38, "ampersand"), (39, "quoteright"), (40, "parenleft"), (41, "parenright"), (42, "asterisk"), (43, "plus"),
+    (44, "comma"), (45, "hyphen"), (46, "period"), (47, "slash"), (48, "zero"), (49, "one"),
+    (50, "two"), (51, "three"), (52, "four"), (53, "five"), (54, "six"), (55, "seven"),
+    (56, "eight"), (57, "nine"), (58, "colon"), (59, "semicolon"), (60, "less"), (61, "equal"),
+    (62, "greater"), (63, "question"), (64, "at"), (65, "A"), (66, "B"), (67, "C"),
+    (68, "D"), (69, "E"), (70, "F"), (71, "G"), (72, "H"), (73, "I"),
+    (74, "J"), (75, "K"), (76, "L"), (77, "M"), (78, "N"), (79, "O"),
+    (80, "P"), (81, "Q"), (82, "R"), (83, "S"), (84, "T"), (85, "U"),
+    (86, "V"), (87, "W"), (88, "X"), (89, "Y"), (90, "Z"), (91, "bracketleft"),
+    (92, "backslash"), (93, "bracketright"), (94, "asciicircum"), (95, "underscore"), (96, "quoteleft"), (97, "a"),
+    (98, "b"), (99, "c"), (100, "d"), (101, "e"), (102, "f"), (103, "g"),
+    (104, "h"), (105, "i"), (106, "j"), (107, "k"), (108, "l"), (109, "m"),
+    (110, "n"), (111, "o"), (112, "p"), (113, "q"), (114, "r"), (115, "s"),
+    (116, "t"), (117, "u"), (118, "v"), (119, "w"), (120, "x"), (121, "y"),
+    (122, "z"), (123, "braceleft"), (124, "bar"), (125, "braceright"), (126, "asciitilde"), (161, "exclamdown"),
+    (162, "cent"), (163, "sterling"), (164, "fraction"), (165, "yen"), (166, "florin"), (167, "section"),
+    (168, "currency"), (169, "quotesingle"), (170, "quotedblleft"), (171, "guillemotleft"), (172, "guilsinglleft"), (173, "guilsinglright"),
+    (174, "fi"), (175, "fl"), (177, "endash"), (178, "dagger"), (179, "daggerdbl"), (180, "periodcentered"),
+    (182, "paragraph"), (183, "bullet"), (184, "quotesinglbase"), (185, "quotedblbase"), (186, "quotedblright"), (187, "guillemotright"),
+    (188, "ellipsis"), (189, "perthousand"), (191, "questiondown"), (193, "grave"), (194, "acute"), (195, "circumflex"),
+    (196, "tilde"), (197, "macron"), (198, "breve"), (199, "dotaccent"), (200, "dieresis"), (202, "ring"),
+    (203, "cedilla"), (205, "hungarumlaut"), (206, "ogonek"), (207, "caron"), (208, "emdash"), (225, "AE"),
+    (227, "ordfeminine"), (232, "Lslash"), (233, "Oslash"), (234, "OE"), (235, "ordmasculine"), (241, "ae"),
+    (245, "dotlessi"), (248, "lslash"), (249, "oslash"), (250, "oe"), (251, "germandbls"), (-1, "Idieresis"),
+    (-1, "eacute"), (-1, "abreve"), (-1, "uhungarumlaut"), (-1, "ecaron"), (-1, "Ydieresis"), (-1, "divide"),
+    (-1, "Yacute"), (-1, "Acircumflex"), (-1, "aacute"), (-1, "Ucircumflex"), (-1, "yacute"), (-1, "scommaaccent"),
+    (-1, "ecircumflex"), (-1, "Uring"), (-1, "Udieresis"), (-1, "aogonek"), (-1, "Uacute"), (-1, "uogonek"),
+    (-1, "Edieresis"), (-1, "Dcroat"), (-1, "commaaccent"), (-1, "copyright"), (-1, "Emacron"), (-1, "ccaron"),
+    (-1, "aring"), (-1, "Ncommaaccent"), (-1, "lacute"), (-1, "agrave"), (-1, "Tcommaaccent"), (-1, "Cacute"),
+    (-1, "atilde"), (-1, "Edotaccent"), (-1, "scaron"), (-1, "scedilla"), (-1, "iacute"), (-1, "lozenge"),
+    (-1, "Rcaron"), (-1, "Gcommaaccent"), (-1, "ucircumflex"), (-1, "acircumflex"), (-1, "Amacron"), (-1, "rcaron"),
+    (-1, "ccedilla"), (-1, "Zdotaccent"), (-1, "Thorn"), (-1, "Omacron"), (-1, "Racute"), (-1, "Sacute"),
+    (-1, "dcaron"), (-1, "Umacron"), (-1, "uring"), (-1, "threesuperior"), (-1, "Ograve"), (-1, "Agrave"),
+    (-1, "Abreve"), (-1, "multiply"), (-1, "uacute"), (-1, "Tcaron"), (-1, "partialdiff"), (-1, "ydieresis"),
+    (-1, "Nacute"), (-1, "icircumflex"), (-1, "Ecircumflex"), (-1, "adieresis"), (-1, "edieresis"), (-1, "cacute"),
+    (-1, "nacute"), (-1, "umacron"), (-1, "Ncaron"), (-1, "Iacute"), (-1, "plusminus"), (-1, "brokenbar"),
+    (-1, "registered"), (-1, "Gbreve"), (-1, "Idotaccent"), (-1, "summation"), (-1, "Egrave"), (-1, "racute"),
+    (-1, "omacron"), (-1, "Zacute"), (-1, "Zcaron"), (-1, "greaterequal"), (-1, "Eth"), (-1, "Ccedilla"),
+    (-1, "lcommaaccent"), (-1, "tcaron"), (-1, "eogonek"), (-1, "Uogonek"), (-1, "Aacute"), (-1, "Adieresis"),
+    (-1, "egrave"), (-1, "zacute"), (-1, "iogonek"), (-1, "Oacute"), (-1, "oacute"), (-1, "amacron"),
+    (-1, "sacute"), (-1, "idieresis"), (-1, "Ocircumflex"), (-1, "Ugrave"), (-1, "Delta"), (-1, "thorn"),
+    (-1, "twosuperior"), (-1, "Odieresis"), (-1, "mu"), (-1, "igrave"), (-1, "ohungarumlaut"), (-1, "Eogonek"),
+    (-1, "dcroat"), (-1, "threequarters"), (-1, "Scedilla"), (-1, "lcaron"), (-1, "Kcommaaccent"), (-1, "Lacute"),
+    (-1, "trademark"), (-1, "edotaccent"), (-1, "Igrave"), (-1, "Imacron"), (-1, "Lcaron"), (-1, "onehalf"),
+    (-1, "lessequal"), (-1, "ocircumflex"), (-1, "ntilde"), (-1, "Uhungarumlaut"), (-1, "Eacute"), (-1, "emacron"),
+    (-1, "gbreve"), (-1, "onequarter"), (-1, "Scaron"), (-1, "Scommaaccent"), (-1, "Ohungarumlaut"), (-1, "degree"),
+    (-1, "ograve"), (-1, "Ccaron"), (-1, "ugrave"), (-1, "radical"), (-1, "Dcaron"), (-1, "rcommaaccent"),
+    (-1, "Ntilde"), (-1, "otilde"), (-1, "Rcommaaccent"), (-1, "Lcommaaccent"), (-1, "Atilde"), (-1, "Aogonek"),
+    (-1, "Aring"), (-1, "Otilde"), (-1, "zdotaccent"), (-1, "Ecaron"), (-1, "Iogonek"), (-1, "kcommaaccent"),
+    (-1, "minus"), (-1, "Icircumflex"), (-1, "ncaron"), (-1, "tcommaaccent"), (-1, "logicalnot"), (-1, "odieresis"),
+    (-1, "udieresis"), (-1, "notequal"), (-1, "gcommaaccent"), (-1, "eth"), (-1, "zcaron"), (-1, "ncommaaccent"),
+    (-1, "onesuperior"), (-1, "imacron"), (-1, "Euro"),
+];
+
+#[rustfmt::skip]
+static HELVETICA_WIDTHS: [u16; 315] = [
+    278, 278, 355, 556, 556, 889, 667, 222, 333, 333, 389, 584, 278, 333,
+    278, 278, 556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278,
+    584, 584, 584, 556, 1015, 667, 667, 722, 722, 667, 611, 778, 722, 278,
+    500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944,
+    667, 667, 611, 278, 278, 278, 469, 556, 222, 556, 556, 500, 556, 556,
+    278, 556, 556, 222, 222, 500, 222, 833, 556, 556, 556, 556, 333, 500,
+    278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584, 333, 556, 556,
+    167, 556, 556, 556, 556, 191, 333, 556, 333, 333, 500, 500, 556, 556,
+    556, 278, 537, 350, 222, 333, 333, 556, 1000, 1000, 611, 333, 333, 333,
+    333, 333, 333, 333, 333, 333, 333, 333, 333, 333, 1000, 1000, 370, 556,
+    778, 1000, 365, 889, 278, 222, 611, 944, 611, 278, 556, 556, 556, 556,
+    667, 584, 667, 667, 556, 722, 500, 500, 556, 722, 722, 556, 722, 556,
+    667, 722, 250, 737, 667, 500, 556, 722, 222, 556, 611, 722, 556, 667,
+    500, 500, 278, 471, 722, 778, 556, 556, 667, 333, 500, 611, 667, 778,
+    722, 667, 643, 722, 556, 333, 778, 667, 667, 584, 556, 611, 476, 500,
+    722, 278, 667, 556, 556, 500, 556, 556, 722, 278, 584, 260, 737, 778,
+    278, 600, 667, 333, 556, 611, 611, 549, 722, 722, 222, 317, 556, 722,
+    667, 667, 556, 500, 222, 778, 556, 556, 500, 278, 778, 722, 612, 556,
+    333, 778, 556, 278, 556, 667, 556, 834, 667, 299, 667, 556, 1000, 556,
+    278, 278, 556, 834, 549, 556, 556, 722, 667, 556, 556, 834, 667, 667,
+    778, 400, 556, 722, 556, 453, 722, 333, 722, 556, 722, 556, 667, 667,
+    667, 778, 500, 667, 278, 500, 584, 278, 556, 278, 584, 556, 556, 549,
+    556, 556, 500, 556, 333, 278, 556,
+];
+
+#[rustfmt::skip]
+static HELVETICA_BOLD_WIDTHS: [u16; 315] = [
+    278, 333, 474, 556, 556, 889, 722, 278, 333, 333, 389, 584, 278, 333,
+    278, 278, 556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333,
+    584, 584, 584, 611, 975, 722, 722, 722, 722, 667, 611, 778, 722, 278,
+    556, 722, 611, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944,
+    667, 667, 611, 333, 278, 333, 584, 556, 278, 556, 611, 556, 611, 556,
+    333, 611, 611, 278, 278, 556, 278, 889, 611, 611, 611, 611, 389, 556,
+    333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584, 333, 556, 556,
+    167, 556, 556, 556, 556, 238, 500, 556, 333, 333, 611, 611, 556, 556,
+    556, 278, 556, 350, 278, 500, 500, 556, 1000, 1000, 611, 333, 333, 333,
+    333, 333, 333, 333, 333, 333, 333, 333, 333, 333, 1000, 1000, 370, 611,
+    778, 1000, 365, 889, 278, 278, 611, 944, 611, 278, 556, 556, 611, 556,
+    667, 584, 667, 722, 556, 722, 556, 556, 556, 722, 722, 556, 722, 611,
+    667, 722, 250, 737, 667, 556, 556, 722, 278, 556, 611, 722, 556, 667,
+    556, 556, 278, 494, 722, 778, 611, 556, 722, 389, 556, 611, 667, 778,
+    722, 667, 743, 722, 611, 333, 778, 722, 722, 584, 611, 611, 494, 556,
+    722, 278, 667, 556, 556, 556, 611, 611, 722, 278, 584, 280, 737, 778,
+    278, 600, 667, 389, 611, 611, 611, 549, 722, 722, 278, 389, 556, 722,
+    722, 722, 556, 500, 278, 778, 611, 556, 556, 278, 778, 722, 612, 611,
+    333, 778, 611, 278, 611, 667, 611, 834, 667, 400, 722, 611, 1000, 556,
+    278, 278, 611, 834, 549, 611, 611, 722, 667, 556, 611, 834, 667, 667,
+    778, 400, 611, 722, 611, 549, 722, 389, 722, 611, 722, 611, 722, 722,
+    722, 778, 500, 667, 278, 556, 584, 278, 611, 333, 584, 611, 611, 549,
+    611, 611, 500, 611, 333, 278, 556,
+];
+
+#[rustfmt::skip]
+static TIMES_ROMAN_WIDTHS: [u16; 315] = [
+    250, 333, 408, 500, 500, 833, 778, 333, 333, 333, 500, 564, 250, 333,
+    250, 278, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 278, 278,
+    564, 564, 564, 444, 921, 722, 667, 667, 722, 611, 556, 722, 722, 333,
+    389, 722, 611, 889, 722, 722, 556, 722, 667, 556, 611, 722, 722, 944,
+    722, 722, 611, 333, 278, 333, 469, 500, 333, 444, 500, 444, 500, 444,
+    333, 500, 500, 278, 278, 500, 278, 778, 500, 500, 500, 500, 333, 389,
+    278, 500, 500, 722, 500, 500, 444, 480, 200, 480, 541, 333, 500, 500,
+    167, 500, 500, 500, 500, 180, 444, 500, 333, 333, 556, 556, 500, 500,
+    500, 250, 453, 350, 333, 444, 444, 500, 1000, 1000, 444, 333, 333, 333,
+    333, 333, 333, 333, 333, 333, 333, 333, 333, 333, 1000, 889, 276, 611,
+    722, 889, 310, 667, 278, 278, 500, 722, 500, 333, 444, 444, 500, 444,
+    722, 564, 722, 722, 444, 722, 500, 389, 444, 722, 722, 444, 722, 500,
+    611, 722, 250, 760, 611, 444, 444, 722, 278, 444, 611, 667, 444, 611,
+    389, 389, 278, 471, 667, 722, 500, 444, 722, 333, 444, 611, 556, 722,
+    667, 556, 588, 722, 500, 300, 722, 722, 722, 564, 500, 611, 476, 500,
+    722, 278, 611, 444, 444, 444, 500, 500, 722, 333, 564, 200, 760, 722,
+    333, 600, 611, 333, 500, 611, 611, 549, 722, 667, 278, 326, 444, 722,
+    722, 722, 444, 444, 278, 722, 500, 444, 389, 278, 722, 722, 612, 500,
+    300, 722, 500, 278, 500, 611, 500, 750, 556, 344, 722, 611, 980, 444,
+    333, 333, 611, 750, 549, 500, 500, 722, 611, 444, 500, 750, 556, 556,
+    722, 400, 500, 667, 500, 453, 722, 333, 722, 500, 667, 611, 722, 722,
+    722, 722, 444, 611, 333, 500, 564, 333, 500, 278, 564, 500, 500, 549,
+    500, 500, 444, 500, 300, 278, 500,
+];
+
+#[rustfmt::skip]
+static TIMES_BOLD_WIDTHS: [u16; 315] = [
+    250, 333, 555, 500, 500, 1000, 833, 333, 333, 333, 500, 570, 250, 333,
+    250, 278, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 333, 333,
+    570, 570, 570, 500, 930, 722, 667, 722, 722, 667, 611, 778, 778, 389,
+    500, 778, 667, 944, 722, 778, 611, 778, 722, 556, 667, 722, 722, 1000,
+    722, 722, 667, 333, 278, 333, 581, 500, 333, 500, 556, 444, 556, 444,
+    333, 500, 556, 278, 333, 556, 278, 833, 556, 500, 556, 556, 444, 389,
+    333, 556, 500, 722, 500, 500, 444, 394, 220, 394, 520, 333, 500, 500,
+    167, 500, 500, 500, 500, 278, 500, 500, 333, 333, 556, 556, 500, 500,
+    500, 250, 540, 350, 333, 500, 500, 500, 1000, 1000, 500, 333, 333, 333,
+    333, 333, 333, 333, 333, 333, 333, 333, 333, 333, 1000, 1000, 300, 667,
+    778, 1000, 330, 722, 278, 278, 500, 722, 556, 389, 444, 500, 556, 444,
+    722, 570, 722, 722, 500, 722, 500, 389, 444, 722, 722, 500, 722, 556,
+    667, 722, 250, 747, 667, 444, 500, 722, 278, 500, 667, 722, 500, 667,
+    389, 389, 278, 494, 722, 778, 556, 500, 722, 444, 444, 667, 611, 778,
+    722, 556, 672, 722, 556, 300, 778, 722, 722, 570, 556, 667, 494, 500,
+    722, 278, 667, 500, 444, 444, 556, 556, 722, 389, 570, 220, 747, 778,
+    389, 600, 667, 444, 500, 667, 667, 549, 722, 722, 278, 416, 444, 722,
+    722, 722, 444, 444, 278, 778, 500, 500, 389, 278, 778, 722, 612, 556,
+    300, 778, 556, 278, 500, 667, 556, 750, 556, 394, 778, 667, 1000, 444,
+    389, 389, 667, 750, 549, 500, 556, 722, 667, 444, 500, 750, 556, 556,
+    778, 400, 500, 722, 556, 549, 722, 444, 722, 500, 722, 667, 722, 722,
+    722, 778, 444, 667, 389, 556, 570, 389, 556, 333, 570, 500, 556, 549,
+    500, 500, 444, 556, 300, 278, 500,
+];
+
+#[rustfmt::skip]
+static TIMES_ITALIC_WIDTHS: [u16; 315] = [
+    250, 333, 420, 500, 500, 833, 778, 333, 333, 333, 500, 675, 250, 333,
+    250, 278, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 333, 333,
+    675, 675, 675, 500, 920, 611, 611, 667, 722, 611, 611, 722, 722, 333,
+    444, 667, 556, 833, 667, 722, 611, 722, 611, 500, 556, 722, 611, 833,
+    611, 556, 556, 389, 278, 389, 422, 500, 333, 500, 500, 444, 500, 444,
+    278, 500, 500, 278, 278, 444, 278, 722, 500, 500, 500, 500, 389, 389,
+    278, 500, 444, 667, 444, 444, 389, 400, 275, 400, 541, 389, 500, 500,
+    167, 500, 500, 500, 500, 214, 556, 500, 333, 333, 500, 500, 500, 500,
+    500, 250, 523, 350, 333, 556, 556, 500, 889, 1000, 500, 333, 333, 333,
+    333, 333, 333, 333, 333, 333, 333, 333, 333, 333, 889, 889, 276, 556,
+    722, 944, 310, 667, 278, 278, 500, 667, 500, 333, 444, 500, 500, 444,
+    556, 675, 556, 611, 500, 722, 444, 389, 444, 722, 722, 500, 722, 500,
+    611, 722, 250, 760, 611, 444, 500, 667, 278, 500, 556, 667, 500, 611,
+    389, 389, 278, 471, 611, 722, 500, 500, 611, 389, 444, 556, 611, 722,
+    611, 500, 544, 722, 500, 300, 722, 611, 611, 675, 500, 556, 476, 444,
+    667, 278, 611, 500, 444, 444, 500, 500, 667, 333, 675, 275, 760, 722,
+    333, 600, 611, 389, 500, 556, 556, 549, 722, 667, 278, 300, 444, 722,
+    611, 611, 444, 389, 278, 722, 500, 500, 389, 278, 722, 722, 612, 500,
+    300, 722, 500, 278, 500, 611, 500, 750, 500, 300, 667, 556, 980, 444,
+    333, 333, 611, 750, 549, 500, 500, 722, 611, 444, 500, 750, 500, 500,
+    722, 400, 500, 667, 500, 453, 722, 389, 667, 500, 611, 556, 611, 611,
+    611, 722, 389, 611, 333, 444, 675, 333, 500, 278, 675, 500, 500, 549,
+    500, 500, 389, 500, 300, 278, 500,
+];
+
+#[rustfmt::skip]
+static TIMES_BOLD_ITALIC_WIDTHS: [u16; 315] = [
+    250, 389, 555, 500, 500, 833, 778, 333, 333, 333, 500, 570, 250, 333,
+    250, 278, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 333, 333,
+    570, 570, 570, 500, 832, 667, 667, 667, 722, 667, 667, 722, 778, 389,
+    500, 667, 611, 889, 722, 722, 611, 722, 667, 556, 611, 722, 667, 889,
+    667, 611, 611, 333, 278, 333, 570, 500, 333, 500, 500, 444, 500, 444,
+    333, 500, 556, 278, 278, 500, 278, 778, 556, 500, 500, 500, 389, 389,
+    278, 556, 444, 667, 500, 444, 389, 348, 220, 348, 570, 389, 500, 500,
+    167, 500, 500, 500, 500, 278, 500, 500, 333, 333, 556, 556, 500, 500,
+    500, 250, 500, 350, 333, 500, 500, 500, 1000, 1000, 500, 333, 333, 333,
+    333, 333, 333, 333, 333, 333, 333, 333, 333, 333, 1000, 944, 266, 611,
+    722, 944, 300, 722, 278, 278, 500, 722, 500, 389, 444, 500, 556, 444,
+    611, 570, 611, 667, 500, 722, 444, 389, 444, 722, 722, 500, 722, 556,
+    667, 722, 250, 747, 667, 444, 500, 722, 278, 500, 611, 667, 500, 667,
+    389, 389, 278, 494, 667, 722, 556, 500, 667, 389, 444, 611, 611, 722,
+    667, 556, 608, 722, 556, 300, 722, 667, 667, 570, 556, 611, 494, 444,
+    722, 278, 667, 500, 444, 444, 556, 556, 722, 389, 570, 220, 747, 722,
+    389, 600, 667, 389, 500, 611, 611, 549, 722, 667, 278, 366, 444, 722,
+    667, 667, 444, 389, 278, 722, 500, 500, 389, 278, 722, 722, 612, 500,
+    300, 722, 576, 278, 500, 667, 500, 750, 556, 382, 667, 611, 1000, 444,
+    389, 389, 611, 750, 549, 500, 556, 722, 667, 444, 500, 750, 556, 556,
+    722, 400, 500, 667, 556, 549, 722, 389, 722, 500, 667, 611, 667, 667,
+    667, 722, 389, 667, 389, 500, 606, 389, 556, 278, 606, 500, 556, 549,
+    500, 500, 389, 556, 300, 278, 500,
+];
+
+/// Built-in encoding of Symbol: (code, width, glyph name).
+#[rustfmt::skip]
+static SYMBOL_GLYPHS: [(u8, u16, &str); 189] = [
+    (32, 250, "space"), (33, 333, "exclam"), (34, 713, "universal"), (35, 500, "numbersign"), (36, 549, "existential"),
+    (37, 833, "percent"), (38, 778, "ampersand"), (39, 439, "suchthat"), (40, 333, "parenleft"), (41, 333, "parenright"),
+    (42, 500, "asteriskmath"), (43, 549, "plus"), (44, 250, "comma"), (45, 549, "minus"), (46, 250, "period"),
+    (47, 278, "slash"), (48, 500, "zero"), (49, 500, "one"), (50, 500, "two"), (51, 500, "three"),
+    (52, 500, "four"), (53, 500, "five"), (54, 500, "six"), (55, 500, "seven"), (56, 500, "eight"),
+    (57, 500, "nine"), (58, 278, "colon"), (59, 278, "semicolon"), (60, 549, "less"), (61, 549, "equal"),
+    (62, 549, "greater"), (63, 444, "question"), (64, 549, "congruent"), (65, 722, "Alpha"), (66, 667, "Beta"),
+    (67, 722, "Chi"), (68, 612, "Delta"), (69, 611, "Epsilon"), (70, 763, "Phi"), (71, 603, "Gamma"),
+    (72, 722, "Eta"), (73, 333, "Iota"), (74, 631, "theta1"), (75, 722, "Kappa"), (76, 686, "Lambda"),
+    (77, 889, "Mu"), (78, 722, "Nu"), (79, 722, "Omicron"), (80, 768, "Pi"), (81, 741, "Theta"),
+    (82, 556, "Rho"), (83, 592, "Sigma"), (84, 611, "Tau"), (85, 690, "Upsilon"), (86, 439, "sigma1"),
+    (87, 768, "Omega"), (88, 645, "Xi"), (89, 795, "Psi"), (90, 611, "Zeta"), (91, 333, "bracketleft"),
+    (92, 863, "therefore"), (93, 333, "bracketright"), (94, 658, "perpendicular"), (95, 500, "underscore"), (96, 500, "radicalex"),
+    (97, 631, "alpha"), (98, 549, "beta"), (99, 549, "chi"), (100, 494, "delta"), (101, 439, "epsilon"),
+    (102, 521, "phi"), (103, 411, "gamma"), (104, 603, "eta"), (105, 329, "iota"), (106, 603, "phi1"),
+    (107, 549, "kappa"), (108, 549, "lambda"), (109, 576, "mu"), (110, 521, "nu"), (111, 549, "omicron"),
+    (112, 549, "pi"), (113, 521, "theta"), (114, 549, "rho"), (115, 603, "sigma"), (116, 439, "tau"),
+    (117, 576, "upsilon"), (118, 713, "omega1"), (119, 686, "omega"), (120, 493, "xi"), (121, 686, "psi"),
+    (122, 494, "zeta"), (123, 480, "braceleft"), (124, 200, "bar"), (125, 480, "braceright"), (126, 549, "similar"),
+    (160, 750, "Euro"), (161, 620, "Upsilon1"), (162, 247, "minute"), (163, 549, "lessequal"), (164, 167, "fraction"),
+    (165, 713, "infinity"), (166, 500, "florin"), (167, 753, "club"), (168, 753, "diamond"), (169, 753, "heart"),
+    (170, 753, "spade"), (171, 1042, "arrowboth"), (172, 987, "arrowleft"), (173, 603, "arrowup"), (174, 987, "arrowright"),
+    (175, 603, "arrowdown"), (176, 400, "degree"), (177, 549, "plusminus"), (178, 411, "second"), (179, 549, "greaterequal"),
+    (180, 549, "multiply"), (181, 713, "proportional"), (182, 494, "partialdiff"), (183, 460, "bullet"), (184, 549, "divide"),
+    (185, 549, "notequal"), (186, 549, "equivalence"), (187, 549, "approxequal"), (188, 1000, "ellipsis"), (189, 603, "arrowvertex"),
+    (190, 1000, "arrowhorizex"), (191, 658, "carriagereturn"), (192, 823, "aleph"), (193, 686, "Ifraktur"), (194, 795, "Rfraktur"),
+    (195, 987, "weierstrass"), (196, 768, "circlemultiply"), (197, 768, "circleplus"), (198, 823, "emptyset"), (199, 768, "intersection"),
+    (200, 768, "union"), (201, 713, "propersuperset"), (202, 713, "reflexsuperset"), (203, 713, "notsubset"), (204, 713, "propersubset"),
+    (205, 713, "reflexsubset"), (206, 713, "element"), (207, 713, "notelement"), (208, 768, "angle"), (209, 713, "gradient"),
+    (210, 790, "registerserif"), (211, 790, "copyrightserif"), (212, 890, "trademarkserif"), (213, 823, "product"), (214, 549, "radical"),
+    (215, 250, "dotmath"), (216, 713, "logicalnot"), (217, 603, "logicaland"), (218, 603, "logicalor"), (219, 1042, "arrowdblboth"),
+    (220, 987, "arrowdblleft"), (221, 603, "arrowdblup"), (222, 987, "arrowdblright"), (223, 603, "arrowdbldown"), (224, 494, "lozenge"),
+    (225, 329, "angleleft"), (226, 790, "registersans"), (227, 790, "copyrightsans"), (228, 786, "trademarksans"), (229, 713, "summation"),
+    (230, 384, "parenlefttp"), (231, 384, "parenleftex"), (232, 384, "parenleftbt"), (233, 384, "bracketlefttp"), (234, 384, "bracketleftex"),
+    (235, 384, "bracketleftbt"), (236, 494, "bracelefttp"), (237, 494, "braceleftmid"), (238, 494, "braceleftbt"), (239, 494, "braceex"),
+    (241, 329, "angleright"), (242, 274, "integral"), (243, 686, "integraltp"), (244, 686, "integralex"), (245, 686, "integralbt"),
+    (246, 384, "parenrighttp"), (247, 384, "parenrightex"), (248, 384, "parenrightbt"), (249, 384, "bracketrighttp"), (250, 384, "bracketrightex"),
+    (251, 384, "bracketrightbt"), (252, 494, "bracerighttp"), (253, 494, "bracerightmid"), (254, 494, "bracerightbt"),
+];
+
+/// Built-in encoding of ZapfDingbats: (code, width, glyph name).
+#[rustfmt::skip]
+static ZAPF_DINGBATS_GLYPHS: [(u8, u16, &str); 202] = [
+    (32, 278, "space"), (33, 974, "a1"), (34, 961, "a2"), (35, 974, "a202"), (36, 980, "a3"),
+    (37, 719, "a4"), (38, 789, "a5"), (39, 790, "a119"), (40, 791, "a118"), (41, 690, "a117"),
+    (42, 960, "a11"), (43, 939, "a12"), (44, 549, "a13"), (45, 855, "a14"), (46, 911, "a15"),
+    (47, 933, "a16"), (48, 911, "a105"), (49, 945, "a17"), (50, 974, "a18"), (51, 755, "a19"),
+    (52, 846, "a20"), (53, 762, "a21"), (54, 761, "a22"), (55, 571, "a23"), (56, 677, "a24"),
+    (57, 763, "a25"), (58, 760, "a26"), (59, 759, "a27"), (60, 754, "a28"), (61, 494, "a6"),
+    (62, 552, "a7"), (63, 537, "a8"), (64, 577, "a9"), (65, 692, "a10"), (66, 786, "a29"),
+    (67, 788, "a30"), (68, 788, "a31"), (69, 790, "a32"), (70, 793, "a33"), (71, 794, "a34"),
+    (72, 816, "a35"), (73, 823, "a36"), (74, 789, "a37"), (75, 841, "a38"), (76, 823, "a39"),
+    (77, 833, "a40"), (78, 816, "a41"), (79, 831, "a42"), (80, 923, "a43"), (81, 744, "a44"),
+    (82, 723, "a45"), (83, 749, "a46"), (84, 790, "a47"), (85, 792, "a48"), (86, 695, "a49"),
+    (87, 776, "a50"), (88, 768, "a51"), (89, 792, "a52"), (90, 759, "a53"), (91, 707, "a54"),
+    (92, 708, "a55"), (93, 682, "a56"), (94, 701, "a57"), (95, 826, "a58"), (96, 815, "a59"),
+    (97, 789, "a60"), (98, 789, "a61"), (99, 707, "a62"), (100, 687, "a63"), (101, 696, "a64"),
+    (102, 689, "a65"), (103, 786, "a66"), (104, 787, "a67"), (105, 713, "a68"), (106, 791, "a69"),
+    (107, 785, "a70"), (108, 791, "a71"), (109, 873, "a72"), (110, 761, "a73"), (111, 762, "a74"),
+    (112, 762, "a203"), (113, 759, "a75"), (114, 759, "a204"), (115, 892, "a76"), (116, 892, "a77"),
+    (117, 788, "a78"), (118, 784, "a79"), (119, 438, "a81"), (120, 138, "a82"), (121, 277, "a83"),
+    (122, 415, "a84"), (123, 392, "a97"), (124, 392, "a98"), (125, 668, "a99"), (126, 668, "a100"),
+    (128, 390, "a89"), (129, 390, "a90"), (130, 317, "a93"), (131, 317, "a94"), (132, 276, "a91"),
+    (133, 276, "a92"), (134, 509, "a205"), (135, 509, "a85"), (136, 410, "a206"), (137, 410, "a86"),
+    (138, 234, "a87"), (139, 234, "a88"), (140, 334, "a95"), (141, 334, "a96"), (161, 732, "a101"),
+    (162, 544, "a102"), (163, 544, "a103"), (164, 910, "a104"), (165, 667, "a106"), (166, 760, "a107"),
+    (167, 760, "a108"), (168, 776, "a112"), (169, 595, "a111"), (170, 694, "a110"), (171, 626, "a109"),
+    (172, 788, "a120"), (173, 788, "a121"), (174, 788, "a122"), (175, 788, "a123"), (176, 788, "a124"),
+    (177, 788, "a125"), (178, 788, "a126"), (179, 788, "a127"), (180, 788, "a128"), (181, 788, "a129"),
+    (182, 788, "a130"), (183, 788, "a131"), (184, 788, "a132"), (185, 788, "a133"), (186, 788, "a134"),
+    (187, 788, "a135"), (188, 788, "a136"), (189, 788, "a137"), (190, 788, "a138"), (191, 788, "a139"),
+    (192, 788, "a140"), (193, 788, "a141"), (194, 788, "a142"), (195, 788, "a143"), (196, 788, "a144"),
+    (197, 788, "a145"), (198, 788, "a146"), (199, 788, "a147"), (200, 788, "a148"), (201, 788, "a149"),
+    (202, 788, "a150"), (203, 788, "a151"), (204, 788, "a152"), (205, 788, "a153"), (206, 788, "a154"),
+    (207, 788, "a155"), (208, 788, "a156"), (209, 788, "a157"), (210, 788, "a158"), (211, 788, "a159"),
+    (212, 894, "a160"), (213, 838, "a161"), (214, 1016, "a163"), (215, 458, "a164"), (216, 748, "a196"),
+    (217, 924, "a165"), (218, 748, "a192"), (219, 918, "a166"), (220, 927, "a167"), (221, 928, "a168"),
+    (222, 928, "a169"), (223, 834, "a170"), (224, 873, "a171"), (225, 828, "a172"), (226, 924, "a173"),
+    (227, 924, "a162"), (228, 917, "a174"), (229, 930, "a175"), (230, 931, "a176"), (231, 463, "a177"),
+    (232, 883, "a178"), (233, 836, "a179"), (234, 836, "a193"), (235, 867, "a180"), (236, 867, "a199"),
+    (237, 696, "a181"), (238, 696, "a200"), (239, 874, "a182"), (241, 874, "a201"), (242, 760, "a183"),
+    (243, 946, "a184"), (244, 771, "a197"), (245, 865, "a185"), (246, 771, "a194"), (247, 888, "a198"),
+    (248, 967, "a186"), (249, 888, "a195"), (250, 831, "a187"), (251, 873, "a188"), (252, 927, "a189"),
+    (253, 970, "a190"), (254, 918, "a191"),
+];
+
+/// WinAnsiEncoding: code to glyph name (unlisted codes are undefined).
+#[rustfmt::skip]
+static WIN_ANSI_ENCODING: [(u8, &str); 224] = [
+    (32, "space"), (33, "exclam"), (34, "quotedbl"), (35, "numbersign"), (36, "dollar"), (37, "percent"),
+    (38, "ampersand"), (39, "quotesingle"), (40, "parenleft"), (41, "parenright"), (42, "asterisk"), (43, "plus"),
+    (44, "comma"), (45, "hyphen"), (46, "period"), (47, "slash"), (48, "zero"), (49, "one"),
+    (50, "two"), (51, "three"), (52, "four"), (53, "five"), (54, "six"), (55, "seven"),
+    (56, "eight"), (57, "nine"), (58, "colon"), (59, "semicolon"), (60, "less"), (61, "equal"),
+    (62, "greater"), (63, "question"), (64, "at"), (65, "A"), (66, "B"), (67, "C"),
+    (68, "D"), (69, "E"), (70, "F"), (71, "G"), (72, "H"), (73, "I"),
+    (74, "J"), (75, "K"), (76, "L"), (77, "M"), (78, "N"), (79, "O"),
+    (80, "P"), (81, "Q"), (82, "R"), (83, "S"), (84, "T"), (85, "U"),
+    (86, "V"), (87, "W"), (88, "X"), (89, "Y"), (90, "Z"), (91, "bracketleft"),
+    (92, "backslash"), (93, "bracketright"), (94, "asciicircum"), (95, "underscore"), (96, "grave"), (97, "a"),
+    (98, "b"), (99, "c"), (100, "d"), (101, "e"), (102, "f"), (103, "g"),
+    (104, "h"), (105, "i"), (106, "j"), (107, "k"), (108, "l"), (109, "m"),
+    (110, "n"), (111, "o"), (112, "p"), (113, "q"), (114, "r"), (115, "s"),
+    (116, "t"), (117, "u"), (118, "v"), (119, "w"), (120, "x"), (121, "y"),
+    (122, "z"), (123, "braceleft"), (124, "bar"), (125, "braceright"), (126, "asciitilde"), (127, "bullet"),
+    (128, "Euro"), (129, "bullet"), (130, "quotesinglbase"), (131, "florin"), (132, "quotedblbase"), (133, "ellipsis"),
+    (134, "dagger"), (135, "daggerdbl"), (136, "circumflex"), (137, "perthousand"), (138, "Scaron"), (139, "guilsinglleft"),
+    (140, "OE"), (141, "bullet"), (142, "Zcaron"), (143, "bullet"), (144, "bullet"), (145, "quoteleft"),
+    (146, "quoteright"), (147, "quotedblleft"), (148, "quotedblright"), (149, "bullet"), (150, "endash"), (151, "emdash"),
+    (152, "tilde"), (153, "trademark"), (154, "scaron"), (155, "guilsinglright"), (156, "oe"), (157, "bullet"),
+    (158, "zcaron"), (159, "Ydieresis"), (160, "space"), (161, "exclamdown"), (162, "cent"), (163, "sterling"),
+    (164, "currency"), (165, "yen"), (166, "brokenbar"), (167, "section"), (168, "dieresis"), (169, "copyright"),
+    (170, "ordfeminine"), (171, "guillemotleft"), (172, "logicalnot"), (173, "hyphen"), (174, "registered"), (175, "macron"),
+    (176, "degree"), (177, "plusminus"), (178, "twosuperior"), (179, "threesuperior"), (180, "acute"), (181, "mu"),
+    (182, "paragraph"), (183, "periodcentered"), (184, "cedilla"), (185, "onesuperior"), (186, "ordmasculine"), (187, "guillemotright"),
+    (188, "onequarter"), (189, "onehalf"), (190, "threequarters"), (191, "questiondown"), (192, "Agrave"), (193, "Aacute"),
+    (194, "Acircumflex"), (195, "Atilde"), (196, "Adieresis"), (197, "Aring"), (198, "AE"), (199, "Ccedilla"),
+    (200, "Egrave"), (201, "Eacute"), (202, "Ecircumflex"), (203, "Edieresis"), (204, "Igrave"), (205, "Iacute"),
+    (206, "Icircumflex"), (207, "Idieresis"), (208, "Eth"), (209, "Ntilde"), (210, "Ograve"), (211, "Oacute"),
+    (212, "Ocircumflex"), (213, "Otilde"), (214, "Odieresis"), (215, "multiply"), (216, "Oslash"), (217, "Ugrave"),
+    (218, "Uacute"), (219, "Ucircumflex"), (220, "Udieresis"), (221, "Yacute"), (222, "Thorn"), (223, "germandbls"),
+    (224, "agrave"), (225, "aacute"), (226, "acircumflex"), (227, "atilde"), (228, "adieresis"), (229, "aring"),
+    (230, "ae"), (231, "ccedilla"), (232, "egrave"), (233, "eacute"), (234, "ecircumflex"), (235, "edieresis"),
+    (236, "igrave"), (237, "iacute"), (238, "icircumflex"), (239, "idieresis"), (240, "eth"), (241, "ntilde"),
+    (242, "ograve"), (243, "oacute"), (244, "ocircumflex"), (245, "otilde"), (246, "odieresis"), (247, "divide"),
+    (248, "oslash"), (249, "ugrave"), (250, "uacute"), (251, "ucircumflex"), (252, "udieresis"), (253, "yacute"),
+    (254, "thorn"), (255, "ydieresis"),
+];
+
+/// MacRomanEncoding: code to glyph name (unlisted codes are undefined).
+#[rustfmt::skip]
+static MAC_ROMAN_ENCODING: [(u8, &str); 223] = [
+    (32, "space"), (33, "exclam"), (34, "quotedbl"), (35, "numbersign"), (36, "dollar"), (37, "percent"),
+    (38, "ampersand"), (39, "quotesingle"), (40, "parenleft"), (41, "parenright"), (42, "asterisk"), (43, "plus"),
+    (44, "comma"), (45, "hyphen"), (46, "period"), (47, "slash"), (48, "zero"), (49, "one"),
+    (50, "two"), (51, "three"), (52, "four"), (53, "five"), (54, "six"), (55, "seven"),
+    (56, "eight"), (57, "nine"), (58, "colon"), (59, "semicolon"), (60, "less"), (61, "equal"),
+    (62, "greater"), (63, "question"), (64, "at"), (65, "A"), (66, "B"), (67, "C"),
+    (68, "D"), (69, "E"), (70, "F"), (71, "G"), (72, "H"), (73, "I"),
+    (74, "J"), (75, "K"), (76, "L"), (77, "M"), (78, "N"), (79, "O"),
+    (80, "P"), (81, "Q"), (82, "R"), (83, "S"), (84, "T"), (85, "U"),
+    (86, "V"), (87, "W"), (88, "X"), (89, "Y"), (90, "Z"), (91, "bracketleft"),
+    (92, "backslash"), (93, "bracketright"), (94, "asciicircum"), (95, "underscore"), (96, "grave"), (97, "a"),
+    (98, "b"), (99, "c"), (100, "d"), (101, "e"), (102, "f"), (103, "g"),
+    (104, "h"), (105, "i"), (106, "j"), (107, "k"), (108, "l"), (109, "m"),
+    (110, "n"), (111, "o"), (112, "p"), (113, "q"), (114, "r"), (115, "s"),
+    (116, "t"), (117, "u"), (118, "v"), (119, "w"), (120, "x"), (121, "y"),
+    (122, "z"), (123, "braceleft"), (124, "bar"), (125, "braceright"), (126, "asciitilde"), (128, "Adieresis"),
+    (129, "Aring"), (130, "Ccedilla"), (131, "Eacute"), (132, "Ntilde"), (133, "Odieresis"), (134, "Udieresis"),
+    (135, "aacute"), (136, "agrave"), (137, "acircumflex"), (138, "adieresis"), (139, "atilde"), (140, "aring"),
+    (141, "ccedilla"), (142, "eacute"), (143, "egrave"), (144, "ecircumflex"), (145, "edieresis"), (146, "iacute"),
+    (147, "igrave"), (148, "icircumflex"), (149, "idieresis"), (150, "ntilde"), (151, "oacute"), (152, "ograve"),
+    (153, "ocircumflex"), (154, "odieresis"), (155, "otilde"), (156, "uacute"), (157, "ugrave"), (158, "ucircumflex"),
+    (159, "udieresis"), (160, "dagger"), (161, "degree"), (162, "cent"), (163, "sterling"), (164, "section"),
+    (165, "bullet"), (166, "paragraph"), (167, "germandbls"), (168, "registered"), (169, "copyright"), (170, "trademark"),
+    (171, "acute"), (172, "dieresis"), (173, "notequal"), (174, "AE"), (175, "Oslash"), (176, "infinity"),
+    (177, "plusminus"), (178, "lessequal"), (179, "greaterequal"), (180, "yen"), (181, "mu"), (182, "partialdiff"),
+    (183, "summation"), (184, "product"), (185, "pi"), (186, "integral"), (187, "ordfeminine"), (188, "ordmasculine"),
+    (189, "Omega"), (190, "ae"), (191, "oslash"), (192, "questiondown"), (193, "exclamdown"), (194, "logicalnot"),
+    (195, "radical"), (196, "florin"), (197, "approxequal"), (198, "Delta"), (199, "guillemotleft"), (200, "guillemotright"),
+    (201, "ellipsis"), (202, "space"), (203, "Agrave"), (204, "Atilde"), (205, "Otilde"), (206, "OE"),
+    (207, "oe"), (208, "endash"), (209, "emdash"), (210, "quotedblleft"), (211, "quotedblright"), (212, "quoteleft"),
+    (213, "quoteright"), (214, "divide"), (215, "lozenge"), (216, "ydieresis"), (217, "Ydieresis"), (218, "fraction"),
+    (219, "currency"), (220, "guilsinglleft"), (221, "guilsinglright"), (222, "fi"), (223, "fl"), (224, "daggerdbl"),
+    (225, "periodcentered"), (226, "quotesinglbase"), (227, "quotedblbase"), (228, "perthousand"), (229, "Acircumflex"), (230, "Ecircumflex"),
+    (231, "Aacute"), (232, "Edieresis"), (233, "Egrave"), (234, "Iacute"), (235, "Icircumflex"), (236, "Idieresis"),
+    (237, "Igrave"), (238, "Oacute"), (239, "Ocircumflex"), (240, "apple"), (241, "Ograve"), (242, "Uacute"),
+    (243, "Ucircumflex"), (244, "Ugrave"), (245, "dotlessi"), (246, "circumflex"), (247, "tilde"), (248, "macron"),
+    (249, "breve"), (250, "dotaccent"), (251, "ring"), (252, "cedilla"), (253, "hungarumlaut"), (254, "ogonek"),
+    (255, "caron"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_aliases() {
+        assert_eq!(
+            StandardFont::from_base_font("Helvetica"),
+            Some(StandardFont::Helvetica)
+        );
+        assert_eq!(
+            StandardFont::from_base_font("ABCDEF+Helvetica-BoldOblique"),
+            Some(StandardFont::HelveticaBoldOblique)
+        );
+        assert_eq!(
+            StandardFont::from_base_font("Arial,Bold"),
+            Some(StandardFont::HelveticaBold)
+        );
+        assert_eq!(
+            StandardFont::from_base_font("ArialMT"),
+            Some(StandardFont::Helvetica)
+        );
+        assert_eq!(
+            StandardFont::from_base_font("TimesNewRomanPS-BoldItalicMT"),
+            Some(StandardFont::TimesBoldItalic)
+        );
+        assert_eq!(
+            StandardFont::from_base_font("Times-Roman"),
+            Some(StandardFont::TimesRoman)
+        );
+        assert_eq!(
+            StandardFont::from_base_font("CourierNew,Italic"),
+            Some(StandardFont::CourierOblique)
+        );
+        assert_eq!(
+            StandardFont::from_base_font("Symbol"),
+            Some(StandardFont::Symbol)
+        );
+        assert_eq!(StandardFont::from_base_font("Arial-Narrow"), None);
+        assert_eq!(StandardFont::from_base_font("Calibri"), None);
+    }
+
+    #[test]
+    fn test_glyph_widths() {
+        assert_eq!(StandardFont::Helvetica.glyph_width("A"), Some(667));
+        assert_eq!(StandardFont::Helvetica.glyph_width("space"), Some(278));
+        assert_eq!(StandardFont::HelveticaBold.glyph_width("A"), Some(722));
+        assert_eq!(StandardFont::TimesRoman.glyph_width("a"), Some(444));
+        assert_eq!(StandardFont::CourierBold.glyph_width("W"), Some(600));
+        assert_eq!(StandardFont::Symbol.glyph_width("alpha"), Some(631));
+        assert_eq!(StandardFont::Helvetica.glyph_width("nosuchglyph"), None);
+    }
+
+    #[test]
+    fn test_code_widths_with_encoding() {
+        // Built-in StandardEncoding: 0x27 is quoteright
+        let std = StandardFont::Helvetica.code_widths(None, &[]);
+        assert_eq!(std.get(&39), Some(&222));
+        assert_eq!(std.get(&65), Some(&667));
+
+        // WinAnsiEncoding: 0x27 is quotesingle, 0xE9 is eacute
+        let win = StandardFont::Helvetica.code_widths(Some(b"WinAnsiEncoding"), &[]);
+        assert_eq!(win.get(&39), Some(&191));
+        assert_eq!(win.get(&0xE9), Some(&556));
+
+        // Differences override the base encoding
+        let diff =
+            StandardFont::Helvetica.code_widths(Some(b"WinAnsiEncoding"), &[(65, "W".to_string())]);
+        assert_eq!(diff.get(&65), Some(&944));
+    }
+}
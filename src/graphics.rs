@@ -0,0 +1,282 @@
+//! Vector graphics
+//!
+//! Ruling lines, cell borders, shaded boxes and underlines are drawn with path
+//! operators rather than text. Painted paths are captured per page, in page
+//! space, as straight lines, axis-aligned rectangles and (for curves and other
+//! polygons) bounding boxes, for layout, table and markdown heuristics.
+
+/// Tolerance, in points, for treating a segment as horizontal or vertical
+const AXIS_TOLERANCE: f32 = 0.5;
+
+/// What a graphic item is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicKind {
+    /// Straight segment from (x1, y1) to (x2, y2)
+    Line,
+    /// Axis-aligned rectangle spanning (x1, y1)–(x2, y2)
+    Rect,
+    /// Curved or irregular shape, by its bounding box (x1, y1)–(x2, y2)
+    Shape,
+}
+
+/// A painted path element in page space (PDF coordinates, origin bottom-left)
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphicItem {
+    pub kind: GraphicKind,
+    /// Start of a line, or bottom-left corner of a rectangle or shape
+    pub x1: f32,
+    pub y1: f32,
+    /// End of a line, or top-right corner of a rectangle or shape
+    pub x2: f32,
+    pub y2: f32,
+    /// Page number (1-indexed)
+    pub page: u32,
+    /// Whether the path is stroked
+    pub stroked: bool,
+    /// Whether the path is filled
+    pub filled: bool,
+    /// Stroke width in page space
+    pub line_width: f32,
+    /// Stroke color as RGB components in 0..=1
+    pub stroke_color: [f32; 3],
+    /// Fill color as RGB components in 0..=1
+    pub fill_color: [f32; 3],
+}
+
+impl GraphicItem {
+    /// Horizontal extent
+    pub fn width(&self) -> f32 {
+        (self.x2 - self.x1).abs()
+    }
+
+    /// Vertical extent
+    pub fn height(&self) -> f32 {
+        (self.y2 - self.y1).abs()
+    }
+
+    /// Whether this is a horizontal rule: a horizontal line, or a rectangle
+    /// at most `max_thickness` high
+    pub fn is_horizontal_rule(&self, max_thickness: f32) -> bool {
+        match self.kind {
+            GraphicKind::Line => self.height() <= AXIS_TOLERANCE,
+            GraphicKind::Rect => self.height() <= max_thickness && self.width() > self.height(),
+            GraphicKind::Shape => false,
+        }
+    }
+
+    /// Whether this is a vertical rule: a vertical line, or a rectangle at
+    /// most `max_thickness` wide
+    pub fn is_vertical_rule(&self, max_thickness: f32) -> bool {
+        match self.kind {
+            GraphicKind::Line => self.width() <= AXIS_TOLERANCE,
+            GraphicKind::Rect => self.width() <= max_thickness && self.height() > self.width(),
+            GraphicKind::Shape => false,
+        }
+    }
+}
+
+/// A path construction operator, with its points already in page space
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum PathElement {
+    MoveTo((f32, f32)),
+    LineTo((f32, f32)),
+    /// Bézier curve: two control points and the end point
+    CurveTo([(f32, f32); 3]),
+    /// `re`, as its corners in drawing order
+    Rect([(f32, f32); 4]),
+    Close,
+}
+
+impl PathElement {
+    /// Points defining the element; curves are bounded by their control points
+    pub(crate) fn points(&self) -> &[(f32, f32)] {
+        match self {
+            PathElement::MoveTo(p) | PathElement::LineTo(p) => std::slice::from_ref(p),
+            PathElement::CurveTo(points) => points,
+            PathElement::Rect(corners) => corners,
+            PathElement::Close => &[],
+        }
+    }
+}
+
+/// How a path is painted
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PathPaint {
+    pub page: u32,
+    pub stroked: bool,
+    pub filled: bool,
+    pub line_width: f32,
+    pub stroke_color: [f32; 3],
+    pub fill_color: [f32; 3],
+}
+
+/// Convert a painted path into graphic items, one subpath at a time
+pub(crate) fn path_graphics(path: &[PathElement], paint: &PathPaint) -> Vec<GraphicItem> {
+    let mut graphics = Vec::new();
+    let mut subpath: Vec<(f32, f32)> = Vec::new();
+    let mut curved = false;
+    let mut closed = false;
+
+    for element in path {
+        match *element {
+            PathElement::MoveTo(p) => {
+                finish_subpath(&subpath, curved, closed, paint, &mut graphics);
+                subpath = vec![p];
+                (curved, closed) = (false, false);
+            }
+            PathElement::LineTo(p) => subpath.push(p),
+            PathElement::CurveTo(points) => {
+                subpath.extend(points);
+                curved = true;
+            }
+            PathElement::Close => {
+                if let Some(&start) = subpath.first() {
+                    if subpath.last() != Some(&start) {
+                        subpath.push(start);
+                    }
+                    closed = true;
+                }
+            }
+            PathElement::Rect(corners) => {
+                finish_subpath(&subpath, curved, closed, paint, &mut graphics);
+                let mut outline = corners.to_vec();
+                outline.push(corners[0]);
+                finish_subpath(&outline, false, true, paint, &mut graphics);
+                subpath = Vec::new();
+                (curved, closed) = (false, false);
+            }
+        }
+    }
+    finish_subpath(&subpath, curved, closed, paint, &mut graphics);
+    graphics
+}
+
+fn finish_subpath(
+    points: &[(f32, f32)],
+    curved: bool,
+    closed: bool,
+    paint: &PathPaint,
+    graphics: &mut Vec<GraphicItem>,
+) {
+    if points.len() < 2 {
+        return;
+    }
+    let item = |kind, (x1, y1), (x2, y2)| GraphicItem {
+        kind,
+        x1,
+        y1,
+        x2,
+        y2,
+        page: paint.page,
+        stroked: paint.stroked,
+        filled: paint.filled,
+        line_width: paint.line_width,
+        stroke_color: paint.stroke_color,
+        fill_color: paint.fill_color,
+    };
+    let x_min = points.iter().map(|p| p.0).fold(f32::INFINITY, f32::min);
+    let y_min = points.iter().map(|p| p.1).fold(f32::INFINITY, f32::min);
+    let x_max = points.iter().map(|p| p.0).fold(f32::NEG_INFINITY, f32::max);
+    let y_max = points.iter().map(|p| p.1).fold(f32::NEG_INFINITY, f32::max);
+    let bounds = ((x_min, y_min), (x_max, y_max));
+
+    if curved {
+        graphics.push(item(GraphicKind::Shape, bounds.0, bounds.1));
+    } else if (closed || points.first() == points.last())
+        && (is_axis_aligned_rect(points) || points.len() <= 3)
+    {
+        // Closed back-and-forth strokes are degenerate rectangles too
+        graphics.push(item(GraphicKind::Rect, bounds.0, bounds.1));
+    } else if paint.stroked && !(closed && paint.filled) {
+        for segment in points.windows(2) {
+            if segment[0] != segment[1] {
+                graphics.push(item(GraphicKind::Line, segment[0], segment[1]));
+            }
+        }
+    } else if paint.filled && points.len() > 2 {
+        graphics.push(item(GraphicKind::Shape, bounds.0, bounds.1));
+    } else if paint.stroked {
+        for segment in points.windows(2) {
+            graphics.push(item(GraphicKind::Line, segment[0], segment[1]));
+        }
+    }
+}
+
+/// Whether an outline of four sides runs along the axes
+fn is_axis_aligned_rect(points: &[(f32, f32)]) -> bool {
+    let outline = match points {
+        [first, .., last] if first == last => &points[..points.len() - 1],
+        _ => points,
+    };
+    if outline.len() != 4 {
+        return false;
+    }
+    (0..4).all(|i| {
+        let (a, b) = (outline[i], outline[(i + 1) % 4]);
+        (a.0 - b.0).abs() <= AXIS_TOLERANCE || (a.1 - b.1).abs() <= AXIS_TOLERANCE
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paint(stroked: bool, filled: bool) -> PathPaint {
+        PathPaint {
+            page: 1,
+            stroked,
+            filled,
+            line_width: 1.0,
+            stroke_color: [0.0; 3],
+            fill_color: [0.0; 3],
+        }
+    }
+
+    #[test]
+    fn test_path_graphics() {
+        let path = [
+            // Table rule
+            PathElement::MoveTo((72.0, 500.0)),
+            PathElement::LineTo((540.0, 500.0)),
+            // Cell border drawn as a closed polyline
+            PathElement::MoveTo((72.0, 400.0)),
+            PathElement::LineTo((200.0, 400.0)),
+            PathElement::LineTo((200.0, 450.0)),
+            PathElement::LineTo((72.0, 450.0)),
+            PathElement::Close,
+            // Rounded corner
+            PathElement::MoveTo((300.0, 300.0)),
+            PathElement::CurveTo([(310.0, 300.0), (320.0, 310.0), (320.0, 320.0)]),
+        ];
+        let graphics = path_graphics(&path, &paint(true, false));
+        let kinds: Vec<GraphicKind> = graphics.iter().map(|g| g.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![GraphicKind::Line, GraphicKind::Rect, GraphicKind::Shape]
+        );
+        assert!(graphics[0].is_horizontal_rule(2.0));
+        assert_eq!(
+            (
+                graphics[1].x1,
+                graphics[1].y1,
+                graphics[1].x2,
+                graphics[1].y2
+            ),
+            (72.0, 400.0, 200.0, 450.0)
+        );
+        assert_eq!((graphics[2].width(), graphics[2].height()), (20.0, 20.0));
+
+        // A thin filled rectangle is a rule too
+        let bar = [PathElement::Rect([
+            (72.0, 300.0),
+            (540.0, 300.0),
+            (540.0, 300.5),
+            (72.0, 300.5),
+        ])];
+        let graphics = path_graphics(&bar, &paint(false, true));
+        assert_eq!(graphics.len(), 1);
+        assert_eq!(graphics[0].kind, GraphicKind::Rect);
+        assert!(graphics[0].is_horizontal_rule(1.0));
+        assert!(!graphics[0].is_vertical_rule(1.0));
+    }
+}
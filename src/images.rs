@@ -0,0 +1,1000 @@
+//! Image extraction
+//!
+//! Decodes image XObjects drawn on each page. JPEG (`/DCTDecode`) and
+//! JPEG 2000 (`/JPXDecode`) data is passed through unchanged; everything else
+//! is decoded to samples, converted to gray or RGB (with the soft mask as alpha)
+//! and encoded as PNG.
+
+use crate::ccitt::{self, CcittParams};
+use crate::extractor::{get_number, page_image_items, resolve_array, resolve_dict, resolve_object};
+use crate::PdfError;
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::Path;
+
+/// Upper bound on the decoded size of a single filter stage
+const MAX_DECODED_BYTES: usize = 512 * 1024 * 1024;
+
+/// Encoded file format of an extracted image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Jpeg2000,
+}
+
+impl ImageFormat {
+    /// Conventional file extension
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Jpeg => "jpg",
+            ImageFormat::Jpeg2000 => "jp2",
+        }
+    }
+
+    /// MIME type
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Jpeg2000 => "image/jp2",
+        }
+    }
+}
+
+/// Properties of an image XObject, attached to image items
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImageInfo {
+    /// XObject resource name
+    pub name: String,
+    /// Object id of the image XObject; resource names can repeat between a
+    /// page and its Form XObjects, object ids cannot
+    pub object_id: ObjectId,
+    /// Native width in pixels
+    pub pixel_width: u32,
+    /// Native height in pixels
+    pub pixel_height: u32,
+    /// Color space family (e.g. "DeviceRGB", "ICCBased", "Indexed"); `None`
+    /// for stencil masks
+    pub color_space: Option<String>,
+    /// Last filter of the stream, which determines the image encoding
+    /// (e.g. "DCTDecode", "FlateDecode"); `None` for uncompressed images
+    pub filter: Option<String>,
+}
+
+impl ImageInfo {
+    /// Describe the image XObject stream `object_id`
+    pub fn from_stream(doc: &Document, name: &str, object_id: ObjectId, stream: &Stream) -> Self {
+        let dict = &stream.dict;
+        let color_space = match dict
+            .get(b"ColorSpace")
+            .or_else(|_| dict.get(b"CS"))
+            .map(|o| resolve_object(doc, o))
+        {
+            Ok(Object::Name(family)) => Some(family.as_slice()),
+            Ok(Object::Array(array)) => array.first().and_then(|o| o.as_name().ok()),
+            _ => None,
+        }
+        .map(|family| String::from_utf8_lossy(family).to_string());
+        let filter = filter_chain(doc, dict)
+            .last()
+            .map(|(filter, _)| String::from_utf8_lossy(filter).to_string());
+
+        ImageInfo {
+            name: name.to_string(),
+            object_id,
+            pixel_width: dimension(dict, b"Width").unwrap_or(0),
+            pixel_height: dimension(dict, b"Height").unwrap_or(0),
+            color_space,
+            filter,
+        }
+    }
+
+    /// SHA-256 of the image's encoded stream data, read from `doc` on demand
+    /// (see [`content_hash`])
+    pub fn hash(&self, doc: &Document) -> Option<String> {
+        let stream = doc.get_object(self.object_id).ok()?.as_stream().ok()?;
+        Some(content_hash(&stream.content))
+    }
+}
+
+/// SHA-256 of encoded image data, as lowercase hex; identical images share the
+/// hash across pages and documents
+pub fn content_hash(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// An image XObject drawn on a page, encoded as a standalone file
+#[derive(Debug, Clone)]
+pub struct ExtractedImage {
+    /// Page number (1-based)
+    pub page: u32,
+    /// XObject resource name, as in the `[Image: Im1]` placeholder items
+    pub name: String,
+    /// Object id of the image XObject, as in [`ImageInfo::object_id`]
+    pub object_id: ObjectId,
+    /// Page-space bounds of its first placement: `[x_min, y_min, x_max, y_max]`
+    pub bbox: [f32; 4],
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    pub format: ImageFormat,
+    /// Encoded file contents
+    pub data: Vec<u8>,
+    /// [`content_hash`] of the XObject's stream data, as returned by
+    /// [`ImageInfo::hash`]
+    pub hash: String,
+}
+
+impl ExtractedImage {
+    /// Key of this image in [`crate::MarkdownOptions::image_sources`]
+    pub fn key(&self) -> (u32, ObjectId) {
+        (self.page, self.object_id)
+    }
+
+    /// File name for exporting, with the object number to tell apart images
+    /// sharing a resource name, e.g. `page1-Im1-12.png`
+    pub fn file_name(&self) -> String {
+        let name: String = self
+            .name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!(
+            "page{}-{}-{}.{}",
+            self.page,
+            name,
+            self.object_id.0,
+            self.format.extension()
+        )
+    }
+
+    /// The image as a `data:` URI
+    pub fn to_data_uri(&self) -> String {
+        format!(
+            "data:{};base64,{}",
+            self.format.mime_type(),
+            base64_encode(&self.data)
+        )
+    }
+}
+
+/// Extract the images of a PDF file
+pub fn extract_images<P: AsRef<Path>>(path: P) -> Result<Vec<ExtractedImage>, PdfError> {
+    let doc = Document::load(path)?;
+    Ok(document_images(&doc))
+}
+
+/// Extract the images of a PDF in memory
+pub fn extract_images_mem(buffer: &[u8]) -> Result<Vec<ExtractedImage>, PdfError> {
+    let doc = Document::load_mem(buffer)?;
+    Ok(document_images(&doc))
+}
+
+/// Images of every page, in page order
+pub fn document_images(doc: &Document) -> Vec<ExtractedImage> {
+    doc.get_pages()
+        .into_iter()
+        .flat_map(|(page_num, page_id)| page_images(doc, page_id, page_num))
+        .collect()
+}
+
+/// Images drawn by a page's content, including inside its Form XObjects and
+/// annotation appearances. An image drawn more than once is returned once, at
+/// its first placement. Images that cannot be decoded (e.g. JBIG2) are skipped.
+pub fn page_images(doc: &Document, page_id: ObjectId, page_num: u32) -> Vec<ExtractedImage> {
+    let mut seen_ids = HashSet::new();
+    page_image_items(doc, page_id, page_num)
+        .into_iter()
+        .filter_map(|item| {
+            let info = item.image?;
+            let id = info.object_id;
+            if !seen_ids.insert(id) {
+                return None;
+            }
+            let stream = doc.get_object(id).ok()?.as_stream().ok()?;
+            match decode_image(doc, stream) {
+                Ok((format, data)) => Some(ExtractedImage {
+                    page: page_num,
+                    name: info.name,
+                    object_id: id,
+                    bbox: [item.x, item.y, item.x + item.width, item.y + item.height],
+                    width: dimension(&stream.dict, b"Width")?,
+                    height: dimension(&stream.dict, b"Height")?,
+                    format,
+                    data,
+                    hash: content_hash(&stream.content),
+                }),
+                Err(e) => {
+                    log::debug!("skipping image {} on page {}: {}", info.name, page_num, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Write images to `dir` and return their paths keyed for
+/// [`crate::MarkdownOptions::image_sources`]
+pub fn export_images(
+    images: &[ExtractedImage],
+    dir: &Path,
+) -> std::io::Result<HashMap<(u32, ObjectId), String>> {
+    std::fs::create_dir_all(dir)?;
+    let mut sources = HashMap::new();
+    for image in images {
+        let path = dir.join(image.file_name());
+        std::fs::write(&path, &image.data)?;
+        sources.insert(image.key(), path.to_string_lossy().to_string());
+    }
+    Ok(sources)
+}
+
+/// `data:` URIs of images keyed for [`crate::MarkdownOptions::image_sources`]
+pub fn image_data_uris(images: &[ExtractedImage]) -> HashMap<(u32, ObjectId), String> {
+    images
+        .iter()
+        .map(|image| (image.key(), image.to_data_uri()))
+        .collect()
+}
+
+fn dimension(dict: &Dictionary, key: &[u8]) -> Option<u32> {
+    dict.get(key)
+        .and_then(Object::as_i64)
+        .ok()
+        .filter(|&v| v > 0 && v <= u32::MAX as i64)
+        .map(|v| v as u32)
+}
+
+/// Decode an image XObject into an encoded file: PNG for sample data,
+/// passthrough for JPEG and JPEG 2000
+pub fn decode_image(doc: &Document, stream: &Stream) -> Result<(ImageFormat, Vec<u8>), PdfError> {
+    match decode_samples(doc, stream)? {
+        Decoded::Encoded(format, data) => Ok((format, data)),
+        Decoded::Pixels(mut pixels) => {
+            if let Some(mask) = stream
+                .dict
+                .get(b"SMask")
+                .ok()
+                .and_then(|o| resolve_object(doc, o).as_stream().ok())
+            {
+                if let Ok(Decoded::Pixels(mask)) = decode_samples(doc, mask) {
+                    pixels.add_alpha(&mask);
+                }
+            }
+            Ok((ImageFormat::Png, pixels.to_png()?))
+        }
+    }
+}
+
+/// Result of decoding an image stream
+enum Decoded {
+    /// Data in a standalone file format
+    Encoded(ImageFormat, Vec<u8>),
+    Pixels(Pixels),
+}
+
+/// 8-bit pixels, gray or RGB with optional alpha
+struct Pixels {
+    width: usize,
+    height: usize,
+    /// Color channels: 1 (gray) or 3 (RGB)
+    colors: usize,
+    has_alpha: bool,
+    data: Vec<u8>,
+}
+
+impl Pixels {
+    fn channels(&self) -> usize {
+        self.colors + usize::from(self.has_alpha)
+    }
+
+    /// Add a soft mask as the alpha channel, scaling it to the image size
+    fn add_alpha(&mut self, mask: &Pixels) {
+        if self.has_alpha || mask.width == 0 || mask.height == 0 {
+            return;
+        }
+        let mut data = Vec::with_capacity(self.width * self.height * (self.colors + 1));
+        for y in 0..self.height {
+            let my = y * mask.height / self.height;
+            for x in 0..self.width {
+                let mx = x * mask.width / self.width;
+                let start = (y * self.width + x) * self.colors;
+                data.extend_from_slice(&self.data[start..start + self.colors]);
+                // Use the mask's first channel (it is gray unless oddly encoded)
+                data.push(mask.data[(my * mask.width + mx) * mask.channels()]);
+            }
+        }
+        self.data = data;
+        self.has_alpha = true;
+    }
+
+    fn to_png(&self) -> std::io::Result<Vec<u8>> {
+        let color_type = match (self.colors, self.has_alpha) {
+            (1, false) => 0,
+            (1, true) => 4,
+            (_, false) => 2,
+            (_, true) => 6,
+        };
+        encode_png(
+            self.width as u32,
+            self.height as u32,
+            color_type,
+            self.channels(),
+            &self.data,
+        )
+    }
+}
+
+/// Color spaces of images and of fill/stroke colors
+#[derive(Debug, Clone)]
+pub(crate) enum ColorSpace {
+    Gray,
+    Rgb,
+    Cmyk,
+    /// CIE L*a*b* with its white point
+    Lab([f32; 3]),
+    Indexed {
+        base: Box<ColorSpace>,
+        hival: usize,
+        lookup: Vec<u8>,
+    },
+    /// Single-colorant tint, shown as gray (full tint = black)
+    Separation,
+}
+
+impl ColorSpace {
+    pub(crate) fn from_object(
+        doc: &Document,
+        obj: &Object,
+        depth: usize,
+    ) -> Result<Self, PdfError> {
+        let unsupported = || PdfError::Parse("unsupported image color space".to_string());
+        if depth > 4 {
+            return Err(unsupported());
+        }
+        let obj = resolve_object(doc, obj);
+        let (family, params) = match obj {
+            Object::Name(name) => (name.as_slice(), &[][..]),
+            Object::Array(array) => {
+                let family = array
+                    .first()
+                    .and_then(|o| o.as_name().ok())
+                    .ok_or_else(unsupported)?;
+                (family, &array[1..])
+            }
+            _ => return Err(unsupported()),
+        };
+        match family {
+            b"DeviceGray" | b"CalGray" | b"G" => Ok(ColorSpace::Gray),
+            b"DeviceRGB" | b"CalRGB" | b"RGB" => Ok(ColorSpace::Rgb),
+            b"DeviceCMYK" | b"CMYK" => Ok(ColorSpace::Cmyk),
+            b"Lab" => {
+                let white = params
+                    .first()
+                    .and_then(|o| resolve_dict(doc, o))
+                    .and_then(|d| d.get(b"WhitePoint").ok())
+                    .and_then(|o| resolve_array(doc, o))
+                    .map(|a| {
+                        let v = |i: usize| a.get(i).and_then(get_number).unwrap_or(1.0);
+                        [v(0), v(1), v(2)]
+                    })
+                    .unwrap_or([0.9505, 1.0, 1.089]);
+                Ok(ColorSpace::Lab(white))
+            }
+            b"ICCBased" => {
+                let profile = params
+                    .first()
+                    .and_then(|o| resolve_object(doc, o).as_stream().ok())
+                    .ok_or_else(unsupported)?;
+                if let Ok(alternate) = profile.dict.get(b"Alternate") {
+                    if let Ok(cs) = ColorSpace::from_object(doc, alternate, depth + 1) {
+                        return Ok(cs);
+                    }
+                }
+                match profile.dict.get(b"N").and_then(Object::as_i64) {
+                    Ok(1) => Ok(ColorSpace::Gray),
+                    Ok(4) => Ok(ColorSpace::Cmyk),
+                    _ => Ok(ColorSpace::Rgb),
+                }
+            }
+            b"Indexed" | b"I" if params.len() >= 3 => {
+                let base = ColorSpace::from_object(doc, &params[0], depth + 1)?;
+                let hival = params[1].as_i64().map_err(|_| unsupported())?.clamp(0, 255) as usize;
+                let lookup = match resolve_object(doc, &params[2]) {
+                    Object::String(bytes, _) => bytes.clone(),
+                    Object::Stream(stream) => stream_data(stream)?,
+                    _ => return Err(unsupported()),
+                };
+                Ok(ColorSpace::Indexed {
+                    base: Box::new(base),
+                    hival,
+                    lookup,
+                })
+            }
+            b"Separation" => Ok(ColorSpace::Separation),
+            b"DeviceN"
+                if params
+                    .first()
+                    .and_then(|o| resolve_array(doc, o))
+                    .map(Vec::len)
+                    == Some(1) =>
+            {
+                Ok(ColorSpace::Separation)
+            }
+            _ => Err(unsupported()),
+        }
+    }
+
+    pub(crate) fn components(&self) -> usize {
+        match self {
+            ColorSpace::Gray | ColorSpace::Separation | ColorSpace::Indexed { .. } => 1,
+            ColorSpace::Rgb | ColorSpace::Lab(_) => 3,
+            ColorSpace::Cmyk => 4,
+        }
+    }
+
+    /// Output channels: 1 for gray results, 3 for RGB
+    fn output_colors(&self) -> usize {
+        match self {
+            ColorSpace::Gray | ColorSpace::Separation => 1,
+            ColorSpace::Indexed { base, .. } => base.output_colors(),
+            _ => 3,
+        }
+    }
+
+    /// Default /Decode range of component `i`
+    fn default_decode(&self, i: usize, bits: u32) -> (f32, f32) {
+        match self {
+            ColorSpace::Indexed { .. } => (0.0, ((1u32 << bits) - 1) as f32),
+            ColorSpace::Lab(_) if i == 0 => (0.0, 100.0),
+            ColorSpace::Lab(_) => (-100.0, 100.0),
+            _ => (0.0, 1.0),
+        }
+    }
+
+    /// Initial color set by selecting the color space (PDF 32000-1, 8.6.8): black,
+    /// or the first entry of an indexed palette
+    pub(crate) fn initial_values(&self) -> Vec<f32> {
+        match self {
+            ColorSpace::Cmyk => vec![0.0, 0.0, 0.0, 1.0],
+            ColorSpace::Separation => vec![1.0],
+            _ => vec![0.0; self.components()],
+        }
+    }
+
+    /// Convert color operand values to RGB components in 0..=1
+    pub(crate) fn to_rgb(&self, values: &[f32]) -> [f32; 3] {
+        let mut out = Vec::with_capacity(3);
+        self.convert(values, &mut out);
+        let channel = |i: usize| out.get(i).or(out.first()).copied().unwrap_or(0) as f32 / 255.0;
+        [channel(0), channel(1), channel(2)]
+    }
+
+    /// Convert decoded component values to 8-bit output channels
+    fn convert(&self, values: &[f32], out: &mut Vec<u8>) {
+        let byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+        match self {
+            ColorSpace::Gray => out.push(byte(values[0])),
+            ColorSpace::Separation => out.push(byte(1.0 - values[0])),
+            ColorSpace::Rgb => out.extend(values[..3].iter().map(|&v| byte(v))),
+            ColorSpace::Cmyk => {
+                let k = 1.0 - values[3].clamp(0.0, 1.0);
+                for &c in &values[..3] {
+                    out.push(byte((1.0 - c.clamp(0.0, 1.0)) * k));
+                }
+            }
+            ColorSpace::Lab(white) => out.extend(lab_to_srgb(values, white).map(byte)),
+            ColorSpace::Indexed {
+                base,
+                hival,
+                lookup,
+            } => {
+                let n = base.components();
+                let index = (values[0].round().max(0.0) as usize).min(*hival);
+                let entry: Vec<f32> = (0..n)
+                    .map(|i| lookup.get(index * n + i).copied().unwrap_or(0) as f32 / 255.0)
+                    .collect();
+                // Lab lookup entries are scaled to the component ranges
+                let entry = match base.as_ref() {
+                    ColorSpace::Lab(_) => vec![
+                        entry[0] * 100.0,
+                        entry[1] * 200.0 - 100.0,
+                        entry[2] * 200.0 - 100.0,
+                    ],
+                    _ => entry,
+                };
+                base.convert(&entry, out);
+            }
+        }
+    }
+}
+
+/// CIE L*a*b* to sRGB, components in 0..1
+fn lab_to_srgb(values: &[f32], white: &[f32; 3]) -> [f32; 3] {
+    let fy = (values[0] + 16.0) / 116.0;
+    let fx = fy + values[1] / 500.0;
+    let fz = fy - values[2] / 200.0;
+    let finv = |t: f32| {
+        if t > 6.0 / 29.0 {
+            t * t * t
+        } else {
+            3.0 * (6.0f32 / 29.0).powi(2) * (t - 4.0 / 29.0)
+        }
+    };
+    let (x, y, z) = (
+        white[0] * finv(fx),
+        white[1] * finv(fy),
+        white[2] * finv(fz),
+    );
+    let linear = [
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    ];
+    linear.map(|c| {
+        if c <= 0.003_130_8 {
+            12.92 * c
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        }
+    })
+}
+
+/// Decoded content of a non-image stream (color lookup tables)
+fn stream_data(stream: &Stream) -> Result<Vec<u8>, PdfError> {
+    if stream.dict.has(b"Filter") {
+        Ok(stream.decompressed_content_with_limit(MAX_DECODED_BYTES)?)
+    } else {
+        Ok(stream.content.clone())
+    }
+}
+
+/// Filters of a stream with their decode parameters, in decoding order
+fn filter_chain<'a>(
+    doc: &'a Document,
+    dict: &'a Dictionary,
+) -> Vec<(&'a [u8], Option<&'a Dictionary>)> {
+    let lookup = |long: &[u8], short: &[u8]| {
+        dict.get(long)
+            .or_else(|_| dict.get(short))
+            .ok()
+            .map(|o| resolve_object(doc, o))
+    };
+    let filters: Vec<&[u8]> = match lookup(b"Filter", b"F") {
+        Some(Object::Name(name)) => vec![name.as_slice()],
+        Some(Object::Array(array)) => array.iter().filter_map(|o| o.as_name().ok()).collect(),
+        _ => Vec::new(),
+    };
+    let params: Vec<Option<&Dictionary>> = match lookup(b"DecodeParms", b"DP") {
+        Some(Object::Dictionary(d)) => vec![Some(d)],
+        Some(Object::Array(array)) => array.iter().map(|o| resolve_dict(doc, o)).collect(),
+        _ => Vec::new(),
+    };
+    filters
+        .into_iter()
+        .enumerate()
+        .map(|(i, filter)| (filter, params.get(i).copied().flatten()))
+        .collect()
+}
+
+/// Apply one general-purpose filter (Flate with predictors, LZW, RunLength, ASCII)
+fn apply_filter(
+    data: Vec<u8>,
+    filter: &[u8],
+    params: Option<&Dictionary>,
+) -> Result<Vec<u8>, PdfError> {
+    let mut dict = Dictionary::new();
+    dict.set("Filter", Object::Name(filter.to_vec()));
+    if let Some(params) = params {
+        dict.set("DecodeParms", Object::Dictionary(params.clone()));
+    }
+    Ok(Stream::new(dict, data).decompressed_content_with_limit(MAX_DECODED_BYTES)?)
+}
+
+fn decode_samples(doc: &Document, stream: &Stream) -> Result<Decoded, PdfError> {
+    let dict = &stream.dict;
+    let width = dimension(dict, b"Width").ok_or(PdfError::InvalidStructure)? as usize;
+    let height = dimension(dict, b"Height").ok_or(PdfError::InvalidStructure)? as usize;
+
+    let mut data = stream.content.clone();
+    let mut ccitt_params = None;
+    for (filter, params) in filter_chain(doc, dict) {
+        match filter {
+            b"DCTDecode" | b"DCT" => return Ok(Decoded::Encoded(ImageFormat::Jpeg, data)),
+            b"JPXDecode" => return Ok(Decoded::Encoded(ImageFormat::Jpeg2000, data)),
+            b"CCITTFaxDecode" | b"CCF" => {
+                let get = |key: &[u8]| params.and_then(|p| p.get(key).ok());
+                let int = |key: &[u8]| get(key).and_then(|o| o.as_i64().ok());
+                let flag = |key: &[u8]| get(key).and_then(|o| o.as_bool().ok()).unwrap_or(false);
+                let params = CcittParams {
+                    k: int(b"K").unwrap_or(0),
+                    columns: int(b"Columns").map_or(1728, |c| c.max(1) as usize),
+                    rows: int(b"Rows").map_or(height, |r| r.max(0) as usize),
+                    encoded_byte_align: flag(b"EncodedByteAlign"),
+                    black_is_1: flag(b"BlackIs1"),
+                };
+                data = ccitt::decode(&data, &params, MAX_DECODED_BYTES).ok_or_else(|| {
+                    PdfError::Parse("CCITT image larger than the decode limit".to_string())
+                })?;
+                ccitt_params = Some(params);
+            }
+            b"JBIG2Decode" => {
+                return Err(PdfError::Parse(
+                    "JBIG2 images are not supported".to_string(),
+                ))
+            }
+            other => data = apply_filter(data, other, params)?,
+        }
+    }
+
+    let image_mask = dict
+        .get(b"ImageMask")
+        .or_else(|_| dict.get(b"IM"))
+        .and_then(Object::as_bool)
+        .unwrap_or(false);
+    let (color_space, bits) = if image_mask {
+        (ColorSpace::Gray, 1)
+    } else {
+        let cs = dict
+            .get(b"ColorSpace")
+            .or_else(|_| dict.get(b"CS"))
+            .map_err(|_| PdfError::Parse("image without color space".to_string()))?;
+        let bits = match ccitt_params {
+            Some(_) => 1,
+            None => dict
+                .get(b"BitsPerComponent")
+                .or_else(|_| dict.get(b"BPC"))
+                .and_then(Object::as_i64)
+                .unwrap_or(8) as u32,
+        };
+        (ColorSpace::from_object(doc, cs, 0)?, bits)
+    };
+    if ![1, 2, 4, 8, 16].contains(&bits) {
+        return Err(PdfError::Parse(format!(
+            "unsupported bits per component: {}",
+            bits
+        )));
+    }
+
+    let n = color_space.components();
+    let decode: Vec<(f32, f32)> = match dict
+        .get(b"Decode")
+        .or_else(|_| dict.get(b"D"))
+        .ok()
+        .and_then(|o| resolve_array(doc, o))
+    {
+        Some(array) if array.len() >= 2 * n => (0..n)
+            .map(|i| {
+                let (lo, hi) = color_space.default_decode(i, bits);
+                (
+                    array.get(2 * i).and_then(get_number).unwrap_or(lo),
+                    array.get(2 * i + 1).and_then(get_number).unwrap_or(hi),
+                )
+            })
+            .collect(),
+        _ => (0..n)
+            .map(|i| color_space.default_decode(i, bits))
+            .collect(),
+    };
+
+    // /Width and /Height come straight from the file: bound the samples and
+    // pixels they call for before allocating them
+    let colors = color_space.output_colors();
+    let within_limit = |size: Option<usize>| size.filter(|&s| s <= MAX_DECODED_BYTES);
+    let too_large = || PdfError::Parse(format!("image too large: {}x{}", width, height));
+    within_limit(
+        width
+            .checked_mul(n.max(colors))
+            .and_then(|s| s.checked_mul(height)),
+    )
+    .ok_or_else(too_large)?;
+
+    let max_value = ((1u32 << bits) - 1) as f32;
+    let row_bytes = (width * n * bits as usize).div_ceil(8);
+    data.resize(row_bytes * height, 0);
+
+    let mut pixels = Vec::with_capacity(width * height * colors);
+    let mut values = vec![0f32; n];
+    for row in data.chunks_exact(row_bytes) {
+        for x in 0..width {
+            for (i, value) in values.iter_mut().enumerate() {
+                let sample = read_sample(row, x * n + i, bits) as f32;
+                let (lo, hi) = decode[i];
+                *value = lo + sample * (hi - lo) / max_value;
+            }
+            color_space.convert(&values, &mut pixels);
+        }
+    }
+
+    Ok(Decoded::Pixels(Pixels {
+        width,
+        height,
+        colors,
+        has_alpha: false,
+        data: pixels,
+    }))
+}
+
+/// Read the `index`-th sample of a packed row
+fn read_sample(row: &[u8], index: usize, bits: u32) -> u32 {
+    match bits {
+        8 => row[index] as u32,
+        16 => u16::from_be_bytes([row[2 * index], row[2 * index + 1]]) as u32,
+        _ => {
+            let bit = index * bits as usize;
+            let byte = row[bit / 8] as u32;
+            let shift = 8 - bits - (bit % 8) as u32;
+            (byte >> shift) & ((1 << bits) - 1)
+        }
+    }
+}
+
+/// Encode 8-bit pixels as PNG
+fn encode_png(
+    width: u32,
+    height: u32,
+    color_type: u8,
+    channels: usize,
+    data: &[u8],
+) -> std::io::Result<Vec<u8>> {
+    fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], body: &[u8]) {
+        out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        out.extend_from_slice(kind);
+        out.extend_from_slice(body);
+        let mut crc = flate2::Crc::new();
+        crc.update(kind);
+        crc.update(body);
+        out.extend_from_slice(&crc.sum().to_be_bytes());
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[8, color_type, 0, 0, 0]);
+
+    // Each scanline is preceded by its filter type (0 = none)
+    let stride = width as usize * channels;
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    for row in data.chunks(stride.max(1)) {
+        encoder.write_all(&[0])?;
+        encoder.write_all(row)?;
+    }
+    let compressed = encoder.finish()?;
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut png, b"IHDR", &header);
+    chunk(&mut png, b"IDAT", &compressed);
+    chunk(&mut png, b"IEND", &[]);
+    Ok(png)
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::dictionary;
+
+    /// Decode the IDAT scanlines of a PNG produced by `encode_png`
+    fn png_pixels(png: &[u8]) -> (u32, u32, u8, Vec<u8>) {
+        use std::io::Read;
+        let width = u32::from_be_bytes(png[16..20].try_into().unwrap());
+        let height = u32::from_be_bytes(png[20..24].try_into().unwrap());
+        let color_type = png[25];
+        let idat_len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&png[37..41], b"IDAT");
+        let mut raw = Vec::new();
+        flate2::read::ZlibDecoder::new(&png[41..41 + idat_len])
+            .read_to_end(&mut raw)
+            .unwrap();
+        let stride = raw.len() / height as usize;
+        let pixels = raw
+            .chunks(stride)
+            .flat_map(|row| row[1..].to_vec())
+            .collect();
+        (width, height, color_type, pixels)
+    }
+
+    #[test]
+    fn test_image_info() {
+        let doc = Document::with_version("1.5");
+        let stream = Stream::new(
+            dictionary! {
+                "Subtype" => "Image",
+                "Width" => 640,
+                "Height" => 480,
+                "ColorSpace" => vec!["ICCBased".into(), Object::Reference((9, 0))],
+                "Filter" => vec!["ASCII85Decode".into(), "DCTDecode".into()],
+            },
+            b"abc".to_vec(),
+        );
+        let info = ImageInfo::from_stream(&doc, "Im1", (7, 0), &stream);
+        assert_eq!((info.pixel_width, info.pixel_height), (640, 480));
+        assert_eq!(info.color_space.as_deref(), Some("ICCBased"));
+        assert_eq!(info.filter.as_deref(), Some("DCTDecode"));
+        assert_eq!(
+            content_hash(&stream.content),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64_encode(b"Man"), "TWFu");
+        assert_eq!(base64_encode(b"Ma"), "TWE=");
+        assert_eq!(base64_encode(b"M"), "TQ==");
+    }
+
+    #[test]
+    fn test_decode_indexed_image_with_soft_mask() {
+        use flate2::write::ZlibEncoder;
+
+        let mut doc = Document::with_version("1.5");
+        let mask = doc.add_object(Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 1,
+                "Height" => 1,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+            },
+            vec![0x80],
+        ));
+        // 2x2, 1 bit per index: red, green / green, red
+        let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&[0b0100_0000, 0b1000_0000]).unwrap();
+        let image = Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => 2,
+                "Height" => 2,
+                "ColorSpace" => vec![
+                    "Indexed".into(),
+                    "DeviceRGB".into(),
+                    1.into(),
+                    Object::String(vec![255, 0, 0, 0, 255, 0], lopdf::StringFormat::Hexadecimal),
+                ],
+                "BitsPerComponent" => 1,
+                "Filter" => "FlateDecode",
+                "SMask" => mask,
+            },
+            encoder.finish().unwrap(),
+        );
+
+        let (format, png) = decode_image(&doc, &image).unwrap();
+        assert_eq!(format, ImageFormat::Png);
+        let (width, height, color_type, pixels) = png_pixels(&png);
+        assert_eq!((width, height, color_type), (2, 2, 6));
+        assert_eq!(
+            pixels,
+            vec![
+                255, 0, 0, 0x80, 0, 255, 0, 0x80, //
+                0, 255, 0, 0x80, 255, 0, 0, 0x80,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_jpeg_passthrough_and_cmyk() {
+        let doc = Document::with_version("1.5");
+        let jpeg = Stream::new(
+            dictionary! {
+                "Subtype" => "Image",
+                "Width" => 1,
+                "Height" => 1,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+                "Filter" => vec!["ASCIIHexDecode".into(), "DCTDecode".into()],
+            },
+            b"FFD8FFD9>".to_vec(),
+        );
+        assert_eq!(
+            decode_image(&doc, &jpeg).unwrap(),
+            (ImageFormat::Jpeg, vec![0xFF, 0xD8, 0xFF, 0xD9])
+        );
+
+        // CMYK converts to RGB: no ink is white, magenta + yellow is red
+        let cmyk = Stream::new(
+            dictionary! {
+                "Subtype" => "Image",
+                "Width" => 2,
+                "Height" => 1,
+                "ColorSpace" => "DeviceCMYK",
+                "BitsPerComponent" => 8,
+            },
+            vec![0, 0, 0, 0, 0, 255, 255, 0],
+        );
+        let (_, png) = decode_image(&doc, &cmyk).unwrap();
+        assert_eq!(png_pixels(&png).3, vec![255, 255, 255, 255, 0, 0]);
+    }
+
+    #[test]
+    fn test_oversized_image_is_rejected() {
+        let doc = Document::with_version("1.5");
+        let huge = Stream::new(
+            dictionary! {
+                "Subtype" => "Image",
+                "Width" => 60000,
+                "Height" => 60000,
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+            },
+            vec![0; 16],
+        );
+        assert!(decode_image(&doc, &huge).is_err());
+    }
+
+    #[test]
+    fn test_page_images_are_the_drawn_ones() {
+        use crate::extractor::tests::add_single_page;
+
+        let mut doc = Document::with_version("1.5");
+        let image = |doc: &mut Document| {
+            doc.add_object(Stream::new(
+                dictionary! {
+                    "Type" => "XObject",
+                    "Subtype" => "Image",
+                    "Width" => 1,
+                    "Height" => 1,
+                    "ColorSpace" => "DeviceGray",
+                    "BitsPerComponent" => 8,
+                },
+                vec![0x80],
+            ))
+        };
+        let drawn = image(&mut doc);
+        let unused = image(&mut doc);
+        // Resources list both images; the content draws one of them twice
+        let page_id = add_single_page(
+            &mut doc,
+            b"q 200 0 0 100 50 600 cm /Im1 Do Q q 10 0 0 10 0 0 cm /Im1 Do Q",
+            dictionary! {
+                "Resources" => dictionary! {
+                    "XObject" => dictionary! { "Im1" => drawn, "Im2" => unused },
+                },
+            },
+        );
+
+        let images = page_images(&doc, page_id, 1);
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].object_id, drawn);
+        assert_eq!(images[0].name, "Im1");
+        assert_eq!(images[0].bbox, [50.0, 600.0, 250.0, 700.0]);
+    }
+}
@@ -0,0 +1,579 @@
+//! Key-value pairs
+//!
+//! Invoices, statements and certificates carry much of their data as labelled
+//! fields rather than prose: "Invoice No: 10042", a label with its value some
+//! way to its right, or a small label set above its value. Lines are split
+//! into segments at wide horizontal gaps, and runs of lines whose segments all
+//! pair up this way form a key-value block. Table detection rejects these
+//! layouts (see [`crate::tables`]), so they would otherwise end up in
+//! paragraphs.
+
+use crate::extractor::{group_into_lines, ItemType, TextItem, TextLine};
+use crate::graphics::GraphicItem;
+use crate::markdown::{
+    document_tables, remove_unwanted_items, strip_running_text, MarkdownOptions,
+};
+use crate::PdfError;
+use std::path::Path;
+
+/// Horizontal gap splitting a line into segments, in multiples of the font size
+const SEGMENT_GAP: f32 = 1.5;
+
+/// Maximum vertical distance between lines of a block, in multiples of the
+/// font size
+const MAX_LINE_GAP: f32 = 2.5;
+
+/// Maximum words in a key
+const MAX_KEY_WORDS: usize = 5;
+
+/// Maximum words in a value
+const MAX_VALUE_WORDS: usize = 12;
+
+/// Minimum number of pairs in a block
+const MIN_BLOCK_PAIRS: usize = 2;
+
+/// How a key is set against its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyValueLayout {
+    /// `Key: value`, on one line
+    Colon,
+    /// Key with its value to the right, lined up with the values of the
+    /// lines around it
+    LeftOf,
+    /// Key on the line above its value, set apart by size, weight, case or a
+    /// trailing colon
+    Above,
+}
+
+/// A key with its value
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyValue {
+    /// Key, without a trailing colon
+    pub key: String,
+    pub value: String,
+    /// Page number (1-indexed)
+    pub page: u32,
+    /// Label of the page from `/PageLabels`, if the document labels its pages
+    pub page_label: Option<String>,
+    /// Bounds of key and value as `[x_min, y_min, x_max, y_max]`
+    pub bbox: [f32; 4],
+    pub layout: KeyValueLayout,
+}
+
+/// What markdown conversion does with key-value blocks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyValueMode {
+    /// Leave them in the text flow, as paragraphs
+    #[default]
+    Text,
+    /// Render them as definition lists (`Key` / `: value`)
+    DefinitionList,
+    /// Render them as one `**Key:** value` line per pair
+    BoldKeys,
+}
+
+/// Consecutive lines of key-value pairs
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct KeyValueBlock {
+    /// Indices of the lines the block is made of
+    pub lines: std::ops::Range<usize>,
+    pub pairs: Vec<KeyValue>,
+}
+
+/// Run of items on a line, set apart from the rest by a wide gap
+struct Segment<'a> {
+    items: &'a [TextItem],
+    text: String,
+}
+
+impl Segment<'_> {
+    fn x(&self) -> f32 {
+        self.items[0].x
+    }
+
+    fn x_end(&self) -> f32 {
+        let last = &self.items[self.items.len() - 1];
+        last.x + last.width
+    }
+
+    fn font_size(&self) -> f32 {
+        self.items[0].font_size
+    }
+
+    fn is_bold(&self) -> bool {
+        self.items.iter().all(|item| item.is_bold)
+    }
+
+    fn bbox(&self) -> [f32; 4] {
+        self.items.iter().fold(
+            [
+                f32::INFINITY,
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+                f32::NEG_INFINITY,
+            ],
+            |b, i| {
+                [
+                    b[0].min(i.x),
+                    b[1].min(i.y),
+                    b[2].max(i.x + i.width),
+                    b[3].max(i.y + i.height),
+                ]
+            },
+        )
+    }
+}
+
+/// Split a line into segments at gaps wider than [`SEGMENT_GAP`]
+fn segments(line: &TextLine) -> Vec<Segment<'_>> {
+    let mut bounds = Vec::new();
+    let mut start = 0;
+    for i in 1..line.items.len() {
+        let (prev, item) = (&line.items[i - 1], &line.items[i]);
+        if item.x - (prev.x + prev.width) > SEGMENT_GAP * prev.font_size.max(item.font_size) {
+            bounds.push(start..i);
+            start = i;
+        }
+    }
+    bounds.push(start..line.items.len());
+    bounds
+        .into_iter()
+        .filter(|range| !range.is_empty())
+        .map(|range| {
+            let items = &line.items[range];
+            let text = TextLine {
+                items: items.to_vec(),
+                y: line.y,
+                page: line.page,
+            }
+            .text();
+            Segment {
+                items,
+                text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+            }
+        })
+        .filter(|segment| !segment.text.is_empty())
+        .collect()
+}
+
+/// Whether `text` reads like a field label: a few words starting with a
+/// letter, not a sentence
+fn is_key(text: &str) -> bool {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let Some(last) = words.last() else {
+        return false;
+    };
+    words.len() <= MAX_KEY_WORDS
+        && text.chars().next().is_some_and(char::is_alphabetic)
+        && !text.contains(['?', '!', ';'])
+        // "No." and "Ref." end labels, full stops end sentences
+        && !(last.ends_with('.') && last.len() > 4)
+}
+
+fn is_value(text: &str) -> bool {
+    let words = text.split_whitespace().count();
+    (1..=MAX_VALUE_WORDS).contains(&words)
+}
+
+/// The key of a label ending in a colon
+fn colon_key(text: &str) -> Option<&str> {
+    let key = text.strip_suffix(':')?.trim_end();
+    is_key(key).then_some(key)
+}
+
+fn union(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [
+        a[0].min(b[0]),
+        a[1].min(b[1]),
+        a[2].max(b[2]),
+        a[3].max(b[3]),
+    ]
+}
+
+fn pair(
+    key: &str,
+    value: &str,
+    line: &TextLine,
+    bbox: [f32; 4],
+    layout: KeyValueLayout,
+) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: value.to_string(),
+        page: line.page,
+        page_label: line.items.first().and_then(|item| item.page_label.clone()),
+        bbox,
+        layout,
+    }
+}
+
+/// Pairs of a line made only of `Key: value` segments, or of `Key:` segments
+/// each followed by its value
+fn colon_pairs(line: &TextLine) -> Option<Vec<KeyValue>> {
+    let segments = segments(line);
+    let mut pairs = Vec::new();
+    let mut i = 0;
+    while i < segments.len() {
+        let segment = &segments[i];
+        if let Some(key) = colon_key(&segment.text) {
+            let value = segments
+                .get(i + 1)
+                .filter(|v| colon_key(&v.text).is_none())?;
+            if !is_value(&value.text) {
+                return None;
+            }
+            let bbox = union(segment.bbox(), value.bbox());
+            pairs.push(pair(key, &value.text, line, bbox, KeyValueLayout::Colon));
+            i += 2;
+            continue;
+        }
+        let (key, value) = segment.text.split_once(": ")?;
+        let value = value.trim();
+        if !is_key(key) || !is_value(value) {
+            return None;
+        }
+        pairs.push(pair(
+            key,
+            value,
+            line,
+            segment.bbox(),
+            KeyValueLayout::Colon,
+        ));
+        i += 1;
+    }
+    (!pairs.is_empty()).then_some(pairs)
+}
+
+/// Pairs of a line of keys over a line of values: as many keys as values,
+/// lined up, with the keys set apart from the values
+fn above_pairs(keys: &TextLine, values: &TextLine) -> Option<Vec<KeyValue>> {
+    let (key_segments, value_segments) = (segments(keys), segments(values));
+    if key_segments.len() != value_segments.len() {
+        return None;
+    }
+    let mut pairs = Vec::new();
+    for (key, value) in key_segments.iter().zip(&value_segments) {
+        let tolerance = key.font_size();
+        let aligned = (key.x() - value.x()).abs() <= tolerance
+            || (key.x_end() - value.x_end()).abs() <= tolerance;
+        let is_upper = |text: &str| {
+            text.chars().any(char::is_alphabetic) && !text.chars().any(char::is_lowercase)
+        };
+        let distinct = colon_key(&key.text).is_some()
+            || key.font_size() < value.font_size() - 0.5
+            || (key.is_bold() && !value.is_bold())
+            || (is_upper(&key.text) && !is_upper(&value.text));
+        let key_text = colon_key(&key.text).unwrap_or(&key.text);
+        if !aligned || !distinct || !is_key(key_text) || !is_value(&value.text) {
+            return None;
+        }
+        let bbox = union(key.bbox(), value.bbox());
+        pairs.push(pair(
+            key_text,
+            &value.text,
+            keys,
+            bbox,
+            KeyValueLayout::Above,
+        ));
+    }
+    Some(pairs)
+}
+
+/// Whether `below` directly follows `above` on the same page
+fn adjacent(above: &TextLine, below: &TextLine) -> bool {
+    let font_size = |line: &TextLine| line.items.first().map_or(0.0, |i| i.font_size);
+    above.page == below.page
+        && above.y - below.y <= MAX_LINE_GAP * font_size(above).max(font_size(below))
+}
+
+/// Pairs of the run of at least `min_lines` lines from `start` made of a key
+/// and a value, with the keys lined up and the values lined up (left or
+/// right), and the number of lines in the run
+fn left_of_pairs(
+    lines: &[TextLine],
+    start: usize,
+    min_lines: usize,
+) -> Option<(usize, Vec<KeyValue>)> {
+    let first = segments(&lines[start]);
+    let [first_key, first_value] = &first[..] else {
+        return None;
+    };
+    let tolerance = first_key.font_size();
+
+    let mut pairs = Vec::new();
+    for (i, line) in lines.iter().enumerate().skip(start) {
+        if i > start && !adjacent(&lines[i - 1], line) {
+            break;
+        }
+        let segments = segments(line);
+        let [key, value] = &segments[..] else {
+            break;
+        };
+        let aligned = (key.x() - first_key.x()).abs() <= tolerance
+            && ((value.x() - first_value.x()).abs() <= tolerance
+                || (value.x_end() - first_value.x_end()).abs() <= tolerance);
+        if !aligned || !is_key(&key.text) || !is_value(&value.text) {
+            break;
+        }
+        let bbox = union(key.bbox(), value.bbox());
+        pairs.push(pair(
+            &key.text,
+            &value.text,
+            line,
+            bbox,
+            KeyValueLayout::LeftOf,
+        ));
+    }
+    (pairs.len() >= min_lines).then_some((pairs.len(), pairs))
+}
+
+/// Find the key-value blocks among `lines` (in reading order, as from
+/// [`group_into_lines`])
+pub(crate) fn key_value_blocks(lines: &[TextLine]) -> Vec<KeyValueBlock> {
+    let mut blocks: Vec<KeyValueBlock> = Vec::new();
+    let mut current: Option<KeyValueBlock> = None;
+    let close = |current: &mut Option<KeyValueBlock>, blocks: &mut Vec<KeyValueBlock>| {
+        if let Some(block) = current.take() {
+            if block.pairs.len() >= MIN_BLOCK_PAIRS {
+                blocks.push(block);
+            }
+        }
+    };
+
+    let mut i = 0;
+    while i < lines.len() {
+        // Directly below the block so far
+        let joins = current
+            .as_ref()
+            .is_some_and(|block| adjacent(&lines[block.lines.end - 1], &lines[i]));
+        let found = colon_pairs(&lines[i])
+            .map(|pairs| (1, pairs))
+            .or_else(|| {
+                let values = lines.get(i + 1).filter(|v| adjacent(&lines[i], v))?;
+                above_pairs(&lines[i], values).map(|pairs| (2, pairs))
+            })
+            // A single key and value continues a block, a run starts one
+            .or_else(|| left_of_pairs(lines, i, if joins { 1 } else { MIN_BLOCK_PAIRS }));
+        let Some((count, pairs)) = found else {
+            close(&mut current, &mut blocks);
+            i += 1;
+            continue;
+        };
+
+        if !joins {
+            close(&mut current, &mut blocks);
+        }
+        let block = current.get_or_insert(KeyValueBlock {
+            lines: i..i,
+            pairs: Vec::new(),
+        });
+        block.lines.end = i + count;
+        block.pairs.extend(pairs);
+        i += count;
+    }
+    close(&mut current, &mut blocks);
+    blocks
+}
+
+/// Find the key-value pairs among `lines` (in reading order, as from
+/// [`group_into_lines`])
+pub fn detect_key_values(lines: &[TextLine]) -> Vec<KeyValue> {
+    key_value_blocks(lines)
+        .into_iter()
+        .flat_map(|block| block.pairs)
+        .collect()
+}
+
+/// Render key-value pairs as markdown; empty for [`KeyValueMode::Text`]
+pub fn key_values_to_markdown(pairs: &[KeyValue], mode: KeyValueMode) -> String {
+    let mut output = String::new();
+    match mode {
+        KeyValueMode::Text => {}
+        KeyValueMode::DefinitionList => {
+            for (i, kv) in pairs.iter().enumerate() {
+                if i > 0 {
+                    output.push('\n');
+                }
+                output.push_str(&format!("{}\n: {}\n", kv.key, kv.value));
+            }
+        }
+        KeyValueMode::BoldKeys => {
+            for (i, kv) in pairs.iter().enumerate() {
+                // Hard line breaks keep the pairs on lines of their own
+                let end = if i + 1 < pairs.len() { "  \n" } else { "\n" };
+                output.push_str(&format!("**{}:** {}{}", kv.key, kv.value, end));
+            }
+        }
+    }
+    output
+}
+
+/// Extract the key-value pairs of a PDF file
+pub fn extract_key_values<P: AsRef<Path>>(path: P) -> Result<Vec<KeyValue>, PdfError> {
+    let buffer = std::fs::read(path.as_ref())?;
+    extract_key_values_mem(&buffer)
+}
+
+/// Extract the key-value pairs of a PDF in memory
+pub fn extract_key_values_mem(buffer: &[u8]) -> Result<Vec<KeyValue>, PdfError> {
+    let (items, graphics) = crate::extractor::extract_text_and_graphics_mem(buffer)?;
+    Ok(key_values_from_items(
+        items,
+        &graphics,
+        &MarkdownOptions::default(),
+    ))
+}
+
+/// Find the key-value pairs among positioned text items, outside the tables
+/// markdown conversion with `options` would find, in reading order
+pub fn key_values_from_items(
+    items: Vec<TextItem>,
+    graphics: &[GraphicItem],
+    options: &MarkdownOptions,
+) -> Vec<KeyValue> {
+    let text_items: Vec<TextItem> = remove_unwanted_items(items, options)
+        .into_iter()
+        .filter(|item| item.item_type == ItemType::Text)
+        .collect();
+    let (_, table_items) = document_tables(&text_items, graphics, options);
+    let items: Vec<TextItem> = text_items
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| !table_items.contains(idx))
+        .map(|(_, item)| item)
+        .collect();
+    let (lines, _) = strip_running_text(group_into_lines(items), options);
+    detect_key_values(&lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(text: &str, x: f32, y: f32, font_size: f32) -> TextItem {
+        TextItem {
+            text: text.into(),
+            x,
+            y,
+            width: text.len() as f32 * font_size * 0.5,
+            height: font_size,
+            font: "F1".into(),
+            font_size,
+            page: 1,
+            item_type: ItemType::Text,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_colon_pairs() {
+        let lines = group_into_lines(vec![
+            item("Invoice No: 10042", 72.0, 700.0, 10.0),
+            item("Date:", 72.0, 686.0, 10.0),
+            item("March 3, 2024", 160.0, 686.0, 10.0),
+            item("Terms: Net 30", 300.0, 686.0, 10.0),
+            item(
+                "Note: payment is due within thirty days of receipt.",
+                72.0,
+                640.0,
+                10.0,
+            ),
+        ]);
+        let pairs = detect_key_values(&lines);
+        let found: Vec<(&str, &str)> = pairs
+            .iter()
+            .map(|kv| (kv.key.as_str(), kv.value.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Invoice No", "10042"),
+                ("Date", "March 3, 2024"),
+                ("Terms", "Net 30")
+            ]
+        );
+        assert_eq!(pairs[1].layout, KeyValueLayout::Colon);
+        assert_eq!(pairs[1].bbox, [72.0, 686.0, 225.0, 696.0]);
+    }
+
+    #[test]
+    fn test_left_of_and_above_pairs() {
+        let mut bold = item("CERTIFICATE NO", 72.0, 600.0, 8.0);
+        bold.is_bold = true;
+        let lines = group_into_lines(vec![
+            // Labels left of values lined up at 200
+            item("Policy holder", 72.0, 700.0, 10.0),
+            item("Jane Doe", 200.0, 700.0, 10.0),
+            item("Coverage", 72.0, 686.0, 10.0),
+            item("Comprehensive", 200.0, 686.0, 10.0),
+            // Small capitals above their values
+            bold,
+            item("ISSUED", 250.0, 600.0, 8.0),
+            item("C-2291", 72.0, 588.0, 10.0),
+            item("1 May 2024", 250.0, 588.0, 10.0),
+            // Body text is no label
+            item(
+                "This certificate remains valid until revoked.",
+                72.0,
+                540.0,
+                10.0,
+            ),
+        ]);
+        let blocks = key_value_blocks(&lines);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].lines, 0..2);
+        assert_eq!(blocks[0].pairs[1].value, "Comprehensive");
+        assert_eq!(blocks[0].pairs[1].layout, KeyValueLayout::LeftOf);
+        assert_eq!(blocks[1].lines, 2..4);
+        assert_eq!(
+            (
+                blocks[1].pairs[1].key.as_str(),
+                blocks[1].pairs[1].value.as_str()
+            ),
+            ("ISSUED", "1 May 2024")
+        );
+        assert_eq!(blocks[1].pairs[0].layout, KeyValueLayout::Above);
+
+        // Two lines in the same style are not labels over values
+        let lines = group_into_lines(vec![
+            item("Alpha", 72.0, 700.0, 10.0),
+            item("Beta", 250.0, 700.0, 10.0),
+            item("Gamma", 72.0, 686.0, 10.0),
+            item(
+                "Delta is a longer sentence of body text here",
+                100.0,
+                686.0,
+                10.0,
+            ),
+        ]);
+        assert!(key_value_blocks(&lines).is_empty());
+    }
+
+    #[test]
+    fn test_key_values_to_markdown() {
+        let line = TextLine {
+            items: vec![],
+            y: 700.0,
+            page: 1,
+        };
+        let pairs = vec![
+            pair(
+                "Invoice No",
+                "10042",
+                &line,
+                [0.0; 4],
+                KeyValueLayout::Colon,
+            ),
+            pair("Due", "30 days", &line, [0.0; 4], KeyValueLayout::Colon),
+        ];
+        assert_eq!(
+            key_values_to_markdown(&pairs, KeyValueMode::DefinitionList),
+            "Invoice No\n: 10042\n\nDue\n: 30 days\n"
+        );
+        assert_eq!(
+            key_values_to_markdown(&pairs, KeyValueMode::BoldKeys),
+            "**Invoice No:** 10042  \n**Due:** 30 days\n"
+        );
+        assert!(key_values_to_markdown(&pairs, KeyValueMode::Text).is_empty());
+    }
+}
@@ -5,16 +5,46 @@
 //! - Direct text extraction from text-based PDFs
 //! - Markdown conversion with structure detection
 
+pub mod attachments;
+pub mod ccitt;
 pub mod detector;
 pub mod extractor;
+pub mod font_metrics;
 pub mod glyph_names;
+pub mod graphics;
+pub mod hidden_text;
+pub mod images;
+pub mod key_values;
 pub mod markdown;
+pub mod optional_content;
+pub mod page_labels;
+pub mod running_text;
+pub mod table_export;
 pub mod tables;
 pub mod tounicode;
+pub mod watermarks;
 
+pub use attachments::{extract_attachments, Attachment};
 pub use detector::{detect_pdf_type, PdfType, PdfTypeResult};
-pub use extractor::{extract_text, extract_text_with_positions, TextItem};
-pub use markdown::{to_markdown, to_markdown_from_items, MarkdownOptions};
+pub use extractor::{
+    extract_annotations, extract_text, extract_text_and_graphics, extract_text_with_positions,
+    extract_text_with_positions_in_layers, Annotation, AnnotationKind, TextItem,
+};
+pub use graphics::{GraphicItem, GraphicKind};
+pub use hidden_text::{hidden_text_report, HiddenReason, HiddenText, HiddenTextReport};
+pub use images::{extract_images, ExtractedImage, ImageFormat, ImageInfo};
+pub use key_values::{
+    detect_key_values, extract_key_values, KeyValue, KeyValueLayout, KeyValueMode,
+};
+pub use markdown::{
+    to_markdown, to_markdown_from_items, to_markdown_from_items_with_graphics, MarkdownOptions,
+};
+pub use optional_content::{extract_layers, Layer, LayerSelection};
+pub use page_labels::{extract_page_labels, page_labels};
+pub use running_text::{detect_running_text, RunningText, RunningTextMode};
+pub use table_export::{extract_tables, tables_to_json, CellValue, ExtractedTable, TableCell};
+pub use tables::{stitch_tables, RowKind, TableDetectionConfig, TableDetectionMode, TableRegion};
+pub use watermarks::{detect_watermarks, Watermark, WatermarkSignal};
 
 use std::path::Path;
 
@@ -29,10 +59,73 @@ pub struct PdfProcessResult {
     pub markdown: Option<String>,
     /// Page count
     pub page_count: u32,
+    /// Page labels from `/PageLabels` (physical numbers where absent), one per
+    /// page; empty unless requested with [`ProcessOptions::page_labels`] or
+    /// page markers are on, or if the PDF was not extracted
+    pub page_labels: Vec<String>,
+    /// Watermarks found in the extracted text
+    pub watermarks: Vec<Watermark>,
+    /// Text present in the PDF that a reader cannot see
+    pub hidden_text: HiddenTextReport,
+    /// Tables found while converting to markdown; empty unless requested with
+    /// [`ProcessOptions::tables`]
+    pub tables: Vec<ExtractedTable>,
     /// Processing time in milliseconds
     pub processing_time_ms: u64,
 }
 
+/// Options for [`process_pdf_with_options`] and [`process_pdf_mem_with_options`]
+#[derive(Debug, Clone, Default)]
+pub struct ProcessOptions {
+    /// Markdown conversion options
+    pub markdown: MarkdownOptions,
+    /// Optional content layers to extract text from
+    pub layers: LayerSelection,
+    /// Also convert attached PDFs (embedded files, portfolio members) and
+    /// append their markdown as nested sections
+    pub include_attachments: bool,
+    /// Read the page labels into [`PdfProcessResult::page_labels`]; they are
+    /// also read, and used in the page markers, when page markers are on
+    pub page_labels: bool,
+    /// Also return the tables found for the markdown, with typed cells, in
+    /// [`PdfProcessResult::tables`]
+    pub tables: bool,
+}
+
+impl ProcessOptions {
+    /// Whether the page labels have to be read
+    fn wants_page_labels(&self) -> bool {
+        self.page_labels || self.markdown.page_markers
+    }
+
+    /// Markdown options with the document's page labels for the page markers,
+    /// unless the caller supplied their own
+    fn markdown_options(&self, page_labels: &[String]) -> MarkdownOptions {
+        let mut markdown = self.markdown.clone();
+        if markdown.page_markers && markdown.page_labels.is_none() && !page_labels.is_empty() {
+            markdown.page_labels = Some(page_labels.to_vec());
+        }
+        markdown
+    }
+
+    /// The tables markdown conversion with `markdown` finds, if requested
+    fn export_tables(
+        &self,
+        items: &[TextItem],
+        graphics: &[GraphicItem],
+        markdown: &MarkdownOptions,
+    ) -> Vec<ExtractedTable> {
+        if self.tables {
+            table_export::tables_from_items(items.to_vec(), graphics, markdown)
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Maximum nesting of attached PDFs converted by `include_attachments`
+const MAX_ATTACHMENT_DEPTH: usize = 4;
+
 /// Process a PDF file with smart detection and extraction
 ///
 /// This function will:
@@ -40,6 +133,14 @@ pub struct PdfProcessResult {
 /// 2. If text-based, extract text and convert to markdown
 /// 3. If scanned, return early indicating OCR is needed
 pub fn process_pdf<P: AsRef<Path>>(path: P) -> Result<PdfProcessResult, PdfError> {
+    process_pdf_with_options(path, &ProcessOptions::default())
+}
+
+/// Process a PDF file with smart detection and extraction, using custom options
+pub fn process_pdf_with_options<P: AsRef<Path>>(
+    path: P,
+    options: &ProcessOptions,
+) -> Result<PdfProcessResult, PdfError> {
     let start = std::time::Instant::now();
 
     // Step 1: Smart detection (fast, no full load)
@@ -48,37 +149,83 @@ pub fn process_pdf<P: AsRef<Path>>(path: P) -> Result<PdfProcessResult, PdfError
     let result = match detection.pdf_type {
         PdfType::TextBased => {
             // Step 2: Full extraction with position-aware reading order
-            let items = extract_text_with_positions(&path)?;
-            let markdown = to_markdown_from_items(items, MarkdownOptions::default());
+            let (items, graphics, page_labels, attachments) = extractor::extract_content(
+                &path,
+                &options.layers,
+                options.markdown.table_detection.needs_graphics(),
+                options.wants_page_labels(),
+                options.include_attachments,
+            )?;
+            let watermarks = detect_watermarks(&items);
+            let hidden_text = hidden_text_report(&items);
+            let markdown_options = options.markdown_options(&page_labels);
+            let tables = options.export_tables(&items, &graphics, &markdown_options);
+            let markdown = to_markdown_from_items_with_graphics(items, &graphics, markdown_options);
 
             PdfProcessResult {
                 pdf_type: PdfType::TextBased,
                 text: None, // We now produce markdown directly
-                markdown: Some(markdown),
+                markdown: with_attachments(Some(markdown), &attachments, options, 0),
                 page_count: detection.page_count,
+                page_labels,
+                watermarks,
+                hidden_text,
+                tables,
                 processing_time_ms: start.elapsed().as_millis() as u64,
             }
         }
         PdfType::Scanned | PdfType::ImageBased => {
-            // Return early - OCR needed
+            // Return early - OCR needed; attached PDFs may still have text
+            let attachments = if options.include_attachments {
+                extract_attachments(&path).unwrap_or_default()
+            } else {
+                Vec::new()
+            };
             PdfProcessResult {
                 pdf_type: detection.pdf_type,
                 text: None,
-                markdown: None,
+                markdown: with_attachments(None, &attachments, options, 0),
                 page_count: detection.page_count,
+                page_labels: Vec::new(),
+                watermarks: Vec::new(),
+                hidden_text: HiddenTextReport::default(),
+                tables: Vec::new(),
                 processing_time_ms: start.elapsed().as_millis() as u64,
             }
         }
         PdfType::Mixed => {
             // Try to extract what we can with position-aware reading order
-            let items = extract_text_with_positions(&path).ok();
-            let markdown = items.map(|i| to_markdown_from_items(i, MarkdownOptions::default()));
+            let content = extractor::extract_content(
+                &path,
+                &options.layers,
+                options.markdown.table_detection.needs_graphics(),
+                options.wants_page_labels(),
+                options.include_attachments,
+            )
+            .ok();
+            let items = content.as_ref().map(|(items, ..)| items.as_slice());
+            let watermarks = items.map(detect_watermarks).unwrap_or_default();
+            let hidden_text = items.map(hidden_text_report).unwrap_or_default();
+            let mut page_labels = Vec::new();
+            let mut attachments = Vec::new();
+            let mut tables = Vec::new();
+            let markdown = content.map(|(items, graphics, labels, attached)| {
+                let markdown_options = options.markdown_options(&labels);
+                page_labels = labels;
+                attachments = attached;
+                tables = options.export_tables(&items, &graphics, &markdown_options);
+                to_markdown_from_items_with_graphics(items, &graphics, markdown_options)
+            });
 
             PdfProcessResult {
                 pdf_type: PdfType::Mixed,
                 text: None,
-                markdown,
+                markdown: with_attachments(markdown, &attachments, options, 0),
                 page_count: detection.page_count,
+                page_labels,
+                watermarks,
+                hidden_text,
+                tables,
                 processing_time_ms: start.elapsed().as_millis() as u64,
             }
         }
@@ -89,6 +236,23 @@ pub fn process_pdf<P: AsRef<Path>>(path: P) -> Result<PdfProcessResult, PdfError
 
 /// Process PDF from memory buffer
 pub fn process_pdf_mem(buffer: &[u8]) -> Result<PdfProcessResult, PdfError> {
+    process_pdf_mem_with_options(buffer, &ProcessOptions::default())
+}
+
+/// Process PDF from memory buffer, using custom options
+pub fn process_pdf_mem_with_options(
+    buffer: &[u8],
+    options: &ProcessOptions,
+) -> Result<PdfProcessResult, PdfError> {
+    process_pdf_mem_at_depth(buffer, options, 0)
+}
+
+/// Process PDF from memory buffer; `depth` counts the attachments it is nested in
+fn process_pdf_mem_at_depth(
+    buffer: &[u8],
+    options: &ProcessOptions,
+    depth: usize,
+) -> Result<PdfProcessResult, PdfError> {
     let start = std::time::Instant::now();
 
     // Step 1: Smart detection (fast, no full load)
@@ -97,33 +261,81 @@ pub fn process_pdf_mem(buffer: &[u8]) -> Result<PdfProcessResult, PdfError> {
     let result = match detection.pdf_type {
         PdfType::TextBased => {
             // Step 2: Full extraction with position-aware reading order
-            let items = extractor::extract_text_with_positions_mem(buffer)?;
-            let markdown = to_markdown_from_items(items, MarkdownOptions::default());
+            let (items, graphics, page_labels, attachments) = extractor::extract_content_mem(
+                buffer,
+                &options.layers,
+                options.markdown.table_detection.needs_graphics(),
+                options.wants_page_labels(),
+                options.include_attachments,
+            )?;
+            let watermarks = detect_watermarks(&items);
+            let hidden_text = hidden_text_report(&items);
+            let markdown_options = options.markdown_options(&page_labels);
+            let tables = options.export_tables(&items, &graphics, &markdown_options);
+            let markdown = to_markdown_from_items_with_graphics(items, &graphics, markdown_options);
 
             PdfProcessResult {
                 pdf_type: PdfType::TextBased,
                 text: None,
-                markdown: Some(markdown),
+                markdown: with_attachments(Some(markdown), &attachments, options, depth),
+                page_count: detection.page_count,
+                page_labels,
+                watermarks,
+                hidden_text,
+                tables,
+                processing_time_ms: start.elapsed().as_millis() as u64,
+            }
+        }
+        PdfType::Scanned | PdfType::ImageBased => {
+            let attachments = if options.include_attachments {
+                attachments::extract_attachments_mem(buffer).unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+            PdfProcessResult {
+                pdf_type: detection.pdf_type,
+                text: None,
+                markdown: with_attachments(None, &attachments, options, depth),
                 page_count: detection.page_count,
+                page_labels: Vec::new(),
+                watermarks: Vec::new(),
+                hidden_text: HiddenTextReport::default(),
+                tables: Vec::new(),
                 processing_time_ms: start.elapsed().as_millis() as u64,
             }
         }
-        PdfType::Scanned | PdfType::ImageBased => PdfProcessResult {
-            pdf_type: detection.pdf_type,
-            text: None,
-            markdown: None,
-            page_count: detection.page_count,
-            processing_time_ms: start.elapsed().as_millis() as u64,
-        },
         PdfType::Mixed => {
-            let items = extractor::extract_text_with_positions_mem(buffer).ok();
-            let markdown = items.map(|i| to_markdown_from_items(i, MarkdownOptions::default()));
+            let content = extractor::extract_content_mem(
+                buffer,
+                &options.layers,
+                options.markdown.table_detection.needs_graphics(),
+                options.wants_page_labels(),
+                options.include_attachments,
+            )
+            .ok();
+            let items = content.as_ref().map(|(items, ..)| items.as_slice());
+            let watermarks = items.map(detect_watermarks).unwrap_or_default();
+            let hidden_text = items.map(hidden_text_report).unwrap_or_default();
+            let mut page_labels = Vec::new();
+            let mut attachments = Vec::new();
+            let mut tables = Vec::new();
+            let markdown = content.map(|(items, graphics, labels, attached)| {
+                let markdown_options = options.markdown_options(&labels);
+                page_labels = labels;
+                attachments = attached;
+                tables = options.export_tables(&items, &graphics, &markdown_options);
+                to_markdown_from_items_with_graphics(items, &graphics, markdown_options)
+            });
 
             PdfProcessResult {
                 pdf_type: PdfType::Mixed,
                 text: None,
-                markdown,
+                markdown: with_attachments(markdown, &attachments, options, depth),
                 page_count: detection.page_count,
+                page_labels,
+                watermarks,
+                hidden_text,
+                tables,
                 processing_time_ms: start.elapsed().as_millis() as u64,
             }
         }
@@ -132,6 +344,47 @@ pub fn process_pdf_mem(buffer: &[u8]) -> Result<PdfProcessResult, PdfError> {
     Ok(result)
 }
 
+/// Markdown with the sections for the attached PDFs appended; PDFs without
+/// text of their own get the sections alone, if there are any
+fn with_attachments(
+    markdown: Option<String>,
+    attachments: &[Attachment],
+    options: &ProcessOptions,
+    depth: usize,
+) -> Option<String> {
+    let sections = attachment_sections(attachments, options, depth);
+    match markdown {
+        Some(markdown) => Some(markdown + &sections),
+        None if !sections.is_empty() => Some(sections.trim_start().to_string()),
+        None => None,
+    }
+}
+
+/// Markdown sections for the attached PDFs, each under an "Attachment" heading
+fn attachment_sections(
+    attachments: &[Attachment],
+    options: &ProcessOptions,
+    depth: usize,
+) -> String {
+    let mut sections = String::new();
+    if depth >= MAX_ATTACHMENT_DEPTH {
+        return sections;
+    }
+    for attachment in attachments.iter().filter(|a| a.is_pdf()) {
+        let Ok(nested) = process_pdf_mem_at_depth(&attachment.data, options, depth + 1) else {
+            continue;
+        };
+        if let Some(markdown) = nested.markdown.filter(|m| !m.trim().is_empty()) {
+            sections.push_str(&format!(
+                "\n## Attachment: {}\n\n{}\n",
+                attachment.name,
+                markdown.trim()
+            ));
+        }
+    }
+    sections
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum PdfError {
     #[error("IO error: {0}")]
@@ -6,7 +6,17 @@
 //! - Code blocks (monospace fonts, indentation)
 //! - Paragraphs
 
-use crate::extractor::{group_into_lines, TextItem, TextLine};
+use crate::extractor::{
+    escape_link_text, group_into_lines, Comment, InlineFormat, TextItem, TextLine,
+};
+use crate::graphics::GraphicItem;
+use crate::hidden_text::remove_hidden_text;
+use crate::key_values::{key_value_blocks, key_values_to_markdown, KeyValueMode};
+use crate::running_text::{
+    detect_running_text, remove_running_text, Placement, RunningText, RunningTextMode,
+};
+use crate::tables::{detect_tables_with_config, stitch_tables, Table, TableDetectionConfig};
+use crate::watermarks::remove_watermarks;
 use std::collections::{HashMap, HashSet};
 
 use regex::Regex;
@@ -16,6 +26,8 @@ use regex::Regex;
 pub struct MarkdownOptions {
     /// Detect headers by font size
     pub detect_headers: bool,
+    /// Detect body-sized headers set apart by a color of their own
+    pub detect_color_headers: bool,
     /// Detect list items
     pub detect_lists: bool,
     /// Detect code blocks
@@ -24,6 +36,12 @@ pub struct MarkdownOptions {
     pub base_font_size: Option<f32>,
     /// Remove standalone page numbers
     pub remove_page_numbers: bool,
+    /// Handling of headers and footers repeated across pages
+    pub running_text: RunningTextMode,
+    /// Leave watermarks (see [`crate::watermarks`]) out of the text flow
+    pub remove_watermarks: bool,
+    /// Leave out text a reader cannot see (see [`crate::hidden_text`])
+    pub remove_hidden_text: bool,
     /// Convert URLs to markdown links
     pub format_urls: bool,
     /// Fix hyphenation (broken words across lines)
@@ -34,24 +52,67 @@ pub struct MarkdownOptions {
     pub detect_italic: bool,
     /// Include image placeholders in output
     pub include_images: bool,
-    /// Include extracted hyperlinks
+    /// Include extracted hyperlinks, as `[anchor](url)` on the text they cover
     pub include_links: bool,
+    /// Render links to places inside the document as `[text](#anchor)`, with
+    /// an `<a id>` anchor at each linked destination
+    pub internal_links: bool,
+    /// Render review annotations: highlights as `==text==`, strikeouts as
+    /// `~~text~~` and comments as footnotes
+    pub include_annotations: bool,
+    /// Emit a `<!-- page: N -->` marker at the start of each page
+    pub page_markers: bool,
+    /// Page labels (see [`crate::page_labels`]) to use in page markers instead
+    /// of physical page numbers, indexed by 0-based page
+    pub page_labels: Option<Vec<String>>,
+    /// Image files or `data:` URIs keyed by (page, XObject object id), as
+    /// returned by [`crate::images::export_images`] or
+    /// [`crate::images::image_data_uris`]; images listed here are emitted as
+    /// `![alt](source)` references
+    pub image_sources: HashMap<(u32, lopdf::ObjectId), String>,
+    /// Use the nearest caption line ("Figure 3: ...") as image alt text
+    pub image_alt_from_captions: bool,
+    /// Render tables with merged cells as HTML (`colspan`/`rowspan`) instead
+    /// of flattening them into a markdown table
+    pub html_tables: bool,
+    /// Render line breaks inside table cells as `<br>` instead of a space
+    pub table_line_breaks: bool,
+    /// Table detection tuning, including turning it off and caller-provided
+    /// table regions
+    pub table_detection: TableDetectionConfig,
+    /// Rendering of key-value blocks (see [`crate::key_values`]): left in
+    /// paragraphs, or as definition lists or bold-key lines
+    pub key_values: KeyValueMode,
 }
 
 impl Default for MarkdownOptions {
     fn default() -> Self {
         Self {
             detect_headers: true,
+            detect_color_headers: false,
             detect_lists: true,
             detect_code: true,
             base_font_size: None,
             remove_page_numbers: true,
+            running_text: RunningTextMode::default(),
+            remove_watermarks: false,
+            remove_hidden_text: false,
             format_urls: true,
             fix_hyphenation: true,
             detect_bold: true,
             detect_italic: true,
             include_images: true,
             include_links: true,
+            internal_links: false,
+            include_annotations: false,
+            page_markers: false,
+            page_labels: None,
+            image_sources: HashMap::new(),
+            image_alt_from_captions: false,
+            html_tables: false,
+            table_line_breaks: false,
+            table_detection: TableDetectionConfig::default(),
+            key_values: KeyValueMode::default(),
         }
     }
 }
@@ -114,17 +175,28 @@ pub fn to_markdown(text: &str, options: MarkdownOptions) -> String {
 
 /// Convert positioned text items to markdown with structure detection
 pub fn to_markdown_from_items(items: Vec<TextItem>, options: MarkdownOptions) -> String {
+    to_markdown_from_items_with_graphics(items, &[], options)
+}
+
+/// Convert positioned text items to markdown with structure detection, using
+/// the page's ruling lines and boxes to find ruled tables
+pub fn to_markdown_from_items_with_graphics(
+    items: Vec<TextItem>,
+    graphics: &[GraphicItem],
+    options: MarkdownOptions,
+) -> String {
     use crate::extractor::ItemType;
-    use crate::tables::{detect_tables, table_to_markdown};
-    use std::collections::HashSet;
+    use crate::tables::{table_to_html, table_to_markdown_with_line_breaks};
 
     if items.is_empty() {
         return String::new();
     }
+    let items = remove_unwanted_items(items, &options);
 
     // Separate images and links from text items
     let mut images: Vec<TextItem> = Vec::new();
     let mut links: Vec<TextItem> = Vec::new();
+    let mut anchors: Vec<TextItem> = Vec::new();
     let mut text_items: Vec<TextItem> = Vec::new();
 
     for item in items {
@@ -139,20 +211,28 @@ pub fn to_markdown_from_items(items: Vec<TextItem>, options: MarkdownOptions) ->
                     links.push(item);
                 }
             }
+            ItemType::Anchor(_) => {
+                if options.internal_links {
+                    anchors.push(item);
+                }
+            }
             ItemType::Text => {
                 text_items.push(item);
             }
         }
     }
 
-    // Calculate base font size for table detection
-    let font_stats = calculate_font_stats_from_items(&text_items);
-    let base_size = options
-        .base_font_size
-        .unwrap_or(font_stats.most_common_size);
+    // Collect review comments before items are consumed, for the footnote section
+    let mut comments: Vec<Comment> = if options.include_annotations {
+        text_items
+            .iter()
+            .flat_map(|item| item.comments.iter().cloned())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    comments.sort_by_key(|c| c.id);
 
-    // Detect tables on each page
-    let mut table_items: HashSet<usize> = HashSet::new();
     let mut page_tables: std::collections::HashMap<u32, Vec<(f32, String)>> =
         std::collections::HashMap::new();
 
@@ -160,6 +240,91 @@ pub fn to_markdown_from_items(items: Vec<TextItem>, options: MarkdownOptions) ->
     let mut page_images: std::collections::HashMap<u32, Vec<(f32, String)>> =
         std::collections::HashMap::new();
 
+    // Links with no anchor text underneath are placed on their own line, like images
+    for link in &links {
+        if let ItemType::Link(url) = &link.item_type {
+            if !url.starts_with('#') {
+                page_images
+                    .entry(link.page)
+                    .or_default()
+                    .push((link.y + link.height, format!("[{}]({})\n", url, url)));
+            }
+        }
+    }
+
+    // Detect tables on each page, stitched across page breaks
+    let (tables, table_items) = document_tables(&text_items, graphics, &options);
+    for table in &tables {
+        // Get Y position for table insertion (use highest Y on its first page)
+        let page = table.row_pages.first().copied().unwrap_or(1);
+        let table_y = table.rows.first().copied().unwrap_or(0.0);
+        let table_md = if options.html_tables && !table.spans.is_empty() {
+            table_to_html(table)
+        } else {
+            let line_break = if options.table_line_breaks {
+                "<br>"
+            } else {
+                " "
+            };
+            table_to_markdown_with_line_breaks(table, line_break)
+        };
+
+        page_tables
+            .entry(page)
+            .or_default()
+            .push((table_y, table_md));
+    }
+
+    // Filter out table items and process the rest
+    let non_table_items: Vec<TextItem> = text_items
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| !table_items.contains(idx))
+        .map(|(_, item)| item)
+        .collect();
+
+    let lines = group_into_lines(non_table_items);
+    let (lines, running) = strip_running_text(lines, &options);
+
+    // Key-value blocks are placed like tables, in place of their lines
+    let lines = if options.key_values == KeyValueMode::Text {
+        lines
+    } else {
+        let blocks = key_value_blocks(&lines);
+        let mut block_lines: HashSet<usize> = HashSet::new();
+        for block in &blocks {
+            let first = &lines[block.lines.start];
+            page_tables.entry(first.page).or_default().push((
+                first.y,
+                key_values_to_markdown(&block.pairs, options.key_values),
+            ));
+            block_lines.extend(block.lines.clone());
+        }
+        lines
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, _)| !block_lines.contains(idx))
+            .map(|(_, line)| line)
+            .collect()
+    };
+
+    // Anchors go on their own line too, for the destinations the text links to
+    let targets: HashSet<&str> = lines
+        .iter()
+        .flat_map(|line| &line.items)
+        .filter_map(|item| item.link.as_deref()?.strip_prefix('#'))
+        .collect();
+    for anchor in &anchors {
+        if let ItemType::Anchor(id) = &anchor.item_type {
+            if targets.contains(id.as_str()) {
+                page_images
+                    .entry(anchor.page)
+                    .or_default()
+                    .push((anchor.y, format!("<a id=\"{}\"></a>\n", id)));
+            }
+        }
+    }
+
     for img in &images {
         // Extract image name from "[Image: Im0]" format
         let img_name = img
@@ -167,75 +332,129 @@ pub fn to_markdown_from_items(items: Vec<TextItem>, options: MarkdownOptions) ->
             .strip_prefix("[Image: ")
             .and_then(|s| s.strip_suffix(']'))
             .unwrap_or(&img.text);
-        let img_md = format!("![Image: {}](image)\n", img_name);
+        let source = img
+            .image
+            .as_ref()
+            .and_then(|info| options.image_sources.get(&(img.page, info.object_id)))
+            .map(String::as_str)
+            .unwrap_or("image");
+        let alt = options
+            .image_alt_from_captions
+            .then(|| image_caption(img, &lines))
+            .flatten()
+            .unwrap_or_else(|| format!("Image: {}", img_name));
+        let img_md = format!("![{}]({})\n", alt, source);
         page_images
             .entry(img.page)
             .or_default()
             .push((img.y, img_md));
     }
 
-    // Pre-group items by page with their global indices (O(n) instead of O(pages*n))
-    let mut page_groups: HashMap<u32, Vec<(usize, &TextItem)>> = HashMap::new();
-    for (global_idx, item) in text_items.iter().enumerate() {
-        page_groups
-            .entry(item.page)
-            .or_default()
-            .push((global_idx, item));
-    }
-
-    let mut pages: Vec<u32> = page_groups.keys().copied().collect();
-    pages.sort();
-
-    for page in pages {
-        let group = page_groups.get(&page).unwrap();
-        let page_items: Vec<TextItem> = group.iter().map(|(_, item)| (*item).clone()).collect();
+    // Convert to markdown, inserting tables and images at appropriate positions
+    let markdown =
+        to_markdown_from_lines_with_tables_and_images(lines, options, page_tables, page_images);
+    let markdown = prepend_running_text(markdown, &running);
+    append_comment_footnotes(markdown, &comments)
+}
 
-        let tables = detect_tables(&page_items, base_size);
+/// Remove running headers and footers according to `options.running_text`,
+/// returning the lines left and the running text to list in the output
+pub(crate) fn strip_running_text(
+    lines: Vec<TextLine>,
+    options: &MarkdownOptions,
+) -> (Vec<TextLine>, Vec<RunningText>) {
+    if options.running_text == RunningTextMode::Keep {
+        return (lines, Vec::new());
+    }
+    let running = detect_running_text(&lines);
+    let lines = remove_running_text(lines, &running);
+    if options.running_text == RunningTextMode::Metadata {
+        (lines, running)
+    } else {
+        (lines, Vec::new())
+    }
+}
 
-        for table in tables {
-            // Mark items as belonging to a table using pre-computed global indices
-            for &idx in &table.item_indices {
-                if let Some(&(global_idx, _)) = group.get(idx) {
-                    table_items.insert(global_idx);
-                }
-            }
+/// List running headers and footers once, above the document body
+fn prepend_running_text(markdown: String, running: &[RunningText]) -> String {
+    if running.is_empty() {
+        return markdown;
+    }
+    let mut output = String::new();
+    for text in running {
+        let kind = match text.placement {
+            Placement::Header => "header",
+            Placement::Footer => "footer",
+        };
+        output.push_str(&format!(
+            "<!-- running {}: {} -->\n",
+            kind,
+            text.text.replace("--", "- -")
+        ));
+    }
+    output.push('\n');
+    output.push_str(&markdown);
+    output
+}
 
-            // Get Y position for table insertion (use highest Y in table)
-            let table_y = table.rows.first().copied().unwrap_or(0.0);
-            let table_md = table_to_markdown(&table);
+/// Inline formatting for rendering lines with the given options
+fn inline_format(options: &MarkdownOptions) -> InlineFormat {
+    InlineFormat {
+        bold: options.detect_bold,
+        italic: options.detect_italic,
+        annotations: options.include_annotations,
+        links: options.include_links,
+        internal_links: options.internal_links,
+    }
+}
 
-            page_tables
-                .entry(page)
-                .or_default()
-                .push((table_y, table_md));
+/// Append footnote definitions for the review comments referenced in the markdown
+fn append_comment_footnotes(mut markdown: String, comments: &[Comment]) -> String {
+    let mut seen: HashSet<usize> = HashSet::new();
+    let mut definitions = String::new();
+    for comment in comments {
+        if !seen.insert(comment.id) || !markdown.contains(&format!("[^{}]", comment.id)) {
+            continue;
+        }
+        match &comment.author {
+            Some(author) => definitions.push_str(&format!(
+                "[^{}]: **{}:** {}\n",
+                comment.id, author, comment.text
+            )),
+            None => definitions.push_str(&format!("[^{}]: {}\n", comment.id, comment.text)),
         }
     }
+    if !definitions.is_empty() {
+        while !markdown.ends_with("\n\n") {
+            markdown.push('\n');
+        }
+        markdown.push_str(&definitions);
+    }
+    markdown
+}
 
-    // Filter out table items and process the rest
-    let non_table_items: Vec<TextItem> = text_items
-        .into_iter()
-        .enumerate()
-        .filter(|(idx, _)| !table_items.contains(idx))
-        .map(|(_, item)| item)
-        .collect();
-
-    // Find pages that are table-only (no remaining non-table text)
-    let table_only_pages: HashSet<u32> = {
-        let pages_with_text: HashSet<u32> = non_table_items.iter().map(|i| i.page).collect();
-        page_tables
-            .keys()
-            .filter(|p| !pages_with_text.contains(p))
-            .copied()
-            .collect()
+/// Drop hidden text and watermarks as `options` ask
+pub(crate) fn remove_unwanted_items(
+    items: Vec<TextItem>,
+    options: &MarkdownOptions,
+) -> Vec<TextItem> {
+    let items = if options.remove_hidden_text {
+        remove_hidden_text(items)
+    } else {
+        items
     };
+    if options.remove_watermarks {
+        remove_watermarks(items)
+    } else {
+        items
+    }
+}
 
-    // Merge continuation tables across page breaks, but only for table-only pages
-    merge_continuation_tables(&mut page_tables, &table_only_pages);
-
-    let lines = group_into_lines(non_table_items);
-
-    // Convert to markdown, inserting tables and images at appropriate positions
-    to_markdown_from_lines_with_tables_and_images(lines, options, page_tables, page_images)
+/// Body font size that table detection compares text items against
+pub(crate) fn table_base_font_size(text_items: &[TextItem], options: &MarkdownOptions) -> f32 {
+    options
+        .base_font_size
+        .unwrap_or_else(|| calculate_font_stats_from_items(text_items).most_common_size)
 }
 
 /// Calculate font stats directly from items (before grouping into lines)
@@ -258,126 +477,105 @@ fn calculate_font_stats_from_items(items: &[TextItem]) -> FontStats {
     FontStats { most_common_size }
 }
 
-/// Merge continuation tables that span across page breaks.
-///
-/// When consecutive pages each have exactly one table with the same number of columns
-/// AND both pages are table-only (no non-table text), treat them as a single table.
-/// We strip their header+separator rows and append their data rows to the first page's
-/// table, then remove them from later pages.
-fn merge_continuation_tables(
-    page_tables: &mut std::collections::HashMap<u32, Vec<(f32, String)>>,
-    table_only_pages: &HashSet<u32>,
-) {
-    let mut sorted_pages: Vec<u32> = page_tables.keys().copied().collect();
-    sorted_pages.sort();
+/// Tables of every page in document order, stitched across page breaks where
+/// nothing but page numbers and running headers or footers separates the
+/// parts, with the indices of the text items they hold
+pub(crate) fn document_tables(
+    text_items: &[TextItem],
+    graphics: &[GraphicItem],
+    options: &MarkdownOptions,
+) -> (Vec<Table>, HashSet<usize>) {
+    let base_size = table_base_font_size(text_items, options);
 
-    if sorted_pages.len() < 2 {
-        return;
+    // Pre-group items by page with their global indices (O(n) instead of O(pages*n))
+    let mut page_groups: HashMap<u32, Vec<(usize, &TextItem)>> = HashMap::new();
+    for (global_idx, item) in text_items.iter().enumerate() {
+        page_groups
+            .entry(item.page)
+            .or_default()
+            .push((global_idx, item));
     }
 
-    // Find runs of consecutive pages that each have exactly one table with matching columns
-    let mut i = 0;
-    while i < sorted_pages.len() {
-        let first_page = sorted_pages[i];
-        let first_tables = match page_tables.get(&first_page) {
-            Some(t) if t.len() == 1 => t,
-            _ => {
-                i += 1;
-                continue;
-            }
-        };
-
-        // First page must be table-only to start a merge chain
-        if !table_only_pages.contains(&first_page) {
-            i += 1;
-            continue;
-        }
-
-        let first_col_count = count_table_columns(&first_tables[0].1);
-        if first_col_count == 0 {
-            i += 1;
-            continue;
-        }
-
-        // Collect continuation pages (must also be table-only)
-        let mut continuation_pages = Vec::new();
-        let mut j = i + 1;
-        while j < sorted_pages.len() {
-            let next_page = sorted_pages[j];
-            // Must be consecutive page numbers
-            let prev_page = if continuation_pages.is_empty() {
-                first_page
-            } else {
-                *continuation_pages.last().unwrap()
-            };
-            if next_page != prev_page + 1 {
-                break;
-            }
-
-            // Continuation page must be table-only
-            if !table_only_pages.contains(&next_page) {
-                break;
-            }
-
-            let next_tables = match page_tables.get(&next_page) {
-                Some(t) if t.len() == 1 => t,
-                _ => break,
-            };
+    let mut pages: Vec<u32> = page_groups.keys().copied().collect();
+    pages.sort();
 
-            let next_col_count = count_table_columns(&next_tables[0].1);
-            if next_col_count != first_col_count {
-                break;
-            }
+    let mut tables = Vec::new();
+    let mut table_items: HashSet<usize> = HashSet::new();
+    for page in pages {
+        let group = page_groups.get(&page).unwrap();
+        let page_items: Vec<TextItem> = group.iter().map(|(_, item)| (*item).clone()).collect();
 
-            continuation_pages.push(next_page);
-            j += 1;
-        }
+        let page_graphics: Vec<GraphicItem> = graphics
+            .iter()
+            .filter(|g| g.page == page)
+            .cloned()
+            .collect();
+        let mut page_tables = detect_tables_with_config(
+            &page_items,
+            &page_graphics,
+            base_size,
+            &options.table_detection,
+        );
+        // Top of the page first
+        page_tables.sort_by(|a, b| {
+            let top = |t: &Table| t.rows.first().copied().unwrap_or(0.0);
+            top(b).total_cmp(&top(a))
+        });
 
-        if !continuation_pages.is_empty() {
-            // Collect data rows from continuation pages
-            let mut extra_rows = String::new();
-            for &cont_page in &continuation_pages {
-                if let Some(tables) = page_tables.get(&cont_page) {
-                    let table_md = &tables[0].1;
-                    // Skip header row (line 1) and separator row (line 2), keep the rest
-                    for (line_idx, line) in table_md.lines().enumerate() {
-                        if line_idx >= 2 {
-                            extra_rows.push_str(line);
-                            extra_rows.push('\n');
-                        }
-                    }
+        for table in page_tables {
+            // Mark items as belonging to a table using pre-computed global indices
+            for &idx in &table.item_indices {
+                if let Some(&(global_idx, _)) = group.get(idx) {
+                    table_items.insert(global_idx);
                 }
             }
-
-            // Append continuation rows to the first page's table
-            if let Some(tables) = page_tables.get_mut(&first_page) {
-                tables[0].1.push_str(&extra_rows);
-            }
-
-            // Remove continuation pages from the map
-            for &cont_page in &continuation_pages {
-                page_tables.remove(&cont_page);
-            }
-
-            // Skip past the merged pages
-            i = j;
-        } else {
-            i += 1;
+            tables.push(table);
         }
     }
+
+    // Body text below a table or above its continuation keeps them apart
+    let non_table_items: Vec<TextItem> = text_items
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !table_items.contains(idx))
+        .map(|(_, item)| item.clone())
+        .collect();
+    let (lines, _) = strip_running_text(group_into_lines(non_table_items), options);
+    let lines: Vec<TextLine> = lines
+        .into_iter()
+        .filter(|line| !(options.remove_page_numbers && is_page_number_line(line.text().trim())))
+        .collect();
+    (stitch_tables(tables, &lines), table_items)
 }
 
-/// Count the number of columns in a markdown table by counting `|` in the separator row.
-fn count_table_columns(table_md: &str) -> usize {
-    // The separator row is the second line, containing "| --- | --- |"
-    if let Some(sep_line) = table_md.lines().nth(1) {
-        if sep_line.contains("---") {
-            // Count cells: number of | minus 1 (leading |), but handle edge cases
-            let pipes = sep_line.chars().filter(|&c| c == '|').count();
-            return if pipes >= 2 { pipes - 1 } else { 0 };
-        }
+/// Label of a 1-based page for page markers, falling back to the page number
+fn page_label(page: u32, options: &MarkdownOptions) -> String {
+    options
+        .page_labels
+        .as_ref()
+        .and_then(|labels| labels.get(page.saturating_sub(1) as usize))
+        .cloned()
+        .unwrap_or_else(|| page.to_string())
+}
+
+/// Start a page with a `<!-- page: N -->` marker when page markers are enabled
+fn push_page_marker(
+    page: u32,
+    options: &MarkdownOptions,
+    output: &mut String,
+    in_paragraph: &mut bool,
+) {
+    if !options.page_markers {
+        return;
     }
-    0
+    if *in_paragraph {
+        output.push_str("\n\n");
+        *in_paragraph = false;
+    }
+    if !output.is_empty() && !output.ends_with("\n\n") {
+        output.push_str("\n\n");
+    }
+    output.push_str(&format!("<!-- page: {} -->\n\n", page_label(page, options)));
 }
 
 /// Flush any remaining tables and images for a given page
@@ -442,6 +640,7 @@ fn to_markdown_from_lines_with_tables_and_images(
 
     // Discover heading tiers for this document
     let heading_tiers = compute_heading_tiers(&lines, base_size);
+    let body_color = dominant_text_color(&lines);
 
     // Merge consecutive heading lines at the same level (e.g., wrapped titles)
     let lines = merge_heading_lines(lines, base_size, &heading_tiers);
@@ -500,6 +699,7 @@ fn to_markdown_from_lines_with_tables_and_images(
                 if p >= line.page {
                     break;
                 }
+                push_page_marker(p, &options, &mut output, &mut in_paragraph);
                 flush_page_tables_and_images(
                     p,
                     &page_tables,
@@ -516,6 +716,7 @@ fn to_markdown_from_lines_with_tables_and_images(
                 output.push_str("\n\n");
             }
 
+            push_page_marker(line.page, &options, &mut output, &mut in_paragraph);
             current_page = line.page;
             prev_y = f32::MAX;
         }
@@ -566,7 +767,7 @@ fn to_markdown_from_lines_with_tables_and_images(
         prev_y = line.y;
 
         // Get text with optional bold/italic formatting
-        let text = line.text_with_formatting(options.detect_bold, options.detect_italic);
+        let text = line.text_with_markup(inline_format(&options));
         let trimmed = text.trim();
 
         // Also get plain text for pattern matching (list detection, captions, etc.)
@@ -598,7 +799,14 @@ fn to_markdown_from_lines_with_tables_and_images(
         {
             let line_font_size = line.items.first().map(|i| i.font_size).unwrap_or(base_size);
             if let Some(header_level) =
-                detect_header_level(line_font_size, base_size, &heading_tiers)
+                detect_header_level(line_font_size, base_size, &heading_tiers).or_else(|| {
+                    options
+                        .detect_color_headers
+                        .then(|| {
+                            color_header_level(&line, plain_trimmed, body_color, &heading_tiers)
+                        })
+                        .flatten()
+                })
             {
                 if in_paragraph {
                     output.push_str("\n\n");
@@ -692,6 +900,7 @@ fn to_markdown_from_lines_with_tables_and_images(
         if p <= current_page {
             continue;
         }
+        push_page_marker(p, &options, &mut output, &mut in_paragraph);
         flush_page_tables_and_images(
             p,
             &page_tables,
@@ -717,6 +926,7 @@ pub fn to_markdown_from_lines(lines: Vec<TextLine>, options: MarkdownOptions) ->
     if lines.is_empty() {
         return String::new();
     }
+    let (lines, running) = strip_running_text(lines, &options);
 
     // Calculate font statistics
     let font_stats = calculate_font_stats(&lines);
@@ -729,6 +939,7 @@ pub fn to_markdown_from_lines(lines: Vec<TextLine>, options: MarkdownOptions) ->
 
     // Discover heading tiers for this document
     let heading_tiers = compute_heading_tiers(&lines, base_size);
+    let body_color = dominant_text_color(&lines);
 
     // Merge consecutive heading lines at the same level (e.g., wrapped titles)
     let lines = merge_heading_lines(lines, base_size, &heading_tiers);
@@ -753,6 +964,7 @@ pub fn to_markdown_from_lines(lines: Vec<TextLine>, options: MarkdownOptions) ->
                 }
                 output.push_str("\n\n");
             }
+            push_page_marker(line.page, &options, &mut output, &mut in_paragraph);
             current_page = line.page;
             prev_y = f32::MAX;
             in_list = false;
@@ -771,7 +983,7 @@ pub fn to_markdown_from_lines(lines: Vec<TextLine>, options: MarkdownOptions) ->
         prev_y = line.y;
 
         // Get text with optional bold/italic formatting
-        let text = line.text_with_formatting(options.detect_bold, options.detect_italic);
+        let text = line.text_with_markup(inline_format(&options));
         let trimmed = text.trim();
 
         // Also get plain text for pattern matching
@@ -802,7 +1014,14 @@ pub fn to_markdown_from_lines(lines: Vec<TextLine>, options: MarkdownOptions) ->
         {
             let line_font_size = line.items.first().map(|i| i.font_size).unwrap_or(base_size);
             if let Some(header_level) =
-                detect_header_level(line_font_size, base_size, &heading_tiers)
+                detect_header_level(line_font_size, base_size, &heading_tiers).or_else(|| {
+                    options
+                        .detect_color_headers
+                        .then(|| {
+                            color_header_level(&line, plain_trimmed, body_color, &heading_tiers)
+                        })
+                        .flatten()
+                })
             {
                 if in_paragraph {
                     output.push_str("\n\n");
@@ -886,7 +1105,7 @@ pub fn to_markdown_from_lines(lines: Vec<TextLine>, options: MarkdownOptions) ->
     }
 
     // Clean up and post-process
-    clean_markdown(output, &options)
+    prepend_running_text(clean_markdown(output, &options), &running)
 }
 
 /// Merge drop caps with the appropriate line
@@ -1169,6 +1388,95 @@ fn detect_header_level(font_size: f32, base_size: f32, heading_tiers: &[f32]) ->
     }
 }
 
+/// Most common fill color of the text, weighted by length
+fn dominant_text_color(lines: &[TextLine]) -> [f32; 3] {
+    let mut counts: HashMap<[u8; 3], usize> = HashMap::new();
+    for item in lines.iter().flat_map(|line| &line.items) {
+        *counts.entry(color_key(item.fill_color)).or_default() += item.text.chars().count();
+    }
+    counts
+        .into_iter()
+        .max_by_key(|&(color, count)| (count, std::cmp::Reverse(color)))
+        .map_or([0.0; 3], |(color, _)| color.map(|c| c as f32 / 255.0))
+}
+
+/// A color quantized to 8 bits per component, so that near-identical colors
+/// compare equal
+fn color_key(color: [f32; 3]) -> [u8; 3] {
+    color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+/// Header level for a line in the body font size whose only distinction is its
+/// color: the whole line in one dark enough color clearly unlike the body text,
+/// not a link, and not ending like a sentence. It ranks below the size-based tiers.
+fn color_header_level(
+    line: &TextLine,
+    text: &str,
+    body_color: [f32; 3],
+    heading_tiers: &[f32],
+) -> Option<usize> {
+    let first = line.items.first()?;
+    let color = first.fill_color;
+    let same_color = line
+        .items
+        .iter()
+        .all(|item| color_key(item.fill_color) == color_key(color));
+    let distance = color
+        .iter()
+        .zip(body_color)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f32::max);
+    let luminance = 0.299 * color[0] + 0.587 * color[1] + 0.114 * color[2];
+    let is_heading = same_color
+        && distance >= 0.25
+        && luminance < 0.8
+        && line.items.iter().all(|item| item.link.is_none())
+        && text.split_whitespace().count() <= 12
+        && text
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_uppercase() || c.is_ascii_digit())
+        && !text.ends_with(['.', ',', ';']);
+    is_heading.then(|| (heading_tiers.len() + 1).min(4))
+}
+
+/// Maximum vertical gap between an image and its caption, in points
+const CAPTION_MAX_DISTANCE: f32 = 72.0;
+
+/// Find the caption closest to an image: a caption line on the same page, above
+/// or below the image and overlapping it horizontally. Returns it escaped for
+/// use as alt text.
+fn image_caption(image: &TextItem, lines: &[TextLine]) -> Option<String> {
+    let image_top = image.y + image.height;
+    let image_right = image.x + image.width;
+    lines
+        .iter()
+        .filter(|line| line.page == image.page && !line.items.is_empty())
+        .filter_map(|line| {
+            let left = line.items.iter().map(|i| i.x).fold(f32::INFINITY, f32::min);
+            let right = line
+                .items
+                .iter()
+                .map(|i| i.x + i.width)
+                .fold(f32::NEG_INFINITY, f32::max);
+            if right < image.x || left > image_right {
+                return None;
+            }
+            // Line baselines sit below the image's bottom edge, or above its top
+            let distance = if line.y <= image.y {
+                image.y - line.y
+            } else if line.y >= image_top {
+                line.y - image_top
+            } else {
+                return None;
+            };
+            (distance <= CAPTION_MAX_DISTANCE).then_some((distance, line))
+        })
+        .filter(|(_, line)| is_caption_line(&line.text()))
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, line)| escape_link_text(line.text().trim()))
+}
+
 /// Check if text is a figure/table caption or source citation
 fn is_caption_line(text: &str) -> bool {
     let trimmed = text.trim();
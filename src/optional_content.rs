@@ -0,0 +1,300 @@
+//! Optional content (layers)
+//!
+//! Optional content groups (OCGs) let parts of a page be shown or hidden, e.g.
+//! print-only marks or alternate languages in maps and CAD drawings. Content is
+//! tied to a group by a `BDC /OC` marked-content section, or by an `/OC` entry on
+//! an XObject or annotation; membership dictionaries (OCMDs) combine several
+//! groups. The document's default visibility lives in `/OCProperties /D`.
+
+use crate::extractor::{decode_text_string, resolve_array, resolve_dict};
+use crate::PdfError;
+use lopdf::{Document, Object, ObjectId};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Maximum nesting of OCMD visibility expressions (/VE)
+const MAX_EXPRESSION_DEPTH: usize = 16;
+
+/// An optional content group as listed in the document
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+    /// Layer name (/Name)
+    pub name: String,
+    /// Whether the layer is shown by default (/OCProperties /D)
+    pub visible: bool,
+}
+
+/// Which layers to extract content from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum LayerSelection {
+    /// Layers visible in the document's default configuration
+    #[default]
+    Default,
+    /// Ignore optional content and extract everything
+    All,
+    /// Only the layers with these names; all other layers are hidden
+    Only(Vec<String>),
+}
+
+/// Resolved on/off state of every optional content group in a document
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OptionalContent {
+    /// Whether optional content is evaluated at all
+    enabled: bool,
+    states: HashMap<ObjectId, bool>,
+}
+
+impl OptionalContent {
+    /// Evaluate group states for a layer selection
+    pub(crate) fn new(doc: &Document, selection: &LayerSelection) -> Self {
+        if *selection == LayerSelection::All {
+            return Self::default();
+        }
+        let Some(properties) = oc_properties(doc) else {
+            return Self::default();
+        };
+
+        let mut states = default_states(doc, properties);
+        if let LayerSelection::Only(names) = selection {
+            for (id, state) in states.iter_mut() {
+                *state = group_name(doc, *id).is_some_and(|name| names.contains(&name));
+            }
+        }
+        OptionalContent {
+            enabled: true,
+            states,
+        }
+    }
+
+    /// Whether content governed by `obj` (an OCG or OCMD, direct or by
+    /// reference) is visible
+    pub(crate) fn is_visible(&self, doc: &Document, obj: &Object) -> bool {
+        !self.enabled || self.evaluate(doc, obj)
+    }
+
+    fn evaluate(&self, doc: &Document, obj: &Object) -> bool {
+        let (id, dict) = match obj {
+            Object::Reference(id) => (Some(*id), doc.get_dictionary(*id).ok()),
+            Object::Dictionary(dict) => (None, Some(dict)),
+            _ => return true,
+        };
+        let Some(dict) = dict else {
+            return true;
+        };
+        if dict.get(b"Type").and_then(Object::as_name).ok() == Some(b"OCMD") {
+            self.membership(doc, dict)
+        } else {
+            id.is_none_or(|id| self.group(id))
+        }
+    }
+
+    /// State of a single group; groups missing from /OCProperties are shown
+    fn group(&self, id: ObjectId) -> bool {
+        self.states.get(&id).copied().unwrap_or(true)
+    }
+
+    /// Visibility of an optional content membership dictionary (PDF 32000-1, 8.11.2.2)
+    fn membership(&self, doc: &Document, ocmd: &lopdf::Dictionary) -> bool {
+        if let Some(visible) = ocmd
+            .get(b"VE")
+            .ok()
+            .and_then(|ve| resolve_array(doc, ve))
+            .and_then(|ve| self.expression(doc, ve, 0))
+        {
+            return visible;
+        }
+
+        let groups: Vec<bool> = match ocmd.get(b"OCGs") {
+            Ok(Object::Reference(id)) if doc.get_dictionary(*id).is_ok() => vec![self.group(*id)],
+            Ok(obj) => resolve_array(doc, obj)
+                .into_iter()
+                .flatten()
+                .filter_map(|o| o.as_reference().ok())
+                .map(|id| self.group(id))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        if groups.is_empty() {
+            return true;
+        }
+        match ocmd.get(b"P").and_then(Object::as_name) {
+            Ok(b"AllOn") => groups.iter().all(|&on| on),
+            Ok(b"AnyOff") => groups.iter().any(|&on| !on),
+            Ok(b"AllOff") => groups.iter().all(|&on| !on),
+            _ => groups.iter().any(|&on| on),
+        }
+    }
+
+    /// Evaluate a visibility expression: `[/And|/Or|/Not operand ...]` whose
+    /// operands are groups or nested expressions
+    fn expression(&self, doc: &Document, expr: &[Object], depth: usize) -> Option<bool> {
+        if depth >= MAX_EXPRESSION_DEPTH {
+            return None;
+        }
+        let operator = expr.first()?.as_name().ok()?;
+        let operands = expr[1..]
+            .iter()
+            .map(|operand| match operand {
+                Object::Array(nested) => self.expression(doc, nested, depth + 1),
+                Object::Reference(id) => match doc.get_object(*id).ok()? {
+                    Object::Array(nested) => self.expression(doc, nested, depth + 1),
+                    _ => Some(self.group(*id)),
+                },
+                _ => None,
+            })
+            .collect::<Option<Vec<bool>>>()?;
+        match operator {
+            b"And" => Some(operands.iter().all(|&on| on)),
+            b"Or" => Some(operands.iter().any(|&on| on)),
+            b"Not" => operands.first().map(|&on| !on),
+            _ => None,
+        }
+    }
+}
+
+/// List the document's layers with their default visibility
+pub fn layers(doc: &Document) -> Vec<Layer> {
+    let Some(properties) = oc_properties(doc) else {
+        return Vec::new();
+    };
+    let states = default_states(doc, properties);
+    all_groups(doc, properties)
+        .into_iter()
+        .map(|id| Layer {
+            name: group_name(doc, id).unwrap_or_default(),
+            visible: states.get(&id).copied().unwrap_or(true),
+        })
+        .collect()
+}
+
+/// List the layers of a PDF file
+pub fn extract_layers<P: AsRef<Path>>(path: P) -> Result<Vec<Layer>, PdfError> {
+    let doc = Document::load(path)?;
+    Ok(layers(&doc))
+}
+
+/// List the layers of a PDF in memory
+pub fn extract_layers_mem(buffer: &[u8]) -> Result<Vec<Layer>, PdfError> {
+    let doc = Document::load_mem(buffer)?;
+    Ok(layers(&doc))
+}
+
+fn oc_properties(doc: &Document) -> Option<&lopdf::Dictionary> {
+    let properties = doc.catalog().ok()?.get(b"OCProperties").ok()?;
+    resolve_dict(doc, properties)
+}
+
+/// Every group declared in /OCProperties /OCGs, in document order
+fn all_groups(doc: &Document, properties: &lopdf::Dictionary) -> Vec<ObjectId> {
+    properties
+        .get(b"OCGs")
+        .ok()
+        .and_then(|o| resolve_array(doc, o))
+        .into_iter()
+        .flatten()
+        .filter_map(|o| o.as_reference().ok())
+        .collect()
+}
+
+/// Group states in the default configuration: /BaseState, then /ON and /OFF
+fn default_states(doc: &Document, properties: &lopdf::Dictionary) -> HashMap<ObjectId, bool> {
+    let config = properties.get(b"D").ok().and_then(|o| resolve_dict(doc, o));
+    let base_on = config
+        .and_then(|d| d.get(b"BaseState").and_then(Object::as_name).ok())
+        .is_none_or(|state| state != b"OFF");
+
+    let mut states: HashMap<ObjectId, bool> = all_groups(doc, properties)
+        .into_iter()
+        .map(|id| (id, base_on))
+        .collect();
+    if let Some(config) = config {
+        for (key, on) in [(&b"ON"[..], true), (&b"OFF"[..], false)] {
+            let ids = config
+                .get(key)
+                .ok()
+                .and_then(|o| resolve_array(doc, o))
+                .into_iter()
+                .flatten()
+                .filter_map(|o| o.as_reference().ok());
+            for id in ids {
+                states.insert(id, on);
+            }
+        }
+    }
+    states
+}
+
+fn group_name(doc: &Document, id: ObjectId) -> Option<String> {
+    let group = doc.get_dictionary(id).ok()?;
+    decode_text_string(doc, group.get(b"Name").ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_default_states_and_membership() {
+        let mut doc = Document::with_version("1.5");
+        let print = doc.add_object(dictionary! {
+            "Type" => "OCG",
+            "Name" => Object::string_literal("Print marks"),
+        });
+        let english = doc.add_object(dictionary! {
+            "Type" => "OCG",
+            "Name" => Object::string_literal("English"),
+        });
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "OCProperties" => dictionary! {
+                "OCGs" => vec![print.into(), english.into()],
+                "D" => dictionary! { "OFF" => vec![print.into()] },
+            },
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        assert_eq!(
+            layers(&doc),
+            vec![
+                Layer {
+                    name: "Print marks".to_string(),
+                    visible: false,
+                },
+                Layer {
+                    name: "English".to_string(),
+                    visible: true,
+                },
+            ]
+        );
+
+        let oc = OptionalContent::new(&doc, &LayerSelection::Default);
+        assert!(!oc.is_visible(&doc, &Object::Reference(print)));
+        assert!(oc.is_visible(&doc, &Object::Reference(english)));
+
+        let any_on = Object::Dictionary(dictionary! {
+            "Type" => "OCMD",
+            "OCGs" => vec![print.into(), english.into()],
+        });
+        let all_on = Object::Dictionary(dictionary! {
+            "Type" => "OCMD",
+            "OCGs" => vec![print.into(), english.into()],
+            "P" => "AllOn",
+        });
+        let not_english = Object::Dictionary(dictionary! {
+            "Type" => "OCMD",
+            "VE" => vec![Object::Name(b"Not".to_vec()), english.into()],
+        });
+        assert!(oc.is_visible(&doc, &any_on));
+        assert!(!oc.is_visible(&doc, &all_on));
+        assert!(!oc.is_visible(&doc, &not_english));
+
+        let only_print =
+            OptionalContent::new(&doc, &LayerSelection::Only(vec!["Print marks".into()]));
+        assert!(only_print.is_visible(&doc, &Object::Reference(print)));
+        assert!(!only_print.is_visible(&doc, &Object::Reference(english)));
+
+        let all = OptionalContent::new(&doc, &LayerSelection::All);
+        assert!(all.is_visible(&doc, &all_on));
+    }
+}
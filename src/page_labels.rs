@@ -0,0 +1,312 @@
+//! Page labels
+//!
+//! Documents can number their pages independently of the physical page order,
+//! e.g. roman numerals for front matter or "A-1" for appendices. The labels are
+//! stored as ranges in the catalog's `/PageLabels` number tree.
+
+use crate::extractor::{decode_text_string, resolve_array, resolve_dict};
+use crate::PdfError;
+use lopdf::{Document, Object};
+use std::path::Path;
+
+/// Maximum depth of the `/PageLabels` number tree
+const MAX_TREE_DEPTH: usize = 32;
+
+/// Largest page number written as a roman numeral or letters; larger ones
+/// would take thousands of characters and are written as decimals
+const MAX_STYLED_NUMBER: u32 = 10_000;
+
+/// Largest accepted `/St` start number
+const MAX_START: u32 = 1_000_000_000;
+
+/// Numbering style of a page label range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberingStyle {
+    /// Decimal arabic numerals (1, 2, 3)
+    Decimal,
+    /// Uppercase roman numerals (I, II, III)
+    UpperRoman,
+    /// Lowercase roman numerals (i, ii, iii)
+    LowerRoman,
+    /// Uppercase letters (A to Z, then AA to ZZ, ...)
+    UpperLetters,
+    /// Lowercase letters (a to z, then aa to zz, ...)
+    LowerLetters,
+}
+
+/// A page label range starting at a given page
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageLabelRange {
+    /// 0-based index of the first page in the range
+    pub start_index: u32,
+    /// Numbering style; `None` means labels consist of the prefix only
+    pub style: Option<NumberingStyle>,
+    /// Prefix prepended to each number
+    pub prefix: String,
+    /// Number of the first page in the range
+    pub start: u32,
+}
+
+impl PageLabelRange {
+    /// Label of the page at `page_index` (0-based), which must be within this range
+    pub fn label(&self, page_index: u32) -> String {
+        let number = self
+            .start
+            .saturating_add(page_index.saturating_sub(self.start_index));
+        let numeral = match self.style {
+            Some(NumberingStyle::Decimal) => number.to_string(),
+            Some(_) if number > MAX_STYLED_NUMBER => number.to_string(),
+            Some(NumberingStyle::UpperRoman) => to_roman(number),
+            Some(NumberingStyle::LowerRoman) => to_roman(number).to_lowercase(),
+            Some(NumberingStyle::UpperLetters) => to_letters(number),
+            Some(NumberingStyle::LowerLetters) => to_letters(number).to_lowercase(),
+            None => String::new(),
+        };
+        format!("{}{}", self.prefix, numeral)
+    }
+}
+
+/// Read the page label ranges from the document catalog, sorted by start page
+pub fn page_label_ranges(doc: &Document) -> Vec<PageLabelRange> {
+    let mut ranges = Vec::new();
+    let tree = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"PageLabels").ok())
+        .and_then(|o| resolve_dict(doc, o));
+    if let Some(tree) = tree {
+        collect_ranges(doc, tree, &mut ranges, 0);
+    }
+    ranges.sort_by_key(|r| r.start_index);
+    ranges
+}
+
+/// Label of every page, in page order. Pages not covered by `/PageLabels`
+/// (or all pages, if the document has none) are labelled with their
+/// 1-based physical page number.
+pub fn page_labels(doc: &Document) -> Vec<String> {
+    let page_count = doc.get_pages().len() as u32;
+    labels_from_ranges(&page_label_ranges(doc), page_count)
+}
+
+/// Label of each of `page_count` pages from ranges sorted by start page, as
+/// [`page_labels`] does
+pub fn labels_from_ranges(ranges: &[PageLabelRange], page_count: u32) -> Vec<String> {
+    (0..page_count)
+        .map(|index| {
+            ranges
+                .iter()
+                .rev()
+                .find(|r| r.start_index <= index)
+                .map(|r| r.label(index))
+                .unwrap_or_else(|| (index + 1).to_string())
+        })
+        .collect()
+}
+
+/// Read the page labels of a PDF file
+pub fn extract_page_labels<P: AsRef<Path>>(path: P) -> Result<Vec<String>, PdfError> {
+    let doc = Document::load(path)?;
+    Ok(page_labels(&doc))
+}
+
+/// Read the page labels of a PDF in memory
+pub fn extract_page_labels_mem(buffer: &[u8]) -> Result<Vec<String>, PdfError> {
+    let doc = Document::load_mem(buffer)?;
+    Ok(page_labels(&doc))
+}
+
+/// Walk a number tree node (PDF 32000-1, 7.9.7) collecting label ranges
+fn collect_ranges(
+    doc: &Document,
+    node: &lopdf::Dictionary,
+    ranges: &mut Vec<PageLabelRange>,
+    depth: usize,
+) {
+    if depth >= MAX_TREE_DEPTH {
+        return;
+    }
+    if let Some(nums) = node.get(b"Nums").ok().and_then(|o| resolve_array(doc, o)) {
+        for pair in nums.chunks_exact(2) {
+            let Ok(start_index) = pair[0].as_i64() else {
+                continue;
+            };
+            let Some(label) = resolve_dict(doc, &pair[1]) else {
+                continue;
+            };
+            ranges.push(parse_range(doc, start_index.max(0) as u32, label));
+        }
+    }
+    if let Some(kids) = node.get(b"Kids").ok().and_then(|o| resolve_array(doc, o)) {
+        for kid in kids {
+            if let Some(kid) = resolve_dict(doc, kid) {
+                collect_ranges(doc, kid, ranges, depth + 1);
+            }
+        }
+    }
+}
+
+fn parse_range(doc: &Document, start_index: u32, label: &lopdf::Dictionary) -> PageLabelRange {
+    let style = match label.get(b"S").and_then(Object::as_name) {
+        Ok(b"D") => Some(NumberingStyle::Decimal),
+        Ok(b"R") => Some(NumberingStyle::UpperRoman),
+        Ok(b"r") => Some(NumberingStyle::LowerRoman),
+        Ok(b"A") => Some(NumberingStyle::UpperLetters),
+        Ok(b"a") => Some(NumberingStyle::LowerLetters),
+        _ => None,
+    };
+    let prefix = label
+        .get(b"P")
+        .ok()
+        .and_then(|o| decode_text_string(doc, o))
+        .unwrap_or_default();
+    let start = label
+        .get(b"St")
+        .and_then(Object::as_i64)
+        .ok()
+        .filter(|&st| st >= 1)
+        .map_or(1, |st| u32::try_from(st).unwrap_or(u32::MAX).min(MAX_START));
+    PageLabelRange {
+        start_index,
+        style,
+        prefix,
+        start,
+    }
+}
+
+/// Uppercase roman numeral (numbers of 4000 and above repeat M)
+fn to_roman(mut number: u32) -> String {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut result = String::new();
+    for &(value, numeral) in &NUMERALS {
+        while number >= value {
+            result.push_str(numeral);
+            number -= value;
+        }
+    }
+    result
+}
+
+/// Uppercase letter label: A to Z, then AA to ZZ, AAA to ZZZ, ...
+fn to_letters(number: u32) -> String {
+    if number == 0 {
+        return String::new();
+    }
+    let letter = (b'A' + ((number - 1) % 26) as u8) as char;
+    let count = (number - 1) / 26 + 1;
+    std::iter::repeat_n(letter, count as usize).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::dictionary;
+
+    #[test]
+    fn test_numbering_styles() {
+        assert_eq!(to_roman(4), "IV");
+        assert_eq!(to_roman(1994), "MCMXCIV");
+        assert_eq!(to_letters(1), "A");
+        assert_eq!(to_letters(26), "Z");
+        assert_eq!(to_letters(28), "BB");
+    }
+
+    #[test]
+    fn test_huge_start_numbers() {
+        let range = |style, start| PageLabelRange {
+            start_index: 0,
+            style: Some(style),
+            prefix: String::new(),
+            start,
+        };
+        // Styled numbers past the limit fall back to decimal
+        assert_eq!(
+            range(NumberingStyle::UpperLetters, 20_000).label(0),
+            "20000"
+        );
+        assert_eq!(range(NumberingStyle::LowerRoman, 10_000).label(1), "10001");
+        assert_eq!(
+            range(NumberingStyle::Decimal, u32::MAX).label(5),
+            u32::MAX.to_string()
+        );
+
+        // Large `/St` values are clamped, including those beyond u32
+        let start = |st: i64| {
+            parse_range(
+                &Document::with_version("1.5"),
+                0,
+                &lopdf::dictionary! { "S" => "D", "St" => st },
+            )
+            .start
+        };
+        assert_eq!(start(i64::MAX), MAX_START);
+        assert_eq!(start(4_294_967_295), MAX_START);
+        assert_eq!(start(-3), 1);
+        assert_eq!(start(12), 12);
+    }
+
+    #[test]
+    fn test_page_labels_from_number_tree() {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let kids: Vec<Object> = (0..7)
+            .map(|_| {
+                doc.add_object(dictionary! { "Type" => "Page", "Parent" => pages_id })
+                    .into()
+            })
+            .collect();
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => 7,
+            }),
+        );
+        // Front matter i-iii, body 1-2, appendix A-1, A-2 (in a child node)
+        let appendix = doc.add_object(dictionary! {
+            "Nums" => vec![
+                5.into(),
+                dictionary! { "S" => "D", "P" => Object::string_literal("A-") }.into(),
+            ],
+        });
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+            "PageLabels" => dictionary! {
+                "Kids" => vec![
+                    dictionary! {
+                        "Nums" => vec![
+                            0.into(),
+                            dictionary! { "S" => "r" }.into(),
+                            3.into(),
+                            dictionary! { "S" => "D" }.into(),
+                        ],
+                    }
+                    .into(),
+                    appendix.into(),
+                ],
+            },
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        assert_eq!(
+            page_labels(&doc),
+            vec!["i", "ii", "iii", "1", "2", "A-1", "A-2"]
+        );
+    }
+}
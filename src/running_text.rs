@@ -0,0 +1,284 @@
+//! Running headers and footers
+//!
+//! Journal citations, confidentiality banners and document titles are often
+//! repeated at the top or bottom of every page. They are found by comparing the
+//! first and last lines of each page across the document: text that recurs in
+//! the same vertical band on enough pages is running text. Digits and month
+//! names are ignored when comparing, so "Page 3 of 12" or a date stamp still
+//! matches from page to page.
+
+use crate::extractor::TextLine;
+use std::collections::{HashMap, HashSet};
+
+/// Lines at each end of a page considered as header or footer candidates
+const BAND_LINES: usize = 3;
+
+/// Maximum vertical drift of a running line between pages, in points
+const BAND_TOLERANCE: f32 = 6.0;
+
+/// Minimum number of pages a line must repeat on
+const MIN_PAGES: usize = 3;
+
+/// Minimum fraction of the document's pages a line must repeat on; below one
+/// half so alternating even/odd page headers are found
+const MIN_PAGE_FRACTION: f32 = 0.4;
+
+const MONTHS: [&str; 24] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+    "jan",
+    "feb",
+    "mar",
+    "apr",
+    "jun",
+    "jul",
+    "aug",
+    "sep",
+    "sept",
+    "oct",
+    "nov",
+    "dec",
+];
+
+/// What markdown conversion does with running headers and footers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RunningTextMode {
+    /// Leave them in place on every page
+    #[default]
+    Keep,
+    /// Drop them
+    Remove,
+    /// Drop them from the pages and list each once at the top of the document,
+    /// as `<!-- running header: ... -->` / `<!-- running footer: ... -->`
+    Metadata,
+}
+
+/// A candidate line: page, baseline and text
+type Occurrence = (u32, f32, String);
+
+/// Where running text sits on the page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Placement {
+    Header,
+    Footer,
+}
+
+/// Text repeated at the same position on many pages
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunningText {
+    /// Header or footer
+    pub placement: Placement,
+    /// Text of the first occurrence
+    pub text: String,
+    /// Typical baseline position
+    pub y: f32,
+    /// Pages the text appears on
+    pub pages: Vec<u32>,
+    /// Comparison key: the text with digits and month names masked
+    key: String,
+}
+
+impl RunningText {
+    /// Whether `line` is an occurrence of this running text
+    pub fn matches(&self, line: &TextLine) -> bool {
+        self.pages.binary_search(&line.page).is_ok()
+            && (line.y - self.y).abs() <= BAND_TOLERANCE
+            && normalize(&line.text()) == self.key
+    }
+}
+
+/// Find running headers and footers, in order of first appearance
+pub fn detect_running_text(lines: &[TextLine]) -> Vec<RunningText> {
+    let mut by_page: HashMap<u32, Vec<&TextLine>> = HashMap::new();
+    for line in lines {
+        by_page.entry(line.page).or_default().push(line);
+    }
+    let page_count = by_page.len();
+    let min_pages = MIN_PAGES.max((page_count as f32 * MIN_PAGE_FRACTION).ceil() as usize);
+    if page_count < min_pages {
+        return Vec::new();
+    }
+
+    // Candidate occurrences by (placement, key)
+    let mut candidates: HashMap<(Placement, String), Vec<Occurrence>> = HashMap::new();
+    let mut order: Vec<(Placement, String)> = Vec::new();
+    let mut pages: Vec<u32> = by_page.keys().copied().collect();
+    pages.sort();
+    for page in pages {
+        let mut page_lines = by_page.remove(&page).unwrap_or_default();
+        page_lines.sort_by(|a, b| b.y.total_cmp(&a.y));
+        let count = page_lines.len();
+        for (rank, line) in page_lines.into_iter().enumerate() {
+            // On short pages a line can be near both ends; the nearer one wins
+            let from_bottom = count - 1 - rank;
+            let placement = if rank < BAND_LINES && rank <= from_bottom {
+                Placement::Header
+            } else if from_bottom < BAND_LINES {
+                Placement::Footer
+            } else {
+                continue;
+            };
+            let text = line.text();
+            // Bare page numbers are left to `remove_page_numbers`
+            if !text.chars().any(char::is_alphabetic) {
+                continue;
+            }
+            let key = (placement, normalize(&text));
+            let occurrences = candidates.entry(key.clone()).or_default();
+            if occurrences.is_empty() {
+                order.push(key);
+            }
+            occurrences.push((page, line.y, text.trim().to_string()));
+        }
+    }
+
+    let mut found = Vec::new();
+    for key in order {
+        let occurrences = &candidates[&key];
+        let mut ys: Vec<f32> = occurrences.iter().map(|o| o.1).collect();
+        ys.sort_by(f32::total_cmp);
+        let y = ys[ys.len() / 2];
+        let in_band: Vec<&Occurrence> = occurrences
+            .iter()
+            .filter(|o| (o.1 - y).abs() <= BAND_TOLERANCE)
+            .collect();
+        let mut pages: Vec<u32> = in_band.iter().map(|o| o.0).collect();
+        pages.dedup();
+        if pages.len() < min_pages {
+            continue;
+        }
+        found.push(RunningText {
+            placement: key.0,
+            text: in_band[0].2.clone(),
+            y,
+            pages,
+            key: key.1,
+        });
+    }
+    found
+}
+
+/// Drop the occurrences of running text from `lines`
+pub fn remove_running_text(lines: Vec<TextLine>, running: &[RunningText]) -> Vec<TextLine> {
+    if running.is_empty() {
+        return lines;
+    }
+    lines
+        .into_iter()
+        .filter(|line| !running.iter().any(|r| r.matches(line)))
+        .collect()
+}
+
+/// Comparison key for a line: lowercase, whitespace collapsed, digit runs
+/// replaced by `#` and month names by `<month>`
+fn normalize(text: &str) -> String {
+    let months: HashSet<&str> = MONTHS.into_iter().collect();
+    let mut words = Vec::new();
+    for word in text.split_whitespace() {
+        let lower = word.to_lowercase();
+        let bare = lower.trim_matches(|c: char| !c.is_alphanumeric());
+        if months.contains(bare) {
+            words.push("<month>".to_string());
+            continue;
+        }
+        let mut masked = String::with_capacity(lower.len());
+        for c in lower.chars() {
+            if c.is_ascii_digit() {
+                if !masked.ends_with('#') {
+                    masked.push('#');
+                }
+            } else {
+                masked.push(c);
+            }
+        }
+        words.push(masked);
+    }
+    words.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractor::TextItem;
+
+    fn line(text: &str, y: f32, page: u32) -> TextLine {
+        TextLine {
+            items: vec![TextItem {
+                text: text.to_string(),
+                x: 72.0,
+                y,
+                width: 200.0,
+                height: 10.0,
+                font_size: 10.0,
+                page,
+                ..Default::default()
+            }],
+            y,
+            page,
+        }
+    }
+
+    #[test]
+    fn test_normalize_masks_numbers_and_dates() {
+        assert_eq!(normalize("Page 3 of 12"), normalize("Page 10 of 12"));
+        assert_eq!(
+            normalize("Printed March 3, 2024"),
+            normalize("Printed Apr. 14, 2024")
+        );
+        assert_ne!(normalize("Chapter One"), normalize("Chapter Two"));
+    }
+
+    #[test]
+    fn test_detect_and_remove_running_text() {
+        let body = [
+            ["Introduction", "Scope of the audit", "Methodology"],
+            ["Findings", "Revenue recognition", "Inventory counts"],
+            ["Recommendations", "Controls", "Staffing"],
+            ["Conclusion", "Sign-off", "Distribution"],
+        ];
+        let mut lines = Vec::new();
+        for page in 1..=4 {
+            lines.push(line("ACME Corp - Confidential", 760.0 + page as f32, page));
+            for (i, text) in body[page as usize - 1].iter().enumerate() {
+                lines.push(line(text, 600.0 - 100.0 * i as f32, page));
+            }
+            lines.push(line(
+                &format!("Annual Report 2023 | Page {}", page),
+                40.0,
+                page,
+            ));
+        }
+        // The same text in the body is not running text
+        lines.push(line("ACME Corp - Confidential", 500.0, 2));
+
+        let running = detect_running_text(&lines);
+        assert_eq!(running.len(), 2);
+        assert_eq!(running[0].placement, Placement::Header);
+        assert_eq!(running[0].text, "ACME Corp - Confidential");
+        assert_eq!(running[0].pages, vec![1, 2, 3, 4]);
+        assert_eq!(running[1].placement, Placement::Footer);
+        assert_eq!(running[1].text, "Annual Report 2023 | Page 1");
+
+        let kept = remove_running_text(lines, &running);
+        let texts: Vec<String> = kept.iter().map(|l| l.text()).collect();
+        assert_eq!(texts.len(), 13);
+        assert!(texts.iter().all(|t| !t.starts_with("Annual Report")));
+        assert_eq!(
+            texts
+                .iter()
+                .filter(|t| t.as_str() == "ACME Corp - Confidential")
+                .count(),
+            1
+        );
+    }
+}
@@ -0,0 +1,604 @@
+//! Structured table export
+//!
+//! Tables found by [`crate::tables`] as data rather than markdown: each table
+//! with its pages, bounds and header rows, and each cell with its page, its own
+//! bounds and a typed value parsed from its text. Tables can be written out as CSV or JSON.
+
+use crate::extractor::{ItemType, TextItem};
+use crate::graphics::GraphicItem;
+use crate::markdown::{document_tables, remove_unwanted_items, MarkdownOptions};
+use crate::tables::{RowKind, Table};
+use crate::PdfError;
+use std::collections::HashMap;
+use std::path::Path;
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+/// Currency symbols recognized before or after an amount
+const CURRENCY_SYMBOLS: [&str; 7] = ["$", "€", "£", "¥", "₹", "CHF", "R$"];
+
+/// Value of a table cell, parsed from its text
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue {
+    /// No text
+    Empty,
+    /// Number; thousands separators are dropped and `(1,234)` is negative
+    Number(f64),
+    /// Percentage, in percent (`12.5%` is 12.5)
+    Percent(f64),
+    /// Amount with its currency symbol or ISO code
+    Currency { amount: f64, currency: String },
+    /// Calendar date
+    Date { year: i32, month: u32, day: u32 },
+    /// Anything else
+    Text(String),
+}
+
+/// A cell of an exported table
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableCell {
+    /// Grid position of the cell's top-left corner
+    pub row: usize,
+    pub col: usize,
+    /// Grid positions the cell covers (1 unless merged)
+    pub row_span: usize,
+    pub col_span: usize,
+    /// Page number (1-indexed) the cell is on
+    pub page: u32,
+    /// Label of that page from `/PageLabels`, if the document labels its pages
+    pub page_label: Option<String>,
+    /// Cell text; wrapped lines are separated by `\n`
+    pub text: String,
+    /// Bounds as `[x_min, y_min, x_max, y_max]`; `None` for empty cells of
+    /// tables without ruling lines
+    pub bbox: Option<[f32; 4]>,
+    /// Typed value of the text
+    pub value: CellValue,
+}
+
+/// A table with its location, as data
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractedTable {
+    /// Page number (1-indexed) the table starts on
+    pub page: u32,
+    /// Label of that page from `/PageLabels`, if the document labels its pages
+    pub page_label: Option<String>,
+    /// Pages the table spans, when stitched across page breaks
+    pub pages: Vec<u32>,
+    /// Bounds on the first page as `[x_min, y_min, x_max, y_max]`
+    pub bbox: [f32; 4],
+    /// Number of leading rows forming the header
+    pub header_rows: usize,
+    /// Grid size
+    pub rows: usize,
+    pub cols: usize,
+    /// Indentation level of each row's label, 0 being the outermost; empty
+    /// when the labels are not indented
+    pub row_levels: Vec<usize>,
+    /// What each row holds: header, data, subtotal or total
+    pub row_kinds: Vec<RowKind>,
+    /// Cells in row-major order; positions covered by a merged cell are left out
+    pub cells: Vec<TableCell>,
+}
+
+impl ExtractedTable {
+    fn from_table(table: &Table, page_labels: &HashMap<u32, String>) -> Self {
+        let pages = table.pages();
+        let page = pages.first().copied().unwrap_or(1);
+        let mut cells = Vec::new();
+        for (row, row_cells) in table.cells.iter().enumerate() {
+            for (col, text) in row_cells.iter().enumerate() {
+                let (row_span, col_span) = match table.span_at(row, col) {
+                    Some(span) if (span.row, span.col) != (row, col) => continue,
+                    Some(span) => (span.row_span, span.col_span),
+                    None => (1, 1),
+                };
+                let cell_page = table.row_pages.get(row).copied().unwrap_or(page);
+                cells.push(TableCell {
+                    row,
+                    col,
+                    row_span,
+                    col_span,
+                    page: cell_page,
+                    page_label: page_labels.get(&cell_page).cloned(),
+                    text: text.clone(),
+                    bbox: table
+                        .cell_bounds
+                        .get(row)
+                        .and_then(|r| r.get(col))
+                        .copied()
+                        .flatten(),
+                    value: parse_cell_value(text),
+                });
+            }
+        }
+        ExtractedTable {
+            page,
+            page_label: page_labels.get(&page).cloned(),
+            pages,
+            bbox: table.bbox().unwrap_or_default(),
+            header_rows: table.header_rows,
+            rows: table.cells.len(),
+            cols: table.cells.first().map_or(0, Vec::len),
+            row_levels: table.row_levels.clone(),
+            row_kinds: table.row_kinds.clone(),
+            cells,
+        }
+    }
+
+    /// Cell text as a grid; merged cells hold their text in the top-left position
+    pub fn grid(&self) -> Vec<Vec<String>> {
+        let mut grid = vec![vec![String::new(); self.cols]; self.rows];
+        for cell in &self.cells {
+            grid[cell.row][cell.col] = cell.text.clone();
+        }
+        grid
+    }
+
+    /// The table as CSV (RFC 4180), header rows included. Tables with
+    /// indented row labels get a leading `level` column holding the hierarchy.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for (row, cells) in self.grid().iter().enumerate() {
+            let mut fields: Vec<String> = cells.iter().map(|text| csv_field(text)).collect();
+            if let Some(&level) = self.row_levels.get(row) {
+                let level = match self.row_kinds.get(row) {
+                    Some(RowKind::Header) if row == 0 => "level".to_string(),
+                    Some(RowKind::Header) => String::new(),
+                    _ => level.to_string(),
+                };
+                fields.insert(0, level);
+            }
+            csv.push_str(&fields.join(","));
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+
+    /// The table as a JSON object
+    pub fn to_json(&self) -> String {
+        let cells: Vec<String> = self
+            .cells
+            .iter()
+            .map(|cell| {
+                format!(
+                    r#"{{"row":{},"col":{},"row_span":{},"col_span":{},"page":{},"page_label":{},"text":{},"bbox":{},{}}}"#,
+                    cell.row,
+                    cell.col,
+                    cell.row_span,
+                    cell.col_span,
+                    cell.page,
+                    json_label(cell.page_label.as_deref()),
+                    json_string(&cell.text),
+                    cell.bbox.map_or("null".to_string(), |b| json_bbox(&b)),
+                    json_value(&cell.value),
+                )
+            })
+            .collect();
+        format!(
+            r#"{{"page":{},"page_label":{},"pages":[{}],"bbox":{},"header_rows":{},"rows":{},"cols":{},"row_levels":[{}],"row_kinds":[{}],"cells":[{}]}}"#,
+            self.page,
+            json_label(self.page_label.as_deref()),
+            self.pages
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+            json_bbox(&self.bbox),
+            self.header_rows,
+            self.rows,
+            self.cols,
+            self.row_levels
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+            self.row_kinds
+                .iter()
+                .map(|kind| match kind {
+                    RowKind::Header => r#""header""#,
+                    RowKind::Data => r#""data""#,
+                    RowKind::Subtotal => r#""subtotal""#,
+                    RowKind::Total => r#""total""#,
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+            cells.join(",")
+        )
+    }
+}
+
+/// Tables as a JSON array
+pub fn tables_to_json(tables: &[ExtractedTable]) -> String {
+    let tables: Vec<String> = tables.iter().map(ExtractedTable::to_json).collect();
+    format!("[{}]", tables.join(","))
+}
+
+/// Extract the tables of a PDF file
+pub fn extract_tables<P: AsRef<Path>>(path: P) -> Result<Vec<ExtractedTable>, PdfError> {
+    let buffer = std::fs::read(path.as_ref())?;
+    extract_tables_mem(&buffer)
+}
+
+/// Extract the tables of a PDF in memory
+pub fn extract_tables_mem(buffer: &[u8]) -> Result<Vec<ExtractedTable>, PdfError> {
+    let (items, graphics) = crate::extractor::extract_text_and_graphics_mem(buffer)?;
+    Ok(tables_from_items(
+        items,
+        &graphics,
+        &MarkdownOptions::default(),
+    ))
+}
+
+/// Find the tables among positioned text items and page graphics, as markdown
+/// conversion with `options` would, in page order. Tables continued across
+/// page breaks come out whole.
+pub fn tables_from_items(
+    items: Vec<TextItem>,
+    graphics: &[GraphicItem],
+    options: &MarkdownOptions,
+) -> Vec<ExtractedTable> {
+    let text_items: Vec<TextItem> = remove_unwanted_items(items, options)
+        .into_iter()
+        .filter(|item| item.item_type == ItemType::Text)
+        .collect();
+    let page_labels: HashMap<u32, String> = text_items
+        .iter()
+        .filter_map(|item| Some((item.page, item.page_label.clone()?)))
+        .collect();
+    let (tables, _) = document_tables(&text_items, graphics, options);
+    tables
+        .iter()
+        .map(|table| ExtractedTable::from_table(table, &page_labels))
+        .collect()
+}
+
+/// Parse the text of a cell into a typed value
+pub fn parse_cell_value(text: &str) -> CellValue {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return CellValue::Empty;
+    }
+    if let Some(value) = parse_date(&text) {
+        return value;
+    }
+
+    // "(3.2)%" and "(3.2%)" are negative percentages
+    let percent = match text.strip_prefix('(').and_then(|t| t.strip_suffix("%)")) {
+        Some(inner) => parse_number(inner).map(|n| -n),
+        None => text.strip_suffix('%').and_then(parse_signed_amount),
+    };
+    if let Some(number) = percent {
+        return CellValue::Percent(number);
+    }
+
+    // Parentheses negate, inside or outside a currency symbol
+    let (sign, inner) = match text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        Some(inner) => (-1.0, inner.trim()),
+        None => (1.0, text.as_str()),
+    };
+    if let Some(number) = parse_number(inner) {
+        return CellValue::Number(sign * number);
+    }
+    if let Some((amount, currency)) = parse_currency(inner) {
+        return CellValue::Currency {
+            amount: sign * amount,
+            currency,
+        };
+    }
+    CellValue::Text(text)
+}
+
+/// Parse a plain number: `1,234.5`, `-12`, `+3`, `(1,234)` is handled by the caller
+fn parse_number(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let (negative, digits) = match text.chars().next()? {
+        '-' | '−' | '–' => (
+            true,
+            text.char_indices().nth(1).map_or("", |(i, _)| &text[i..]),
+        ),
+        '+' => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let digits = digits.trim();
+    // Any parenthesized part was already stripped; allow only digits, separators and a point
+    if digits.is_empty()
+        || !digits.chars().next()?.is_ascii_digit() && !digits.starts_with('.')
+        || !digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == ',' || c == '.')
+    {
+        return None;
+    }
+    // Thousands separators must group three digits
+    let (whole, _) = digits.split_once('.').unwrap_or((digits, ""));
+    if whole.contains(',') {
+        let mut groups = whole.split(',');
+        let first = groups.next()?;
+        if first.is_empty() || first.len() > 3 || groups.any(|g| g.len() != 3) {
+            return None;
+        }
+    }
+    // Digit strings beyond f64 range parse as infinity, which JSON cannot hold
+    let value = digits
+        .replace(',', "")
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())?;
+    Some(if negative { -value } else { value })
+}
+
+/// Parse an amount with a currency symbol or ISO code before or after it
+fn parse_currency(text: &str) -> Option<(f64, String)> {
+    let is_code = |code: &str| code.len() == 3 && code.chars().all(|c| c.is_ascii_uppercase());
+    for symbol in CURRENCY_SYMBOLS {
+        if let Some(rest) = text.strip_prefix(symbol) {
+            return parse_signed_amount(rest).map(|amount| (amount, symbol.to_string()));
+        }
+        if let Some(rest) = text.strip_suffix(symbol) {
+            return parse_signed_amount(rest).map(|amount| (amount, symbol.to_string()));
+        }
+    }
+    // "-$1,200" puts the sign before the symbol
+    if let Some(rest) = text.strip_prefix(['-', '−']) {
+        return parse_currency(rest.trim()).map(|(amount, currency)| (-amount, currency));
+    }
+    let (first, last) = text.split_once(' ')?;
+    if is_code(first) {
+        return parse_signed_amount(last).map(|amount| (amount, first.to_string()));
+    }
+    let (amount, code) = text.rsplit_once(' ')?;
+    if is_code(code) {
+        return parse_signed_amount(amount).map(|amount| (amount, code.to_string()));
+    }
+    None
+}
+
+/// A number, possibly in parentheses to mark it negative
+fn parse_signed_amount(text: &str) -> Option<f64> {
+    let text = text.trim();
+    match text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        Some(inner) => parse_number(inner).map(|n| -n),
+        None => parse_number(text),
+    }
+}
+
+/// Parse `2023-12-31`, `12/31/2023`, `31.12.2023`, `December 31, 2023`,
+/// `31 Dec 2023` and similar. Slashed dates are read month first unless the
+/// first number cannot be a month.
+fn parse_date(text: &str) -> Option<CellValue> {
+    let date = |year: i32, month: u32, day: u32| {
+        let valid = (1..=12).contains(&month) && (1..=31).contains(&day) && year >= 1000;
+        valid.then_some(CellValue::Date { year, month, day })
+    };
+    let numbers = |sep: char| -> Option<Vec<u32>> {
+        let parts: Vec<&str> = text.split(sep).collect();
+        if parts.len() != 3 || parts.iter().any(|p| p.is_empty() || p.len() > 4) {
+            return None;
+        }
+        parts.iter().map(|p| p.parse().ok()).collect()
+    };
+
+    if let Some(n) = numbers('-').filter(|_| text.len() == 10) {
+        return date(n[0] as i32, n[1], n[2]);
+    }
+    if let Some(n) = numbers('/') {
+        let year = if n[2] < 100 { n[2] + 2000 } else { n[2] } as i32;
+        return if n[0] > 12 {
+            date(year, n[1], n[0])
+        } else {
+            date(year, n[0], n[1])
+        };
+    }
+    if let Some(n) = numbers('.').filter(|n| n[2] >= 1000) {
+        return date(n[2] as i32, n[1], n[0]);
+    }
+
+    // Month names
+    let words: Vec<String> = text
+        .split([' ', ','])
+        .filter(|w| !w.is_empty())
+        .map(|w| w.trim_end_matches('.').to_lowercase())
+        .collect();
+    if words.len() != 3 {
+        return None;
+    }
+    let month = |word: &str| {
+        MONTHS
+            .iter()
+            .position(|m| word.len() >= 3 && m.starts_with(word))
+            .map(|i| i as u32 + 1)
+    };
+    let number = |word: &str| word.parse::<u32>().ok();
+    match (month(&words[0]), month(&words[1])) {
+        (Some(m), None) => date(number(&words[2])? as i32, m, number(&words[1])?),
+        (None, Some(m)) => date(number(&words[2])? as i32, m, number(&words[0])?),
+        _ => None,
+    }
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// `text` as a quoted JSON string, with quotes, backslashes and control
+/// characters escaped
+pub fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn json_label(label: Option<&str>) -> String {
+    label.map_or("null".to_string(), json_string)
+}
+
+fn json_bbox(bbox: &[f32; 4]) -> String {
+    format!("[{},{},{},{}]", bbox[0], bbox[1], bbox[2], bbox[3])
+}
+
+/// The `"type"` and `"value"` members for a cell value
+fn json_value(value: &CellValue) -> String {
+    match value {
+        CellValue::Empty => r#""type":"empty","value":null"#.to_string(),
+        CellValue::Number(n) => format!(r#""type":"number","value":{}"#, n),
+        CellValue::Percent(n) => format!(r#""type":"percent","value":{}"#, n),
+        CellValue::Currency { amount, currency } => format!(
+            r#""type":"currency","value":{},"currency":{}"#,
+            amount,
+            json_string(currency)
+        ),
+        CellValue::Date { year, month, day } => format!(
+            r#""type":"date","value":"{:04}-{:02}-{:02}""#,
+            year, month, day
+        ),
+        CellValue::Text(text) => format!(r#""type":"text","value":{}"#, json_string(text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cell_value() {
+        assert_eq!(parse_cell_value(""), CellValue::Empty);
+        assert_eq!(parse_cell_value("1,234.50"), CellValue::Number(1234.5));
+        assert_eq!(parse_cell_value("(1,234)"), CellValue::Number(-1234.0));
+        assert_eq!(parse_cell_value("−12"), CellValue::Number(-12.0));
+        assert_eq!(parse_cell_value("12.5%"), CellValue::Percent(12.5));
+        assert_eq!(parse_cell_value("(3.2)%"), CellValue::Percent(-3.2));
+        assert_eq!(
+            parse_cell_value("$(1,200)"),
+            CellValue::Currency {
+                amount: -1200.0,
+                currency: "$".into()
+            }
+        );
+        assert_eq!(
+            parse_cell_value("1.500 EUR"),
+            CellValue::Currency {
+                amount: 1.5,
+                currency: "EUR".into()
+            }
+        );
+        assert_eq!(
+            parse_cell_value("-€40"),
+            CellValue::Currency {
+                amount: -40.0,
+                currency: "€".into()
+            }
+        );
+        let date = |year, month, day| CellValue::Date { year, month, day };
+        assert_eq!(parse_cell_value("2023-12-31"), date(2023, 12, 31));
+        assert_eq!(parse_cell_value("12/31/2023"), date(2023, 12, 31));
+        assert_eq!(parse_cell_value("31/12/23"), date(2023, 12, 31));
+        assert_eq!(parse_cell_value("31.12.2023"), date(2023, 12, 31));
+        assert_eq!(parse_cell_value("December 31, 2023"), date(2023, 12, 31));
+        assert_eq!(parse_cell_value("31 Dec. 2023"), date(2023, 12, 31));
+        // Misplaced separators and free text stay text
+        assert_eq!(parse_cell_value("12,34"), CellValue::Text("12,34".into()));
+        assert_eq!(
+            parse_cell_value("Net income"),
+            CellValue::Text("Net income".into())
+        );
+        let huge = "9".repeat(400);
+        assert_eq!(parse_cell_value(&huge), CellValue::Text(huge.clone()));
+    }
+
+    #[test]
+    fn test_csv_and_json() {
+        let table = ExtractedTable {
+            page: 2,
+            page_label: Some("ii".into()),
+            pages: vec![2, 3],
+            bbox: [72.0, 500.0, 372.0, 540.0],
+            header_rows: 1,
+            rows: 2,
+            cols: 2,
+            row_levels: vec![],
+            row_kinds: vec![RowKind::Header, RowKind::Data],
+            cells: vec![
+                TableCell {
+                    row: 0,
+                    col: 0,
+                    row_span: 1,
+                    col_span: 2,
+                    page: 2,
+                    page_label: Some("ii".into()),
+                    text: "Revenue, \"adjusted\"".into(),
+                    bbox: Some([72.0, 520.0, 372.0, 540.0]),
+                    value: CellValue::Text("Revenue, \"adjusted\"".into()),
+                },
+                TableCell {
+                    row: 1,
+                    col: 0,
+                    row_span: 1,
+                    col_span: 1,
+                    page: 3,
+                    page_label: None,
+                    text: "2023".into(),
+                    bbox: None,
+                    value: CellValue::Number(2023.0),
+                },
+                TableCell {
+                    row: 1,
+                    col: 1,
+                    row_span: 1,
+                    col_span: 1,
+                    page: 3,
+                    page_label: None,
+                    text: "5%".into(),
+                    bbox: None,
+                    value: CellValue::Percent(5.0),
+                },
+            ],
+        };
+
+        assert_eq!(
+            table.to_csv(),
+            "\"Revenue, \"\"adjusted\"\"\",\r\n2023,5%\r\n"
+        );
+        let json = tables_to_json(std::slice::from_ref(&table));
+        assert!(json.starts_with(
+            r#"[{"page":2,"page_label":"ii","pages":[2,3],"bbox":[72,500,372,540],"header_rows":1,"rows":2,"cols":2,"row_levels":[],"row_kinds":["header","data"],"cells":["#
+        ));
+        assert!(json.contains(
+            r#"{"row":0,"col":0,"row_span":1,"col_span":2,"page":2,"page_label":"ii","text":"Revenue, \"adjusted\"","bbox":[72,520,372,540],"type":"text","#
+        ));
+        assert!(json.contains(
+            r#""page":3,"page_label":null,"text":"2023","bbox":null,"type":"number","value":2023}"#
+        ));
+        assert!(json.contains(r#""type":"percent","value":5}]}]"#));
+        assert_eq!(json_string("a\tb\nc\u{1}"), r#""a\tb\nc\u0001""#);
+    }
+}
@@ -1,111 +1,1075 @@
 //! Table detection and formatting
 //!
 //! Detects tabular data in PDF text items and converts to markdown tables.
+//! Tables drawn with ruling lines are read from their grid (lattice mode);
+//! others are found from text alignment and font size (stream mode).
 
-use crate::extractor::TextItem;
+use crate::extractor::{ItemType, TextItem, TextLine};
+use crate::graphics::GraphicItem;
+use std::collections::HashMap;
 
-/// Detection mode controls thresholds for table validation
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum TableDetectionMode {
-    /// Existing behavior: items with font size smaller than body text
+/// Maximum thickness of a filled rectangle drawn as a rule, in points
+const RULE_MAX_THICKNESS: f32 = 3.0;
+
+/// Distance within which rule coordinates are the same grid line, in points
+const GRID_TOLERANCE: f32 = 2.0;
+
+/// Rules on a page above which it is taken for a chart or drawing, and
+/// lattice detection is skipped
+const MAX_RULES: usize = 20_000;
+
+/// Maximum number of header rows recognized above the table body
+const MAX_HEADER_ROWS: usize = 3;
+
+/// Smallest indentation step between hierarchy levels of row labels, in points
+const INDENT_STEP: f32 = 3.0;
+
+/// Maximum number of hierarchy levels recognized in row labels
+const MAX_INDENT_LEVELS: usize = 4;
+
+/// Stream detection pass: which text it considers and how strictly it
+/// validates the result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableDetectionMode {
+    /// Text smaller than the body font, with lenient structural criteria
     SmallFont,
-    /// New: body-font items with stricter structural criteria
+    /// Body-sized text, with stricter structural criteria
     BodyFont,
 }
 
+/// A caller-provided table location, e.g. from an external layout model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableRegion {
+    /// Page number (1-indexed)
+    pub page: u32,
+    /// Bounds as `[x_min, y_min, x_max, y_max]`
+    pub bbox: [f32; 4],
+}
+
+/// Tuning for table detection
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableDetectionConfig {
+    /// Detect tables; when false only `regions` become tables
+    pub enabled: bool,
+    /// Read tables from drawn ruling lines (lattice mode)
+    pub ruled: bool,
+    /// Stream passes to run, in order; text claimed by one pass is not seen
+    /// by the next
+    pub modes: Vec<TableDetectionMode>,
+    /// Largest font size, relative to the body font, of small-font table text
+    pub small_font_ratio: f32,
+    /// Font size range, relative to the body font, of body-font table text
+    pub body_font_band: (f32, f32),
+    /// Smallest font size of table text, in points
+    pub min_font_size: f32,
+    /// Fewest text items a small-font table region may hold
+    pub small_font_min_items: usize,
+    /// Fewest text items a body-font table region may hold
+    pub body_font_min_items: usize,
+    /// Regions read as tables whatever the heuristics say; the text inside
+    /// is split into rows and columns by position
+    pub regions: Vec<TableRegion>,
+}
+
+impl Default for TableDetectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ruled: true,
+            modes: vec![TableDetectionMode::SmallFont, TableDetectionMode::BodyFont],
+            small_font_ratio: 0.90,
+            body_font_band: (0.85, 1.05),
+            min_font_size: 6.0,
+            small_font_min_items: 6,
+            body_font_min_items: 9,
+            regions: Vec::new(),
+        }
+    }
+}
+
+impl TableDetectionConfig {
+    /// No table detection; `regions` can still be added
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Default::default()
+        }
+    }
+
+    /// Whether detection reads vector graphics: ruling lines for lattice
+    /// tables and regions, rules setting off totals for stream tables
+    pub fn needs_graphics(&self) -> bool {
+        !self.regions.is_empty() || (self.enabled && (self.ruled || !self.modes.is_empty()))
+    }
+}
+
 /// A detected table
 #[derive(Debug, Clone)]
 pub struct Table {
     /// Column boundaries (x positions)
     pub columns: Vec<f32>,
-    /// Row boundaries (y positions, descending order)
+    /// Row boundaries (y positions, descending order on each page)
     pub rows: Vec<f32>,
     /// Cell contents indexed by (row, col)
     pub cells: Vec<Vec<String>>,
-    /// Items that belong to this table
+    /// Items that belong to this table; for a table stitched across pages,
+    /// those on its first page
     pub item_indices: Vec<usize>,
+    /// Merged cells covering more than one grid position; the text is held by
+    /// the top-left cell and the other covered cells are empty
+    pub spans: Vec<CellSpan>,
+    /// Number of leading rows forming the header (at least 1)
+    pub header_rows: usize,
+    /// Bounds of each cell as `[x_min, y_min, x_max, y_max]`: the ruled cell
+    /// for lattice tables, the cell's text for stream tables. `None` for empty
+    /// stream cells and for positions covered by a merged cell.
+    pub cell_bounds: Vec<Vec<Option<[f32; 4]>>>,
+    /// Indentation level of each row's label in the first column, 0 being the
+    /// outermost; empty when the labels are not indented
+    pub row_levels: Vec<usize>,
+    /// What each row holds
+    pub row_kinds: Vec<RowKind>,
+    /// Page each row is on (1-indexed)
+    pub row_pages: Vec<u32>,
+}
+
+/// What a table row holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowKind {
+    /// Part of the header
+    Header,
+    /// An ordinary row
+    Data,
+    /// Amounts summing up rows above: set off by a rule above or set in bold
+    Subtotal,
+    /// A subtotal at the outermost indentation level or underlined twice; in
+    /// tables without indentation, also the last subtotal
+    Total,
+}
+
+/// A merged cell: the grid positions `row..row + row_span`, `col..col + col_span`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellSpan {
+    pub row: usize,
+    pub col: usize,
+    pub row_span: usize,
+    pub col_span: usize,
+}
+
+impl CellSpan {
+    fn contains(&self, row: usize, col: usize) -> bool {
+        (self.row..self.row + self.row_span).contains(&row)
+            && (self.col..self.col + self.col_span).contains(&col)
+    }
+}
+
+impl Table {
+    /// The merged cell covering a grid position, if any
+    pub fn span_at(&self, row: usize, col: usize) -> Option<&CellSpan> {
+        self.spans.iter().find(|span| span.contains(row, col))
+    }
+
+    /// Bounds of the table on its first page as `[x_min, y_min, x_max, y_max]`
+    pub fn bbox(&self) -> Option<[f32; 4]> {
+        let first_page = self.row_pages.first();
+        self.cell_bounds
+            .iter()
+            .enumerate()
+            .filter(|(row, _)| self.row_pages.get(*row) == first_page)
+            .flat_map(|(_, bounds)| bounds.iter().flatten())
+            .copied()
+            .reduce(union_bounds)
+    }
+
+    /// Pages the table spans, in order
+    pub fn pages(&self) -> Vec<u32> {
+        let mut pages = self.row_pages.clone();
+        pages.dedup();
+        pages
+    }
+}
+
+fn union_bounds(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    [
+        a[0].min(b[0]),
+        a[1].min(b[1]),
+        a[2].max(b[2]),
+        a[3].max(b[3]),
+    ]
+}
+
+/// Bounds of a group of text items
+fn item_bounds(items: &[&TextItem]) -> Option<[f32; 4]> {
+    items
+        .iter()
+        .map(|i| [i.x, i.y, i.x + i.width, i.y + i.height])
+        .reduce(union_bounds)
 }
 
 /// Detect tables in a set of text items from a single page
 pub fn detect_tables(items: &[TextItem], base_font_size: f32) -> Vec<Table> {
-    if items.len() < 6 {
-        return vec![];
+    let mut tables = detect_stream_tables(items, base_font_size, &TableDetectionConfig::default());
+    for table in &mut tables {
+        annotate_table(table, items, &[], false);
     }
+    tables
+}
 
+/// Detect tables from text alignment and font size alone, running the
+/// configured stream passes
+fn detect_stream_tables(
+    items: &[TextItem],
+    base_font_size: f32,
+    config: &TableDetectionConfig,
+) -> Vec<Table> {
     let mut tables = Vec::new();
     let mut claimed_indices = std::collections::HashSet::new();
 
-    // === Pass 1: Small-font tables (existing behavior) ===
-    let table_font_threshold = base_font_size * 0.90;
-
-    let table_candidates: Vec<(usize, &TextItem)> = items
-        .iter()
-        .enumerate()
-        .filter(|(_, item)| item.font_size <= table_font_threshold && item.font_size >= 6.0)
-        .collect();
-
-    if table_candidates.len() >= 6 {
-        let regions = find_table_regions(&table_candidates);
+    for &mode in &config.modes {
+        let (font_low, font_high, min_items) = match mode {
+            TableDetectionMode::SmallFont => (
+                0.0,
+                base_font_size * config.small_font_ratio,
+                config.small_font_min_items,
+            ),
+            TableDetectionMode::BodyFont => (
+                base_font_size * config.body_font_band.0,
+                base_font_size * config.body_font_band.1,
+                config.body_font_min_items,
+            ),
+        };
+        let candidates: Vec<(usize, &TextItem)> = items
+            .iter()
+            .enumerate()
+            .filter(|(idx, item)| {
+                !claimed_indices.contains(idx)
+                    && item.font_size >= font_low
+                    && item.font_size <= font_high
+                    && item.font_size >= config.min_font_size
+            })
+            .collect();
+        if candidates.len() < min_items {
+            continue;
+        }
 
+        // Small-font tables stand out by font alone; body-font ones need
+        // consistent columns across rows to tell them from paragraphs
+        let regions = match mode {
+            TableDetectionMode::SmallFont => find_table_regions(&candidates),
+            TableDetectionMode::BodyFont => find_table_regions_strict(&candidates),
+        };
         for (y_min, y_max) in regions {
-            let region_items: Vec<(usize, &TextItem)> = table_candidates
+            let region_items: Vec<(usize, &TextItem)> = candidates
                 .iter()
                 .filter(|(_, item)| item.y >= y_min && item.y <= y_max)
                 .cloned()
                 .collect();
 
-            if region_items.len() < 6 {
+            if region_items.len() < min_items {
                 continue;
             }
 
-            if let Some(table) =
-                detect_table_in_region(&region_items, TableDetectionMode::SmallFont)
-            {
-                for &idx in &table.item_indices {
-                    claimed_indices.insert(idx);
-                }
+            if let Some(table) = detect_table_in_region(&region_items, mode) {
+                claimed_indices.extend(table.item_indices.iter().copied());
                 tables.push(table);
             }
         }
     }
 
-    // === Pass 2: Body-font tables (stricter criteria) ===
-    let body_font_low = base_font_size * 0.85;
-    let body_font_high = base_font_size * 1.05;
+    tables
+}
+
+/// Detect tables on a single page using both the drawn ruling lines and the
+/// text layout. Ruled (lattice) tables take precedence; the stream heuristics
+/// of [`detect_tables`] run on the text left over.
+pub fn detect_tables_with_graphics(
+    items: &[TextItem],
+    graphics: &[GraphicItem],
+    base_font_size: f32,
+) -> Vec<Table> {
+    detect_tables_with_config(
+        items,
+        graphics,
+        base_font_size,
+        &TableDetectionConfig::default(),
+    )
+}
+
+/// Detect tables on a single page as configured. Caller-provided regions on
+/// the page of `items` come first, then ruled tables, then the stream passes,
+/// each on the text the previous steps left over.
+pub fn detect_tables_with_config(
+    items: &[TextItem],
+    graphics: &[GraphicItem],
+    base_font_size: f32,
+    config: &TableDetectionConfig,
+) -> Vec<Table> {
+    let mut tables = Vec::new();
+    let mut claimed = std::collections::HashSet::new();
+
+    let page = items.first().map(|item| item.page);
+    for region in config.regions.iter().filter(|r| Some(r.page) == page) {
+        if let Some(table) = region_table(items, graphics, region, &claimed) {
+            claimed.extend(table.item_indices.iter().copied());
+            tables.push(table);
+        }
+    }
+    if !config.enabled {
+        return tables;
+    }
+
+    if config.ruled {
+        for table in detect_unclaimed(items, &claimed, |items| {
+            detect_lattice_tables(items, graphics)
+        }) {
+            claimed.extend(table.item_indices.iter().copied());
+            tables.push(table);
+        }
+    }
+    for mut table in detect_unclaimed(items, &claimed, |items| {
+        detect_stream_tables(items, base_font_size, config)
+    }) {
+        annotate_table(&mut table, items, graphics, false);
+        tables.push(table);
+    }
+    tables
+}
+
+/// Run `detect` on the items not in `claimed`, mapping the item indices of
+/// the tables it finds back to `items`
+fn detect_unclaimed(
+    items: &[TextItem],
+    claimed: &std::collections::HashSet<usize>,
+    detect: impl FnOnce(&[TextItem]) -> Vec<Table>,
+) -> Vec<Table> {
+    if claimed.is_empty() {
+        return detect(items);
+    }
+    let (remaining_indices, remaining): (Vec<usize>, Vec<TextItem>) = items
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| !claimed.contains(idx))
+        .map(|(idx, item)| (idx, item.clone()))
+        .unzip();
+    let mut tables = detect(&remaining);
+    for table in &mut tables {
+        for idx in &mut table.item_indices {
+            *idx = remaining_indices[*idx];
+        }
+    }
+    tables
+}
 
-    let body_candidates: Vec<(usize, &TextItem)> = items
+/// Read a caller-provided region as a table: from the rules drawn inside it
+/// if they form a grid, otherwise by splitting its text into rows and columns
+fn region_table(
+    items: &[TextItem],
+    graphics: &[GraphicItem],
+    region: &TableRegion,
+    claimed: &std::collections::HashSet<usize>,
+) -> Option<Table> {
+    let [x_min, y_min, x_max, y_max] = region.bbox;
+    let inside = |x: f32, y: f32| {
+        x >= x_min - GRID_TOLERANCE
+            && x <= x_max + GRID_TOLERANCE
+            && y >= y_min - GRID_TOLERANCE
+            && y <= y_max + GRID_TOLERANCE
+    };
+    let region_items: Vec<(usize, &TextItem)> = items
         .iter()
         .enumerate()
         .filter(|(idx, item)| {
-            !claimed_indices.contains(idx)
-                && item.font_size >= body_font_low
-                && item.font_size <= body_font_high
-                && item.font_size >= 6.0
+            !claimed.contains(idx)
+                && item.item_type == ItemType::Text
+                && inside(item.x + item.width / 2.0, item.y + item.height / 2.0)
         })
         .collect();
+    if region_items.is_empty() {
+        return None;
+    }
+
+    let region_graphics: Vec<GraphicItem> = graphics
+        .iter()
+        .filter(|g| inside(g.x1, g.y1) && inside(g.x2, g.y2))
+        .cloned()
+        .collect();
+    let (indices, texts): (Vec<usize>, Vec<TextItem>) = region_items
+        .iter()
+        .map(|&(idx, item)| (idx, item.clone()))
+        .unzip();
+    if let Some(mut table) = detect_lattice_tables(&texts, &region_graphics)
+        .into_iter()
+        .next()
+    {
+        for idx in &mut table.item_indices {
+            *idx = indices[*idx];
+        }
+        return Some(table);
+    }
 
-    if body_candidates.len() >= 9 {
-        let regions = find_table_regions_strict(&body_candidates);
+    let columns = match find_column_boundaries(&region_items, TableDetectionMode::SmallFont) {
+        columns if columns.is_empty() => vec![Column::left(x_min)],
+        columns => columns,
+    };
+    let rows = find_row_boundaries(&region_items);
+    let mut table = stream_grid(&region_items, &columns, rows, true).into_table(&columns);
+    annotate_table(&mut table, items, graphics, false);
+    Some(table)
+}
 
-        for (y_min, y_max) in regions {
-            let region_items: Vec<(usize, &TextItem)> = body_candidates
+/// Set the page, label indentation level and kind of each row of a table
+/// found among the `items` of a page. Every row of a `ruled` (lattice) table
+/// sits under a rule, so there only bold text marks totals.
+fn annotate_table(table: &mut Table, items: &[TextItem], graphics: &[GraphicItem], ruled: bool) {
+    let rows = table.cells.len();
+    let page = items.first().map_or(1, |item| item.page);
+    table.row_pages = vec![page; rows];
+    let body = table.header_rows.min(rows);
+    let inside = |b: &[f32; 4], x: f32, y: f32| x >= b[0] && x <= b[2] && y >= b[1] && y <= b[3];
+
+    // Items of each row, by the cell holding them
+    let mut row_items: Vec<Vec<&TextItem>> = vec![Vec::new(); rows];
+    let mut labels: Vec<Vec<&TextItem>> = vec![Vec::new(); rows];
+    for &idx in &table.item_indices {
+        let Some(item) = items.get(idx) else {
+            continue;
+        };
+        let (x, y) = (item.x + item.width / 2.0, item.y + item.height / 2.0);
+        let cell = table
+            .cell_bounds
+            .iter()
+            .enumerate()
+            .find_map(|(row, bounds)| {
+                let col = bounds
+                    .iter()
+                    .position(|b| b.as_ref().is_some_and(|b| inside(b, x, y)))?;
+                Some((row, col))
+            });
+        if let Some((row, col)) = cell {
+            row_items[row].push(item);
+            if col == 0 {
+                labels[row].push(item);
+            }
+        }
+    }
+
+    // Indentation levels of the body row labels
+    let label_x: Vec<Option<f32>> = labels
+        .iter()
+        .map(|items| items.iter().map(|i| i.x).reduce(f32::min))
+        .collect();
+    let mut xs: Vec<f32> = label_x[body..].iter().flatten().copied().collect();
+    xs.sort_by(f32::total_cmp);
+    let mut level_starts: Vec<f32> = Vec::new();
+    let mut prev = f32::NEG_INFINITY;
+    for x in xs {
+        if x - prev > INDENT_STEP {
+            level_starts.push(x);
+        }
+        prev = x;
+    }
+    table.row_levels = if (2..=MAX_INDENT_LEVELS).contains(&level_starts.len()) {
+        label_x
+            .iter()
+            .map(|x| {
+                x.map_or(0, |x| {
+                    level_starts.iter().filter(|&&s| s <= x).count().max(1) - 1
+                })
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // Vertical extent of each row: (bottom, top)
+    let extents: Vec<Option<(f32, f32)>> = table
+        .cell_bounds
+        .iter()
+        .map(|bounds| {
+            bounds
                 .iter()
-                .filter(|(_, item)| item.y >= y_min && item.y <= y_max)
-                .cloned()
-                .collect();
+                .flatten()
+                .copied()
+                .reduce(union_bounds)
+                .map(|b| (b[1], b[3]))
+        })
+        .collect();
+    let amount_cells = |row: usize| -> Vec<[f32; 4]> {
+        (1..table.cells[row].len())
+            .filter(|&col| looks_like_amount(&table.cells[row][col]))
+            .filter_map(|col| table.cell_bounds[row][col])
+            .collect()
+    };
+    let rules: Vec<&GraphicItem> = if ruled {
+        Vec::new()
+    } else {
+        graphics
+            .iter()
+            .filter(|g| g.is_horizontal_rule(RULE_MAX_THICKNESS))
+            .collect()
+    };
+    // Rules between `low` and `high` under some of the cells
+    let rules_between = |low: f32, high: f32, cells: &[[f32; 4]]| -> Vec<f32> {
+        rules
+            .iter()
+            .filter(|g| {
+                let y = (g.y1 + g.y2) / 2.0;
+                let (x1, x2) = (g.x1.min(g.x2), g.x1.max(g.x2));
+                y > low && y <= high && cells.iter().any(|c| x1 < c[2] && x2 > c[0])
+            })
+            .map(|g| (g.y1 + g.y2) / 2.0)
+            .collect()
+    };
+
+    let is_bold = |items: &[&TextItem]| !items.is_empty() && items.iter().all(|i| i.is_bold);
+    let bold: Vec<bool> = (0..rows)
+        .map(|row| {
+            if labels[row].is_empty() {
+                is_bold(&row_items[row])
+            } else {
+                is_bold(&labels[row])
+            }
+        })
+        .collect();
+    let amount_rows: Vec<usize> = (body..rows)
+        .filter(|&row| !amount_cells(row).is_empty())
+        .collect();
+    // Bold marks totals only when other rows are not bold
+    let bold_marks = amount_rows.iter().any(|&row| !bold[row]);
 
-            if region_items.len() < 9 {
+    let mut kinds: Vec<RowKind> = (0..rows)
+        .map(|row| {
+            if row < body {
+                RowKind::Header
+            } else {
+                RowKind::Data
+            }
+        })
+        .collect();
+    let mut last_flagged = None;
+    for &row in &amount_rows {
+        let cells = amount_cells(row);
+        let Some((bottom, top)) = extents[row] else {
+            continue;
+        };
+        let middle = (bottom + top) / 2.0;
+        // A rule under the row above, except the one under the header
+        let ruled_above = row > body
+            && extents[row - 1]
+                .is_some_and(|(above, _)| !rules_between(middle, above, &cells).is_empty());
+        let next_middle = extents
+            .get(row + 1)
+            .copied()
+            .flatten()
+            .map_or(bottom - (top - bottom) * 1.5, |(b, t)| (b + t) / 2.0);
+        let mut below = rules_between(next_middle, middle, &cells);
+        below.sort_by(f32::total_cmp);
+        let double_below = below.windows(2).any(|w| w[1] - w[0] <= 4.0 && w[1] > w[0]);
+
+        if !(ruled_above || double_below || bold_marks && bold[row]) {
+            continue;
+        }
+        let outermost = table.row_levels.get(row) == Some(&0);
+        kinds[row] = if double_below || outermost {
+            RowKind::Total
+        } else {
+            RowKind::Subtotal
+        };
+        last_flagged = Some(row);
+    }
+    if table.row_levels.is_empty() {
+        if let Some(row) = last_flagged {
+            kinds[row] = RowKind::Total;
+        }
+    }
+    table.row_kinds = kinds;
+}
+
+/// Stitch tables continuing across page breaks into single tables.
+/// `tables` are in document order; a table is continued by the next one when
+/// that is the first table on the following page, [`is_continuation`] holds,
+/// and none of `lines` lies below the table or above its continuation.
+/// `lines` is the text outside the tables, without running headers, footers
+/// and page numbers. Repeated header rows are dropped and `row_pages` records
+/// the page of every row.
+pub fn stitch_tables(tables: Vec<Table>, lines: &[TextLine]) -> Vec<Table> {
+    let mut stitched: Vec<Table> = Vec::new();
+    for table in tables {
+        if let Some(last) = stitched.last_mut() {
+            let (end, start) = match (last.row_pages.last(), table.row_pages.first()) {
+                (Some(&end), Some(&start)) => (end, start),
+                _ => (0, 0),
+            };
+            let consecutive = end > 0 && start == end + 1;
+            let (bottom, _) = page_extent(last, end);
+            let (_, top) = page_extent(&table, start);
+            let separated = lines.iter().any(|line| {
+                (line.page == end && line.y < bottom) || (line.page == start && line.y > top)
+            });
+            if consecutive && !separated && is_continuation(last, &table) {
+                append_continuation(last, table);
                 continue;
             }
+        }
+        stitched.push(table);
+    }
+    stitched
+}
 
-            if let Some(table) = detect_table_in_region(&region_items, TableDetectionMode::BodyFont)
+/// Lowest and highest y of a table's rows and cells on `page`
+fn page_extent(table: &Table, page: u32) -> (f32, f32) {
+    let bounds = table
+        .cell_bounds
+        .iter()
+        .zip(&table.row_pages)
+        .filter(|(_, &p)| p == page)
+        .flat_map(|(bounds, _)| bounds.iter().flatten())
+        .flat_map(|b| [b[1], b[3]]);
+    let rows = table
+        .rows
+        .iter()
+        .zip(&table.row_pages)
+        .filter(|(_, &p)| p == page)
+        .map(|(&y, _)| y);
+    bounds
+        .chain(rows)
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), y| {
+            (low.min(y), high.max(y))
+        })
+}
+
+/// Whether `next`, found at the top of a page, continues `table` from the
+/// bottom of the page before: its columns are at the same positions, and it
+/// either starts by repeating the table's header rows or its first row is
+/// shaped like the table's last row (amounts and text in the same columns)
+pub fn is_continuation(table: &Table, next: &Table) -> bool {
+    if table.columns.len() != next.columns.len() || next.cells.is_empty() {
+        return false;
+    }
+    // Columns line up when each is nearer its counterpart than any neighbour
+    let tolerance = table
+        .columns
+        .windows(2)
+        .map(|w| (w[1] - w[0]).abs() / 2.0)
+        .reduce(f32::min)
+        .unwrap_or(10.0)
+        .min(50.0);
+    if table
+        .columns
+        .iter()
+        .zip(&next.columns)
+        .any(|(a, b)| (a - b).abs() >= tolerance)
+    {
+        return false;
+    }
+
+    if repeated_header_rows(table, next) > 0 {
+        return true;
+    }
+    let Some(last) = table.cells.get(table.header_rows..).and_then(<[_]>::last) else {
+        return false;
+    };
+    let first = &next.cells[0];
+    let filled = |row: &[String]| row.iter().filter(|c| !c.trim().is_empty()).count();
+    filled(first) >= 2
+        && last.iter().zip(first).all(|(a, b)| {
+            a.trim().is_empty()
+                || b.trim().is_empty()
+                || looks_like_amount(a) == looks_like_amount(b)
+        })
+}
+
+/// Number of header rows of `table` that `next` repeats at its top
+fn repeated_header_rows(table: &Table, next: &Table) -> usize {
+    let normalize = |row: &[String]| -> Vec<String> {
+        row.iter()
+            .map(|c| c.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect()
+    };
+    let header = &table.cells[..table.header_rows.min(table.cells.len())];
+    let repeated = header.len() <= next.cells.len()
+        && header
+            .iter()
+            .zip(&next.cells)
+            .all(|(a, b)| normalize(a) == normalize(b));
+    if repeated && header.iter().any(|row| row.iter().any(|c| !c.is_empty())) {
+        header.len()
+    } else {
+        0
+    }
+}
+
+/// Append the rows of `next`, a continuation of `table` on the following
+/// page (see [`is_continuation`]), leaving out header rows it repeats
+pub fn append_continuation(table: &mut Table, next: Table) {
+    let skip = repeated_header_rows(table, &next);
+    let offset = table.cells.len();
+
+    let levels = |table: &Table, rows: usize| {
+        if table.row_levels.is_empty() {
+            vec![0; rows]
+        } else {
+            table.row_levels.clone()
+        }
+    };
+    if !table.row_levels.is_empty() || !next.row_levels.is_empty() {
+        let mut row_levels = levels(table, table.cells.len());
+        row_levels.extend(levels(&next, next.cells.len()).into_iter().skip(skip));
+        table.row_levels = row_levels;
+    }
+    table.spans.extend(
+        next.spans
+            .iter()
+            .filter(|span| span.row >= skip)
+            .map(|span| CellSpan {
+                row: span.row - skip + offset,
+                ..*span
+            }),
+    );
+    // Header rows not repeated from the table are data rows
+    table
+        .row_kinds
+        .extend(next.row_kinds.iter().skip(skip).map(|&kind| match kind {
+            RowKind::Header => RowKind::Data,
+            kind => kind,
+        }));
+    table.cells.extend(next.cells.into_iter().skip(skip));
+    table
+        .cell_bounds
+        .extend(next.cell_bounds.into_iter().skip(skip));
+    table.rows.extend(next.rows.into_iter().skip(skip));
+    table
+        .row_pages
+        .extend(next.row_pages.into_iter().skip(skip));
+}
+
+/// Detect ruled tables: grids of intersecting horizontal and vertical rules.
+/// Text is assigned to the cell containing it; where a rule is missing
+/// between two grid cells they form one merged cell, whose text goes to its
+/// top-left grid position.
+pub fn detect_lattice_tables(items: &[TextItem], graphics: &[GraphicItem]) -> Vec<Table> {
+    let rules = collect_rules(graphics);
+    if rules.len() > MAX_RULES {
+        return Vec::new();
+    }
+    let mut tables = Vec::new();
+    let mut claimed = std::collections::HashSet::new();
+    for group in group_rules(&rules) {
+        if let Some(table) = lattice_table(items, &group, &claimed) {
+            claimed.extend(table.item_indices.iter().copied());
+            tables.push(table);
+        }
+    }
+    tables.sort_by(|a, b| b.rows[0].total_cmp(&a.rows[0]));
+    for table in &mut tables {
+        annotate_table(table, items, graphics, true);
+    }
+    tables
+}
+
+/// A horizontal or vertical rule: `pos` is its y (horizontal) or x (vertical)
+/// coordinate, `start`..`end` its extent along the other axis
+#[derive(Debug, Clone, Copy)]
+struct Rule {
+    horizontal: bool,
+    pos: f32,
+    start: f32,
+    end: f32,
+}
+
+impl Rule {
+    fn crosses(&self, other: &Rule) -> bool {
+        if self.horizontal == other.horizontal {
+            // Collinear pieces of one line, drawn cell by cell
+            return (self.pos - other.pos).abs() <= GRID_TOLERANCE
+                && self.start <= other.end + GRID_TOLERANCE
+                && other.start <= self.end + GRID_TOLERANCE;
+        }
+        other.pos >= self.start - GRID_TOLERANCE
+            && other.pos <= self.end + GRID_TOLERANCE
+            && self.pos >= other.start - GRID_TOLERANCE
+            && self.pos <= other.end + GRID_TOLERANCE
+    }
+
+    /// Whether the rule runs along grid line `pos` over the midpoint of `from`..`to`
+    fn covers(&self, pos: f32, from: f32, to: f32) -> bool {
+        let mid = (from + to) / 2.0;
+        (self.pos - pos).abs() <= GRID_TOLERANCE && self.start <= mid && self.end >= mid
+    }
+}
+
+/// Rules drawn on the page: lines, thin filled bars and the sides of stroked boxes
+fn collect_rules(graphics: &[GraphicItem]) -> Vec<Rule> {
+    use crate::graphics::GraphicKind;
+
+    let horizontal = |y: f32, x1: f32, x2: f32| Rule {
+        horizontal: true,
+        pos: y,
+        start: x1.min(x2),
+        end: x1.max(x2),
+    };
+    let vertical = |x: f32, y1: f32, y2: f32| Rule {
+        horizontal: false,
+        pos: x,
+        start: y1.min(y2),
+        end: y1.max(y2),
+    };
+
+    let mut rules = Vec::new();
+    for g in graphics {
+        if g.is_horizontal_rule(RULE_MAX_THICKNESS) {
+            rules.push(horizontal((g.y1 + g.y2) / 2.0, g.x1, g.x2));
+        } else if g.is_vertical_rule(RULE_MAX_THICKNESS) {
+            rules.push(vertical((g.x1 + g.x2) / 2.0, g.y1, g.y2));
+        } else if g.kind == GraphicKind::Rect && g.stroked {
+            rules.push(horizontal(g.y1, g.x1, g.x2));
+            rules.push(horizontal(g.y2, g.x1, g.x2));
+            rules.push(vertical(g.x1, g.y1, g.y2));
+            rules.push(vertical(g.x2, g.y1, g.y2));
+        }
+    }
+    rules
+}
+
+/// Split rules into connected groups of touching rules, one per candidate grid
+fn group_rules(rules: &[Rule]) -> Vec<Vec<Rule>> {
+    let mut parent: Vec<usize> = (0..rules.len()).collect();
+    fn find(parent: &mut [usize], i: usize) -> usize {
+        let mut root = i;
+        while parent[root] != root {
+            root = parent[root];
+        }
+        parent[i] = root;
+        root
+    }
+    let mut join = |i: usize, j: usize| {
+        if rules[i].crosses(&rules[j]) {
+            let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+            parent[a] = b;
+        }
+    };
+
+    // Sorted by position, so that only rules that can touch are compared
+    let by_position = |horizontal: bool| {
+        let mut ids: Vec<usize> = (0..rules.len())
+            .filter(|&i| rules[i].horizontal == horizontal)
+            .collect();
+        ids.sort_by(|&a, &b| rules[a].pos.total_cmp(&rules[b].pos));
+        ids
+    };
+    let (horizontal, vertical) = (by_position(true), by_position(false));
+    // Collinear pieces lie within the tolerance of each other
+    for ids in [&horizontal, &vertical] {
+        for (k, &i) in ids.iter().enumerate() {
+            for &j in ids[k + 1..]
+                .iter()
+                .take_while(|&&j| rules[j].pos - rules[i].pos <= GRID_TOLERANCE)
             {
-                tables.push(table);
+                join(i, j);
+            }
+        }
+    }
+    // A horizontal rule can only cross the vertical rules within its extent
+    for &h in &horizontal {
+        let (start, end) = (rules[h].start, rules[h].end);
+        let first = vertical.partition_point(|&v| rules[v].pos < start - GRID_TOLERANCE);
+        for &v in vertical[first..]
+            .iter()
+            .take_while(|&&v| rules[v].pos <= end + GRID_TOLERANCE)
+        {
+            join(h, v);
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<Rule>> = HashMap::new();
+    for (i, rule) in rules.iter().enumerate() {
+        let root = find(&mut parent, i);
+        groups.entry(root).or_default().push(*rule);
+    }
+    let mut groups: Vec<Vec<Rule>> = groups.into_values().collect();
+    groups.sort_by(|a, b| {
+        let top = |g: &Vec<Rule>| g.iter().map(|r| r.pos.max(r.end)).fold(f32::MIN, f32::max);
+        top(b).total_cmp(&top(a))
+    });
+    groups
+}
+
+/// Distinct grid line positions among rules, ascending
+fn grid_lines(rules: &[Rule], horizontal: bool) -> Vec<f32> {
+    let mut positions: Vec<f32> = rules
+        .iter()
+        .filter(|r| r.horizontal == horizontal)
+        .map(|r| r.pos)
+        .collect();
+    positions.sort_by(f32::total_cmp);
+    let mut lines: Vec<f32> = Vec::new();
+    for pos in positions {
+        match lines.last() {
+            Some(&last) if pos - last <= GRID_TOLERANCE => {}
+            _ => lines.push(pos),
+        }
+    }
+    lines
+}
+
+/// Merged cells of a grid, as (top row, left column, bottom row, right column)
+/// ranges, in row-major order of their top-left grid position
+fn grid_regions(rules: &[Rule], xs: &[f32], ys_desc: &[f32]) -> Vec<(usize, usize, usize, usize)> {
+    let (rows, cols) = (ys_desc.len() - 1, xs.len() - 1);
+    // Grid cell each position merges into, propagated from the top-left
+    let mut owner: Vec<Vec<(usize, usize)>> = vec![vec![(0, 0); cols]; rows];
+    for r in 0..rows {
+        for c in 0..cols {
+            let left_open = c > 0
+                && !rules
+                    .iter()
+                    .any(|rule| !rule.horizontal && rule.covers(xs[c], ys_desc[r + 1], ys_desc[r]));
+            let top_open = r > 0
+                && !rules
+                    .iter()
+                    .any(|rule| rule.horizontal && rule.covers(ys_desc[r], xs[c], xs[c + 1]));
+            owner[r][c] = if left_open && (!top_open || owner[r][c - 1] == owner[r - 1][c]) {
+                owner[r][c - 1]
+            } else if top_open {
+                owner[r - 1][c]
+            } else {
+                (r, c)
+            };
+        }
+    }
+
+    let mut regions: Vec<(usize, usize, usize, usize)> = Vec::new();
+    for (r, row) in owner.iter().enumerate() {
+        for (c, &(top, left)) in row.iter().enumerate() {
+            match regions.iter_mut().find(|g| (g.0, g.1) == (top, left)) {
+                Some(region) => {
+                    region.2 = region.2.max(r);
+                    region.3 = region.3.max(c);
+                }
+                None => regions.push((top, left, r, c)),
             }
         }
     }
+    regions
+}
 
-    tables
+/// Build a table from one group of rules, if it forms a grid with text in it
+fn lattice_table(
+    items: &[TextItem],
+    rules: &[Rule],
+    claimed: &std::collections::HashSet<usize>,
+) -> Option<Table> {
+    let xs = grid_lines(rules, false);
+    let mut ys = grid_lines(rules, true);
+    ys.reverse();
+    if xs.len() < 3 || ys.len() < 3 {
+        return None;
+    }
+    let (rows, cols) = (ys.len() - 1, xs.len() - 1);
+    let regions = grid_regions(rules, &xs, &ys);
+    // Page frames split by a column divider or a header rule have too few cells
+    if regions.len() < 4 {
+        return None;
+    }
+
+    // Grid position of each merged cell's top-left corner, by grid cell
+    let mut anchor = vec![vec![(0, 0); cols]; rows];
+    for &(top, left, bottom, right) in &regions {
+        for row in anchor.iter_mut().take(bottom + 1).skip(top) {
+            for cell in row.iter_mut().take(right + 1).skip(left) {
+                *cell = (top, left);
+            }
+        }
+    }
+
+    let mut cell_items: Vec<Vec<Vec<&TextItem>>> = vec![vec![Vec::new(); cols]; rows];
+    let mut item_indices = Vec::new();
+    for (idx, item) in items.iter().enumerate() {
+        if item.item_type != ItemType::Text || claimed.contains(&idx) {
+            continue;
+        }
+        // Text belongs to the cell containing the middle of its x-height
+        let cx = item.x + item.width / 2.0;
+        let cy = item.y + item.font_size * 0.3;
+        let col = xs.windows(2).position(|w| cx >= w[0] && cx <= w[1]);
+        let row = ys.windows(2).position(|w| cy <= w[0] && cy >= w[1]);
+        if let (Some(row), Some(col)) = (row, col) {
+            let (row, col) = anchor[row][col];
+            cell_items[row][col].push(item);
+            item_indices.push(idx);
+        }
+    }
+    // A lone box or a frame around a paragraph is not a table
+    let filled = cell_items
+        .iter()
+        .flatten()
+        .filter(|c| !c.is_empty())
+        .count();
+    if filled < 2 {
+        return None;
+    }
+
+    let cells: Vec<Vec<String>> = cell_items
+        .iter_mut()
+        .map(|row| row.iter_mut().map(|items| join_cell_lines(items)).collect())
+        .collect();
+    let mut cell_bounds = vec![vec![None; cols]; rows];
+    for &(top, left, bottom, right) in &regions {
+        cell_bounds[top][left] = Some([xs[left], ys[bottom + 1], xs[right + 1], ys[top]]);
+    }
+    let spans: Vec<CellSpan> = regions
+        .iter()
+        .filter(|&&(top, left, bottom, right)| bottom > top || right > left)
+        .map(|&(top, left, bottom, right)| CellSpan {
+            row: top,
+            col: left,
+            row_span: bottom - top + 1,
+            col_span: right - left + 1,
+        })
+        .collect();
+    // Rows with cells spanning several columns over sub-headers are headers too
+    let grouping_rows = (0..rows.min(MAX_HEADER_ROWS) - 1)
+        .take_while(|&row| {
+            spans.iter().any(|span| {
+                span.row == row
+                    && span.col_span > 1
+                    && groups_sub_headers(&cells, row + span.row_span, span.col, span.col_span)
+            })
+        })
+        .count();
+    Some(Table {
+        columns: xs[..cols].to_vec(),
+        rows: ys[..rows].to_vec(),
+        cells,
+        item_indices,
+        spans,
+        header_rows: grouping_rows + 1,
+        cell_bounds,
+        row_levels: Vec::new(),
+        row_kinds: Vec::new(),
+        row_pages: Vec::new(),
+    })
+}
+
+/// Join the items of a lattice cell line by line, top to bottom, with `\n`
+fn join_cell_lines(items: &mut [&TextItem]) -> String {
+    items.sort_by(|a, b| b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x)));
+    let mut lines: Vec<Vec<&TextItem>> = Vec::new();
+    for item in items.iter() {
+        match lines.last_mut() {
+            Some(line) if (line[0].y - item.y).abs() < item.font_size * 0.5 => line.push(item),
+            _ => lines.push(vec![item]),
+        }
+    }
+    lines
+        .iter_mut()
+        .map(|line| {
+            line.sort_by(|a, b| a.x.total_cmp(&b.x));
+            join_cell_items(line)
+        })
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 /// Find Y-regions that likely contain tables
@@ -158,43 +1122,48 @@ fn find_table_regions_strict(items: &[(usize, &TextItem)]) -> Vec<(f32, f32)> {
     }
 
     // Step 1: Group items by Y position (8pt tolerance for same row)
-    let mut row_groups: Vec<(f32, Vec<f32>)> = Vec::new();
+    let mut row_groups: Vec<(f32, Vec<&TextItem>)> = Vec::new();
     for (_, item) in items {
         let mut found = false;
-        for (center, x_positions) in row_groups.iter_mut() {
+        for (center, row_items) in row_groups.iter_mut() {
             if (item.y - *center).abs() < 8.0 {
-                x_positions.push(item.x);
+                row_items.push(item);
                 found = true;
                 break;
             }
         }
         if !found {
-            row_groups.push((item.y, vec![item.x]));
+            row_groups.push((item.y, vec![item]));
         }
     }
 
     // Step 2: Filter to rows with 3+ distinct X-position clusters (20pt tolerance)
-    // Collect cluster start positions for cross-row alignment analysis
-    let mut qualifying_rows: Vec<(f32, Vec<f32>)> = Vec::new(); // (y, cluster_starts)
-    for (y, x_positions) in &row_groups {
-        let mut sorted_xs = x_positions.clone();
-        sorted_xs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-
-        if sorted_xs.is_empty() {
+    // Collect cluster start positions for cross-row alignment analysis, with
+    // the right edge of clusters ending in an amount, which may be right-aligned
+    type Cluster = (f32, Option<f32>);
+    let mut qualifying_rows: Vec<(f32, Vec<Cluster>)> = Vec::new(); // (y, clusters)
+    for (y, row_items) in &row_groups {
+        let mut sorted = row_items.clone();
+        sorted.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal));
+
+        if sorted.is_empty() {
             continue;
         }
 
-        let mut cluster_starts: Vec<f32> = vec![sorted_xs[0]];
-        let mut last_x = sorted_xs[0];
-        for &x in &sorted_xs[1..] {
-            if x - last_x > 20.0 {
-                cluster_starts.push(x);
-                last_x = x;
+        let amount_end =
+            |item: &TextItem| looks_like_amount(&item.text).then_some(item.x + item.width);
+        let mut clusters: Vec<Cluster> = vec![(sorted[0].x, amount_end(sorted[0]))];
+        for item in &sorted[1..] {
+            let last = clusters.last_mut().unwrap();
+            if item.x - last.0 > 20.0 {
+                clusters.push((item.x, amount_end(item)));
+            } else {
+                last.1 = amount_end(item);
             }
         }
 
-        if cluster_starts.len() >= 3 {
-            qualifying_rows.push((*y, cluster_starts));
+        if clusters.len() >= 3 {
+            qualifying_rows.push((*y, clusters));
         }
     }
 
@@ -202,15 +1171,31 @@ fn find_table_regions_strict(items: &[(usize, &TextItem)]) -> Vec<(f32, f32)> {
         return vec![];
     }
 
-    // Step 3: Find contiguous runs of qualifying rows (25pt max Y-gap)
+    // Step 3: Find contiguous runs of qualifying rows (25pt max Y-gap). Wrapped
+    // cell lines in between bridge a wider gap when they are themselves no
+    // further than that apart.
     qualifying_rows.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    let mut all_ys: Vec<f32> = row_groups.iter().map(|(y, _)| *y).collect();
+    all_ys.sort_by(f32::total_cmp);
+    let bridged = |from: f32, to: f32| {
+        let between: Vec<f32> = all_ys
+            .iter()
+            .copied()
+            .filter(|&y| y > from && y < to)
+            .collect();
+        between.len() <= 2
+            && std::iter::once(from)
+                .chain(between.iter().copied())
+                .zip(between.iter().copied().chain(std::iter::once(to)))
+                .all(|(a, b)| b - a <= 25.0)
+    };
 
-    let mut candidate_regions: Vec<Vec<&(f32, Vec<f32>)>> = Vec::new();
-    let mut current_region: Vec<&(f32, Vec<f32>)> = vec![&qualifying_rows[0]];
+    let mut candidate_regions: Vec<Vec<&(f32, Vec<Cluster>)>> = Vec::new();
+    let mut current_region: Vec<&(f32, Vec<Cluster>)> = vec![&qualifying_rows[0]];
 
     for row in qualifying_rows.iter().skip(1) {
         let prev_y = current_region.last().unwrap().0;
-        if row.0 - prev_y > 25.0 {
+        if row.0 - prev_y > 25.0 && !bridged(prev_y, row.0) {
             if current_region.len() >= 3 {
                 candidate_regions.push(current_region);
             }
@@ -232,6 +1217,10 @@ fn find_table_regions_strict(items: &[(usize, &TextItem)]) -> Vec<(f32, f32)> {
         let mut total_score = 0.0f32;
         let mut pair_count = 0u32;
         let tolerance = 10.0f32;
+        let aligned = |a: &Cluster, b: &Cluster| {
+            (a.0 - b.0).abs() < tolerance
+                || matches!((a.1, b.1), (Some(ea), Some(eb)) if (ea - eb).abs() < tolerance)
+        };
 
         for i in 0..num_rows {
             for j in (i + 1)..num_rows {
@@ -240,11 +1229,11 @@ fn find_table_regions_strict(items: &[(usize, &TextItem)]) -> Vec<(f32, f32)> {
 
                 let matches_a = centers_a
                     .iter()
-                    .filter(|&&a| centers_b.iter().any(|&b| (a - b).abs() < tolerance))
+                    .filter(|a| centers_b.iter().any(|b| aligned(a, b)))
                     .count();
                 let matches_b = centers_b
                     .iter()
-                    .filter(|&&b| centers_a.iter().any(|&a| (a - b).abs() < tolerance))
+                    .filter(|b| centers_a.iter().any(|a| aligned(a, b)))
                     .count();
 
                 let max_len = centers_a.len().max(centers_b.len());
@@ -302,54 +1291,12 @@ fn detect_table_in_region(items: &[(usize, &TextItem)], mode: TableDetectionMode
         return None;
     }
 
-    // Build the table grid - first collect items per cell, then join properly
-    let mut cell_items: Vec<Vec<Vec<&TextItem>>> =
-        vec![vec![Vec::new(); columns.len()]; rows.len()];
-    let mut item_indices = Vec::new();
-
-    for (idx, item) in items {
-        let col = find_column_index(&columns, item.x);
-        let row = find_row_index(&rows, item.y);
-
-        if let (Some(col), Some(row)) = (col, row) {
-            cell_items[row][col].push(item);
-            item_indices.push(*idx);
-        }
-    }
-
-    // Detect form header rows and exclude their items
-    // We need to do this BEFORE finalizing item_indices
-    let (first_table_row, excluded_items) = find_first_table_row(&cell_items, &rows, items);
-
-    // Remove excluded items from item_indices
-    let item_indices: Vec<usize> = item_indices
-        .into_iter()
-        .filter(|idx| !excluded_items.contains(idx))
-        .collect();
-
-    // If we excluded rows, adjust the cell_items and rows
-    let (rows, mut cell_items) = if first_table_row > 0 {
-        let new_rows = rows[first_table_row..].to_vec();
-        let new_cell_items = cell_items[first_table_row..].to_vec();
-        (new_rows, new_cell_items)
-    } else {
-        (rows, cell_items)
-    };
-
-    // Sort items within each cell by X position and join with subscript-aware spacing
-    let mut cells: Vec<Vec<String>> = Vec::with_capacity(rows.len());
-    for row_items in &mut cell_items {
-        let mut row_cells = Vec::with_capacity(columns.len());
-        for col_items in row_items.iter_mut() {
-            // Sort by X position
-            col_items.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal));
-
-            // Join items with subscript-aware spacing
-            let text = join_cell_items(col_items);
-            row_cells.push(text);
-        }
-        cells.push(row_cells);
-    }
+    let StreamGrid {
+        rows,
+        cells,
+        cell_bounds,
+        item_indices,
+    } = stream_grid(items, &columns, rows, false);
 
     // Validation 1: most rows should have content in first column
     let rows_with_first_col = cells.iter().filter(|row| !row[0].is_empty()).count();
@@ -405,24 +1352,358 @@ fn detect_table_in_region(items: &[(usize, &TextItem)], mode: TableDetectionMode
         return None;
     }
 
-    // Validation 7: Tables should have some numeric/data content
-    // (not just text labels)
-    if !has_table_like_content(&cells, mode) {
-        return None;
-    }
+    // Validation 7: Tables should have some numeric/data content
+    // (not just text labels)
+    if !has_table_like_content(&cells, mode) {
+        return None;
+    }
+
+    // Validation 8: Check for Table of Contents pattern
+    // TOCs have dots (leader lines) and page numbers, not real table data
+    if is_table_of_contents(&cells) {
+        return None;
+    }
+
+    let grid = StreamGrid {
+        rows,
+        cells,
+        cell_bounds,
+        item_indices,
+    };
+    Some(grid.into_table(&columns))
+}
+
+/// Text of a stream table laid out on its grid, before validation
+struct StreamGrid {
+    rows: Vec<f32>,
+    cells: Vec<Vec<String>>,
+    cell_bounds: Vec<Vec<Option<[f32; 4]>>>,
+    item_indices: Vec<usize>,
+}
+
+impl StreamGrid {
+    /// The table, with header spans found
+    fn into_table(self, columns: &[Column]) -> Table {
+        let StreamGrid {
+            rows,
+            mut cells,
+            mut cell_bounds,
+            item_indices,
+        } = self;
+        let (spans, header_rows) = stream_header_spans(&mut cells);
+        // Group labels moved to the left of their span take their bounds along
+        for span in &spans {
+            let row = &mut cell_bounds[span.row];
+            let bounds = row[span.col..span.col + span.col_span]
+                .iter_mut()
+                .find_map(Option::take);
+            row[span.col] = bounds;
+        }
+        Table {
+            columns: columns.iter().map(|col| col.x).collect(),
+            rows,
+            cells,
+            item_indices,
+            spans,
+            header_rows,
+            cell_bounds,
+            row_levels: Vec::new(),
+            row_kinds: Vec::new(),
+            row_pages: Vec::new(),
+        }
+    }
+}
+
+/// Place items in the cells of the column and row grid, joining the text of
+/// each cell and merging wrapped lines. A region `forced` to be a table keeps
+/// every item, in its nearest cell, and all its rows; otherwise items off the
+/// grid are dropped and form header rows above the table are cut off.
+fn stream_grid(
+    items: &[(usize, &TextItem)],
+    columns: &[Column],
+    rows: Vec<f32>,
+    forced: bool,
+) -> StreamGrid {
+    let nearest = |offsets: &mut dyn Iterator<Item = f32>| {
+        offsets
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(idx, _)| idx)
+    };
+
+    // Build the table grid - first collect items per cell, then join properly
+    let mut cell_items: Vec<Vec<Vec<&TextItem>>> =
+        vec![vec![Vec::new(); columns.len()]; rows.len()];
+    let mut item_indices = Vec::new();
+
+    for (idx, item) in items {
+        let (col, row) = if forced {
+            (
+                nearest(&mut columns.iter().map(|col| col.offset(item))),
+                nearest(&mut rows.iter().map(|y| (item.y - y).abs())),
+            )
+        } else {
+            (
+                find_column_index(columns, item),
+                find_row_index(&rows, item.y),
+            )
+        };
+
+        if let (Some(col), Some(row)) = (col, row) {
+            cell_items[row][col].push(item);
+            item_indices.push(*idx);
+        }
+    }
+
+    // Detect form header rows and exclude their items
+    // We need to do this BEFORE finalizing item_indices
+    let (first_table_row, excluded_items) = if forced {
+        (0, std::collections::HashSet::new())
+    } else {
+        find_first_table_row(&cell_items, &rows, items)
+    };
+
+    // Remove excluded items from item_indices
+    let item_indices: Vec<usize> = item_indices
+        .into_iter()
+        .filter(|idx| !excluded_items.contains(idx))
+        .collect();
+
+    // If we excluded rows, adjust the cell_items and rows
+    let (mut rows, mut cell_items) = if first_table_row > 0 {
+        let new_rows = rows[first_table_row..].to_vec();
+        let new_cell_items = cell_items[first_table_row..].to_vec();
+        (new_rows, new_cell_items)
+    } else {
+        (rows, cell_items)
+    };
+
+    // Sort items within each cell by X position and join with subscript-aware spacing
+    let mut cells: Vec<Vec<String>> = Vec::with_capacity(rows.len());
+    for row_items in &mut cell_items {
+        let mut row_cells = Vec::with_capacity(columns.len());
+        for col_items in row_items.iter_mut() {
+            // Sort by X position
+            col_items.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal));
+
+            // Join items with subscript-aware spacing
+            let text = join_cell_items(col_items);
+            row_cells.push(text);
+        }
+        cells.push(row_cells);
+    }
+    let mut cell_bounds: Vec<Vec<Option<[f32; 4]>>> = cell_items
+        .iter()
+        .map(|row| row.iter().map(|items| item_bounds(items)).collect())
+        .collect();
+    merge_wrapped_rows(
+        &mut cells,
+        &mut cell_bounds,
+        &mut rows,
+        median_font_size(items),
+    );
+
+    StreamGrid {
+        rows,
+        cells,
+        cell_bounds,
+        item_indices,
+    }
+}
+
+/// Whether row `below` holds at least two sub-header labels in the columns
+/// `col..col + col_span` of a group label above them
+fn groups_sub_headers(cells: &[Vec<String>], below: usize, col: usize, col_span: usize) -> bool {
+    let Some(row) = cells.get(below) else {
+        return false;
+    };
+    // Sub-headers are labels, not data; years ("2023 2022") are labels too
+    let is_year = |c: &str| {
+        c.len() == 4
+            && c.chars().all(|ch| ch.is_ascii_digit())
+            && (c.starts_with("19") || c.starts_with("20"))
+    };
+    if row
+        .iter()
+        .any(|c| looks_like_number(c.trim()) && !is_year(c.trim()))
+    {
+        return false;
+    }
+    row.iter()
+        .skip(col)
+        .take(col_span)
+        .filter(|c| !c.is_empty())
+        .count()
+        >= 2
+}
+
+/// Find multi-level headers in a stream table, where a group label ("2023")
+/// sits over several sub-headers ("Q1 Q2 Q3") in the row below. Each empty
+/// cell over a sub-header joins the nearest group label in its row, whose
+/// text moves to the left of the span. Returns the spans and header row count.
+fn stream_header_spans(cells: &mut [Vec<String>]) -> (Vec<CellSpan>, usize) {
+    let mut spans = Vec::new();
+    let mut header_rows = 1;
+    while header_rows < MAX_HEADER_ROWS && header_rows + 1 < cells.len() {
+        let (row, below) = (header_rows - 1, header_rows);
+        let labels: Vec<usize> = (0..cells[row].len())
+            .filter(|&c| !cells[row][c].is_empty())
+            .collect();
+        if labels.is_empty() {
+            break;
+        }
+
+        let mut row_spans = Vec::new();
+        for &label in &labels {
+            // Columns closer to this label than to any other, ties going left
+            let owns = |c: usize| {
+                labels.iter().all(|&other| {
+                    let (d, d_other) = (c.abs_diff(label), c.abs_diff(other));
+                    other == label || d < d_other || (d == d_other && label < other)
+                })
+            };
+            // The first column holds the row labels' own header, not a sub-header
+            let covered = |c: usize| {
+                c == label
+                    || (c > 0 && cells[row][c].is_empty() && !cells[below][c].is_empty() && owns(c))
+            };
+            let mut first = label;
+            while first > 0 && covered(first - 1) {
+                first -= 1;
+            }
+            let mut last = label;
+            while last + 1 < cells[row].len() && covered(last + 1) {
+                last += 1;
+            }
+            if last > first && groups_sub_headers(cells, below, first, last - first + 1) {
+                row_spans.push((label, first, last));
+            }
+        }
+        if row_spans.is_empty() {
+            break;
+        }
+        for (label, first, last) in row_spans {
+            let text = std::mem::take(&mut cells[row][label]);
+            cells[row][first] = text;
+            spans.push(CellSpan {
+                row,
+                col: first,
+                row_span: 1,
+                col_span: last - first + 1,
+            });
+        }
+        header_rows += 1;
+    }
+    (spans, header_rows)
+}
+
+/// Median font size of the items
+fn median_font_size(items: &[(usize, &TextItem)]) -> f32 {
+    let mut sizes: Vec<f32> = items.iter().map(|(_, i)| i.font_size).collect();
+    sizes.sort_by(f32::total_cmp);
+    sizes.get(sizes.len() / 2).copied().unwrap_or(10.0)
+}
 
-    // Validation 8: Check for Table of Contents pattern
-    // TOCs have dots (leader lines) and page numbers, not real table data
-    if is_table_of_contents(&cells) {
-        return None;
+/// Function words that leave a wrapped cell line unfinished
+const CONTINUING_WORDS: [&str; 13] = [
+    "and", "or", "of", "to", "the", "a", "an", "for", "in", "on", "at", "by", "from",
+];
+
+/// Merge rows that are the wrapped continuation of the row above into it,
+/// joining the cell lines with `\n`. A row continues the one above when it is
+/// close below it and its filled columns nest with those above: either the
+/// extra line only continues text cells ("Net income attributable to" /
+/// "shareholders" with the values on the first line), or the row above holds
+/// only text that the row below completes alongside its values. Closeness is
+/// relative to the table's usual row pitch, unless the text itself shows the
+/// wrap (a lowercase start, or a line ending in a comma, hyphen or
+/// connecting word). Text-only rows filling the same columns merge only when
+/// the text shows the wrap.
+fn merge_wrapped_rows(
+    cells: &mut Vec<Vec<String>>,
+    cell_bounds: &mut Vec<Vec<Option<[f32; 4]>>>,
+    rows: &mut Vec<f32>,
+    font_size: f32,
+) {
+    if rows.len() < 3 {
+        return;
+    }
+    let mut gaps: Vec<f32> = rows.windows(2).map(|w| w[0] - w[1]).collect();
+    gaps.sort_by(f32::total_cmp);
+    let row_pitch = gaps[gaps.len() / 2];
+
+    let filled =
+        |row: &[String]| -> Vec<usize> { (0..row.len()).filter(|&c| !row[c].is_empty()).collect() };
+    let is_number = |text: &str| looks_like_number(text.trim_matches(|c| "()$€£%".contains(c)));
+
+    // Baseline of each row's last line, which moves down as lines are merged in
+    let mut last_lines = rows.clone();
+    let mut r = 0;
+    while r + 1 < cells.len() {
+        let gap = last_lines[r] - rows[r + 1];
+        let (above, below) = (&cells[r], &cells[r + 1]);
+        let (filled_above, filled_below) = (filled(above), filled(below));
+        let text_only = |row: &[String], cols: &[usize]| cols.iter().all(|&c| !is_number(&row[c]));
+
+        // Values on the first line, text continued below
+        let continues_text = filled_below.len() < filled_above.len()
+            && !filled_below.is_empty()
+            && filled_below.iter().all(|c| filled_above.contains(c))
+            && text_only(below, &filled_below)
+            && text_only(above, &filled_below);
+        // Text started above, values on the last line
+        let completed_below = filled_above.len() < filled_below.len()
+            && !filled_above.is_empty()
+            && filled_above.iter().all(|c| filled_below.contains(c))
+            && text_only(above, &filled_above)
+            && text_only(below, &filled_above);
+
+        let shared: Vec<usize> = filled_above
+            .iter()
+            .copied()
+            .filter(|c| filled_below.contains(c))
+            .collect();
+        let unfinished = shared.iter().any(|&c| {
+            let last_word = above[c].rsplit([' ', '\n']).next().unwrap_or("");
+            above[c].ends_with([',', '-'])
+                || CONTINUING_WORDS.contains(&last_word.to_lowercase().as_str())
+        });
+        let lowercase_start = shared
+            .iter()
+            .all(|&c| below[c].chars().next().is_some_and(char::is_lowercase));
+        let wrap_shown = unfinished || lowercase_start;
+        let close = gap < row_pitch * 0.85 || (wrap_shown && gap <= font_size * 1.6);
+        // A label wrapped over more lines before its values: only the text tells
+        let text_wrapped = filled_above == filled_below
+            && text_only(above, &filled_above)
+            && text_only(below, &filled_below)
+            && wrap_shown
+            && gap <= font_size * 1.6;
+
+        if ((continues_text || completed_below) && close) || text_wrapped {
+            let below = cells.remove(r + 1);
+            for (cell, extra) in cells[r].iter_mut().zip(below) {
+                if extra.is_empty() {
+                    continue;
+                }
+                if !cell.is_empty() {
+                    cell.push('\n');
+                }
+                cell.push_str(&extra);
+            }
+            let below = cell_bounds.remove(r + 1);
+            for (bounds, extra) in cell_bounds[r].iter_mut().zip(below) {
+                *bounds = match (*bounds, extra) {
+                    (Some(a), Some(b)) => Some(union_bounds(a, b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            rows.remove(r + 1);
+            last_lines.remove(r);
+        } else {
+            r += 1;
+        }
     }
-
-    Some(Table {
-        columns,
-        rows,
-        cells,
-        item_indices,
-    })
 }
 
 /// Check if this looks like a key-value pair layout rather than a table
@@ -545,8 +1826,8 @@ fn looks_like_table_data(s: &str) -> bool {
         return false;
     }
 
-    // Pure numbers
-    if looks_like_number(s) {
+    // Pure numbers and amounts
+    if looks_like_amount(s) {
         return true;
     }
 
@@ -666,7 +1947,7 @@ fn is_table_of_contents(cells: &[Vec<String>]) -> bool {
 /// Check what fraction of items align to detected columns
 fn check_column_alignment(
     items: &[(usize, &TextItem)],
-    columns: &[f32],
+    columns: &[Column],
     mode: TableDetectionMode,
 ) -> f32 {
     let tolerance = match mode {
@@ -675,19 +1956,122 @@ fn check_column_alignment(
     };
     let aligned = items
         .iter()
-        .filter(|(_, item)| columns.iter().any(|&col| (item.x - col).abs() < tolerance))
+        .filter(|(_, item)| columns.iter().any(|col| col.offset(item) < tolerance))
         .count();
 
     aligned as f32 / items.len() as f32
 }
 
-/// Find column boundaries by clustering X positions
-fn find_column_boundaries(items: &[(usize, &TextItem)], mode: TableDetectionMode) -> Vec<f32> {
+/// Edge the text of a stream table column lines up on
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnAlign {
+    Left,
+    Right,
+    /// Numbers lined up on their decimal point
+    Decimal,
+}
+
+/// A stream table column: where its text starts, and the position its text
+/// is aligned at
+#[derive(Debug, Clone, Copy)]
+struct Column {
+    x: f32,
+    align: ColumnAlign,
+    anchor: f32,
+}
+
+impl Column {
+    fn left(x: f32) -> Self {
+        Column {
+            x,
+            align: ColumnAlign::Left,
+            anchor: x,
+        }
+    }
+
+    /// Distance of an item from the column's alignment position
+    fn offset(&self, item: &TextItem) -> f32 {
+        match self.align {
+            ColumnAlign::Left => (item.x - self.anchor).abs(),
+            ColumnAlign::Right => (item.x + item.width - self.anchor).abs(),
+            ColumnAlign::Decimal => (decimal_position(item) - self.anchor).abs(),
+        }
+    }
+}
+
+/// Estimated x of an item's decimal point: after the last digit when it has
+/// none, and at its right edge when it is not a number
+fn decimal_position(item: &TextItem) -> f32 {
+    let chars: Vec<char> = item.text.chars().collect();
+    let point = chars
+        .iter()
+        .position(|&c| c == '.' || c == '·')
+        .or_else(|| {
+            chars
+                .iter()
+                .rposition(|c| c.is_ascii_digit())
+                .map(|last| last + 1)
+        });
+    match point {
+        Some(point) if !chars.is_empty() => item.x + item.width * point as f32 / chars.len() as f32,
+        _ => item.x + item.width,
+    }
+}
+
+/// Find column boundaries: columns of amounts lined up on their right edge or
+/// decimal point, and columns of everything else clustered by left X position
+fn find_column_boundaries(items: &[(usize, &TextItem)], mode: TableDetectionMode) -> Vec<Column> {
+    if items.is_empty() {
+        return vec![];
+    }
+    let (aligned, members) = aligned_number_columns(items);
+    let rest: Vec<(usize, &TextItem)> = items
+        .iter()
+        .filter(|(idx, _)| !members.contains(idx))
+        .copied()
+        .collect();
+    let (left, cluster_threshold) = left_aligned_columns(&rest);
+
+    // Headers over a column of amounts belong to it
+    let mut columns: Vec<Column> = left
+        .into_iter()
+        .filter(|&x| {
+            !aligned
+                .iter()
+                .any(|col| x >= col.x - cluster_threshold / 2.0 && x < col.anchor)
+        })
+        .map(Column::left)
+        .collect();
+    columns.extend(aligned);
+    columns.sort_by(|a, b| a.x.total_cmp(&b.x));
+
+    // Anti-paragraph safeguard for BodyFont mode:
+    // Paragraphs concentrate items at the left margin; tables distribute evenly.
+    // Reject if any single column has >60% of all items.
+    if mode == TableDetectionMode::BodyFont {
+        let total_items = items.len();
+        for col in &columns {
+            let count = items
+                .iter()
+                .filter(|(_, i)| col.offset(i) < cluster_threshold)
+                .count();
+            if count as f32 / total_items as f32 > 0.60 {
+                return vec![];
+            }
+        }
+    }
+
+    columns
+}
+
+/// Cluster left X positions into columns, returning them with the
+/// clustering distance used
+fn left_aligned_columns(items: &[(usize, &TextItem)]) -> (Vec<f32>, f32) {
     let mut x_positions: Vec<f32> = items.iter().map(|(_, i)| i.x).collect();
     x_positions.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
 
     if x_positions.is_empty() {
-        return vec![];
+        return (vec![], 50.0);
     }
 
     // Calculate adaptive threshold based on X-position density
@@ -735,23 +2119,69 @@ fn find_column_boundaries(items: &[(usize, &TextItem)], mode: TableDetectionMode
         })
         .collect();
 
-    // Anti-paragraph safeguard for BodyFont mode:
-    // Paragraphs concentrate items at the left margin; tables distribute evenly.
-    // Reject if any single column has >60% of all items.
-    if mode == TableDetectionMode::BodyFont {
-        let total_items = items.len();
-        for &col_x in &columns {
-            let count = items
-                .iter()
-                .filter(|(_, i)| (i.x - col_x).abs() < cluster_threshold)
-                .count();
-            if count as f32 / total_items as f32 > 0.60 {
-                return vec![];
+    (columns, cluster_threshold)
+}
+
+/// Columns of amounts that share a right edge or decimal point but not a left
+/// edge, as in financial statements, with the indices of the amounts in them
+fn aligned_number_columns(
+    items: &[(usize, &TextItem)],
+) -> (Vec<Column>, std::collections::HashSet<usize>) {
+    let mut columns = Vec::new();
+    let mut members = std::collections::HashSet::new();
+    let tolerance = (median_font_size(items) * 0.6).max(3.0);
+
+    for align in [ColumnAlign::Right, ColumnAlign::Decimal] {
+        let edge = |item: &TextItem| match align {
+            ColumnAlign::Right => item.x + item.width,
+            _ => decimal_position(item),
+        };
+        let mut amounts: Vec<(usize, &TextItem)> = items
+            .iter()
+            .filter(|(idx, item)| !members.contains(idx) && looks_like_amount(&item.text))
+            .copied()
+            .collect();
+        amounts.sort_by(|a, b| edge(a.1).total_cmp(&edge(b.1)));
+
+        let mut clusters: Vec<Vec<(usize, &TextItem)>> = Vec::new();
+        for amount in amounts {
+            match clusters.last_mut() {
+                Some(cluster) if edge(amount.1) - edge(cluster[0].1) <= tolerance => {
+                    cluster.push(amount)
+                }
+                _ => clusters.push(vec![amount]),
+            }
+        }
+        for cluster in clusters.into_iter().filter(|c| c.len() >= 3) {
+            let lefts = cluster.iter().map(|(_, i)| i.x);
+            let x = lefts.clone().fold(f32::INFINITY, f32::min);
+            // Amounts starting at the same X are left-aligned
+            if lefts.fold(f32::NEG_INFINITY, f32::max) - x <= tolerance {
+                continue;
             }
+            let anchor = cluster.iter().map(|(_, i)| edge(i)).sum::<f32>() / cluster.len() as f32;
+            members.extend(cluster.iter().map(|(idx, _)| *idx));
+            columns.push(Column { x, align, anchor });
         }
     }
+    (columns, members)
+}
 
-    columns
+/// Whether text is a number or an amount: `1,234.5`, `(1,234)`, `$12`,
+/// `4.5%`, or a dash standing for zero
+fn looks_like_amount(s: &str) -> bool {
+    let s = s.trim();
+    if matches!(s, "-" | "–" | "—") {
+        return true;
+    }
+    let s = s
+        .strip_prefix('(')
+        .and_then(|s| s.strip_suffix(')'))
+        .unwrap_or(s);
+    let s = s
+        .trim_start_matches(['$', '€', '£', '¥'])
+        .trim_end_matches('%');
+    looks_like_number(s)
 }
 
 /// Find row boundaries by clustering Y positions
@@ -794,13 +2224,13 @@ fn find_row_boundaries(items: &[(usize, &TextItem)]) -> Vec<f32> {
     rows
 }
 
-/// Find which column index an X position belongs to
-fn find_column_index(columns: &[f32], x: f32) -> Option<usize> {
+/// Find which column an item belongs to
+fn find_column_index(columns: &[Column], item: &TextItem) -> Option<usize> {
     // Calculate adaptive threshold based on column spacing
     let threshold = if columns.len() >= 2 {
         let min_gap = columns
             .windows(2)
-            .map(|w| (w[1] - w[0]).abs())
+            .map(|w| (w[1].x - w[0].x).abs())
             .fold(f32::INFINITY, f32::min);
         (min_gap / 2.0).clamp(25.0, 50.0)
     } else {
@@ -809,14 +2239,10 @@ fn find_column_index(columns: &[f32], x: f32) -> Option<usize> {
 
     columns
         .iter()
+        .map(|col| col.offset(item))
         .enumerate()
-        .min_by(|(_, a), (_, b)| {
-            (x - *a)
-                .abs()
-                .partial_cmp(&(x - *b).abs())
-                .unwrap_or(std::cmp::Ordering::Equal)
-        })
-        .filter(|(_, col_x)| (x - *col_x).abs() < threshold)
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+        .filter(|(_, offset)| *offset < threshold)
         .map(|(idx, _)| idx)
 }
 
@@ -883,14 +2309,28 @@ fn join_cell_items(items: &[&TextItem]) -> String {
     result
 }
 
-/// Format a table as markdown
+/// Format a table as markdown. Multi-row headers are flattened into one
+/// header row ("2023 Q1"), and merged body cells repeat their text down the
+/// rows they span. Line breaks inside cells become spaces.
 pub fn table_to_markdown(table: &Table) -> String {
+    table_to_markdown_with_line_breaks(table, " ")
+}
+
+/// Format a table as markdown, rendering line breaks inside cells as
+/// `line_break` (e.g. `"<br>"`)
+pub fn table_to_markdown_with_line_breaks(table: &Table, line_break: &str) -> String {
     if table.cells.is_empty() || table.cells[0].is_empty() {
         return String::new();
     }
 
     // Clean up the table: merge continuation rows, extract footnotes, remove empty rows
-    let (cleaned_cells, footnotes) = clean_table_cells(&table.cells);
+    let mut cells = flatten_spans(table);
+    for cell in cells.iter_mut().flatten() {
+        if cell.contains('\n') {
+            *cell = cell.replace('\n', line_break);
+        }
+    }
+    let (cleaned_cells, footnotes) = clean_table_cells(&cells);
 
     if cleaned_cells.is_empty() {
         return String::new();
@@ -942,6 +2382,105 @@ pub fn table_to_markdown(table: &Table) -> String {
     output
 }
 
+/// Cells with the header rows joined into one and merged body cells filled in
+fn flatten_spans(table: &Table) -> Vec<Vec<String>> {
+    if table.spans.is_empty() && table.header_rows <= 1 {
+        return table.cells.clone();
+    }
+    let header_rows = table.header_rows.clamp(1, table.cells.len());
+    let num_cols = table.cells[0].len();
+
+    // Text covering a grid position, through the merged cell it belongs to
+    let text_at = |row: usize, col: usize| -> (&str, usize) {
+        match table.span_at(row, col) {
+            Some(span) => (table.cells[span.row][span.col].as_str(), span.row),
+            None => (table.cells[row][col].as_str(), row),
+        }
+    };
+
+    let header: Vec<String> = (0..num_cols)
+        .map(|col| {
+            let mut parts: Vec<&str> = Vec::new();
+            for row in 0..header_rows {
+                // A cell spanning header rows is counted once
+                let (text, from_row) = text_at(row, col);
+                if from_row == row && !text.trim().is_empty() {
+                    parts.push(text.trim());
+                }
+            }
+            parts.join(" ")
+        })
+        .collect();
+
+    let mut cells = vec![header];
+    for row in header_rows..table.cells.len() {
+        cells.push(
+            (0..num_cols)
+                .map(|col| match table.span_at(row, col) {
+                    // Values merged down a column repeat; across columns stay in the first
+                    Some(span) if span.col == col => table.cells[span.row][col].clone(),
+                    _ => table.cells[row][col].clone(),
+                })
+                .collect(),
+        );
+    }
+    cells
+}
+
+/// Format a table as HTML, with merged cells as `rowspan`/`colspan` and the
+/// header rows in `<thead>`
+pub fn table_to_html(table: &Table) -> String {
+    let mut output = String::from("<table>\n");
+    for (row_idx, row) in table.cells.iter().enumerate() {
+        if row_idx == 0 {
+            output.push_str("<thead>\n");
+        } else if row_idx == table.header_rows {
+            output.push_str("<tbody>\n");
+        }
+        let tag = if row_idx < table.header_rows {
+            "th"
+        } else {
+            "td"
+        };
+        output.push_str("<tr>");
+        for (col_idx, cell) in row.iter().enumerate() {
+            let mut attrs = String::new();
+            match table.span_at(row_idx, col_idx) {
+                Some(span) if (span.row, span.col) != (row_idx, col_idx) => continue,
+                Some(span) => {
+                    if span.row_span > 1 {
+                        attrs.push_str(&format!(" rowspan=\"{}\"", span.row_span));
+                    }
+                    if span.col_span > 1 {
+                        attrs.push_str(&format!(" colspan=\"{}\"", span.col_span));
+                    }
+                }
+                None => {}
+            }
+            output.push_str(&format!(
+                "<{tag}{attrs}>{}</{tag}>",
+                escape_html(cell.trim())
+            ));
+        }
+        output.push_str("</tr>\n");
+        if row_idx + 1 == table.header_rows {
+            output.push_str("</thead>\n");
+        }
+    }
+    if table.cells.len() > table.header_rows {
+        output.push_str("</tbody>\n");
+    }
+    output.push_str("</table>\n");
+    output
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', "<br>")
+}
+
 /// Clean up table cells: merge continuation rows, extract footnotes, remove empty rows
 fn clean_table_cells(cells: &[Vec<String>]) -> (Vec<Vec<String>>, Vec<String>) {
     let mut cleaned: Vec<Vec<String>> = Vec::new();
@@ -1166,7 +2705,276 @@ mod tests {
             is_bold: false,
             is_italic: false,
             item_type: crate::extractor::ItemType::Text,
+            ..Default::default()
+        }
+    }
+
+    fn rule(x1: f32, y1: f32, x2: f32, y2: f32) -> GraphicItem {
+        GraphicItem {
+            kind: crate::graphics::GraphicKind::Line,
+            x1,
+            y1,
+            x2,
+            y2,
+            page: 1,
+            stroked: true,
+            filled: false,
+            line_width: 0.5,
+            stroke_color: [0.0; 3],
+            fill_color: [0.0; 3],
+        }
+    }
+
+    #[test]
+    fn test_lattice_table_detection() {
+        let mut items = vec![
+            make_item("Name", 105.0, 487.0, 10.0),
+            make_item("Score", 305.0, 487.0, 10.0),
+            make_item("Alice", 105.0, 467.0, 10.0),
+            make_item("90", 305.0, 467.0, 10.0),
+            make_item("Bob", 105.0, 447.0, 10.0),
+            make_item("Smith", 205.0, 447.0, 10.0),
+            make_item("85", 305.0, 447.0, 10.0),
+            make_item("Text below the table.", 100.0, 300.0, 10.0),
+        ];
+        // A wrapped cell: two lines in one grid cell
+        items[1].text = "Final".into();
+        items.insert(2, make_item("score", 305.0, 482.0, 10.0));
+
+        let mut graphics: Vec<GraphicItem> = [500.0, 480.0, 460.0, 440.0]
+            .iter()
+            .map(|&y| rule(100.0, y, 400.0, y))
+            .collect();
+        for x in [100.0, 300.0, 400.0] {
+            graphics.push(rule(x, 440.0, x, 500.0));
+        }
+        // The header cell spans the first two columns
+        graphics.push(rule(200.0, 440.0, 200.0, 480.0));
+
+        let tables = detect_tables_with_graphics(&items, &graphics, 10.0);
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!(table.columns, vec![100.0, 200.0, 300.0]);
+        assert_eq!(table.rows, vec![500.0, 480.0, 460.0]);
+        assert_eq!(
+            table.cells,
+            vec![
+                vec!["Name", "", "Final\nscore"],
+                vec!["Alice", "", "90"],
+                vec!["Bob", "Smith", "85"],
+            ]
+        );
+        assert_eq!(table.item_indices.len(), 8);
+        assert!(!table.item_indices.contains(&8));
+
+        // Without rules the body-font text is not taken for a table
+        assert!(detect_tables_with_graphics(&items, &[], 10.0).is_empty());
+        // A box around a paragraph is not a table either
+        let frame: Vec<GraphicItem> = vec![
+            rule(90.0, 290.0, 400.0, 290.0),
+            rule(90.0, 320.0, 400.0, 320.0),
+            rule(90.0, 290.0, 90.0, 320.0),
+            rule(400.0, 290.0, 400.0, 320.0),
+        ];
+        assert!(detect_lattice_tables(&items, &frame).is_empty());
+    }
+
+    #[test]
+    fn test_group_rules() {
+        let rule = |horizontal, pos, start, end| Rule {
+            horizontal,
+            pos,
+            start,
+            end,
+        };
+        let rules = vec![
+            // A box drawn as two collinear pieces per side
+            rule(true, 500.0, 100.0, 200.0),
+            rule(true, 501.0, 200.0, 300.0),
+            rule(true, 400.0, 100.0, 300.0),
+            rule(false, 100.0, 400.0, 500.0),
+            rule(false, 300.0, 400.0, 450.0),
+            rule(false, 300.0, 451.5, 500.0),
+            // A cross further down, apart from the box
+            rule(true, 200.0, 100.0, 300.0),
+            rule(false, 200.0, 150.0, 250.0),
+            // A rule touching nothing
+            rule(false, 500.0, 100.0, 300.0),
+        ];
+        let mut sizes: Vec<usize> = group_rules(&rules).iter().map(Vec::len).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 2, 6]);
+    }
+
+    #[test]
+    fn test_merged_header_cells() {
+        // Region | 2023        | Total
+        //        | Q1   | Q2   |
+        // North  | 10   | 12   | 22
+        let mut items = vec![
+            make_item("Region", 105.0, 477.0, 10.0),
+            make_item("2023", 205.0, 487.0, 10.0),
+            make_item("Total", 405.0, 477.0, 10.0),
+            make_item("Q1", 205.0, 467.0, 10.0),
+            make_item("Q2", 305.0, 467.0, 10.0),
+            make_item("North", 105.0, 447.0, 10.0),
+            make_item("10", 205.0, 447.0, 10.0),
+            make_item("12", 305.0, 447.0, 10.0),
+            make_item("22", 405.0, 447.0, 10.0),
+        ];
+        items[1].text = "2023 & prior".into();
+        let mut graphics = vec![
+            rule(100.0, 500.0, 500.0, 500.0),
+            rule(200.0, 480.0, 400.0, 480.0),
+            rule(100.0, 460.0, 500.0, 460.0),
+            rule(100.0, 440.0, 500.0, 440.0),
+            rule(300.0, 440.0, 300.0, 480.0),
+        ];
+        for x in [100.0, 200.0, 400.0, 500.0] {
+            graphics.push(rule(x, 440.0, x, 500.0));
         }
+
+        let tables = detect_lattice_tables(&items, &graphics);
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!(table.header_rows, 2);
+        assert_eq!(
+            table.spans,
+            vec![
+                CellSpan {
+                    row: 0,
+                    col: 0,
+                    row_span: 2,
+                    col_span: 1
+                },
+                CellSpan {
+                    row: 0,
+                    col: 1,
+                    row_span: 1,
+                    col_span: 2
+                },
+                CellSpan {
+                    row: 0,
+                    col: 3,
+                    row_span: 2,
+                    col_span: 1
+                },
+            ]
+        );
+
+        // The merged group label covers both quarter columns
+        assert_eq!(table.cell_bounds[0][1], Some([200.0, 480.0, 400.0, 500.0]));
+        assert_eq!(table.cell_bounds[0][2], None);
+        assert_eq!(table.bbox(), Some([100.0, 440.0, 500.0, 500.0]));
+
+        let md = table_to_markdown(table);
+        let header = md.lines().next().unwrap();
+        assert!(header.contains("| Region "));
+        assert!(header.contains("| 2023 & prior Q1 | 2023 & prior Q2 |"));
+        assert!(md.contains("| North "));
+
+        let html = table_to_html(table);
+        assert!(html.contains(
+            "<tr><th rowspan=\"2\">Region</th><th colspan=\"2\">2023 &amp; prior</th>\
+             <th rowspan=\"2\">Total</th></tr>"
+        ));
+        assert!(html.contains("<tr><th>Q1</th><th>Q2</th></tr>\n</thead>"));
+        assert!(html.contains("<tbody>\n<tr><td>North</td><td>10</td><td>12</td><td>22</td></tr>"));
+    }
+
+    #[test]
+    fn test_stream_header_spans() {
+        let row = |cells: &[&str]| cells.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        let mut cells = vec![
+            row(&["", "", "2023", "", "", "2024", ""]),
+            row(&["Region", "Q1", "Q2", "Q3", "Q1", "Q2", "Q3"]),
+            row(&["North", "1", "2", "3", "4", "5", "6"]),
+        ];
+        let (spans, header_rows) = stream_header_spans(&mut cells);
+        assert_eq!(header_rows, 2);
+        assert_eq!(
+            spans,
+            vec![
+                CellSpan {
+                    row: 0,
+                    col: 1,
+                    row_span: 1,
+                    col_span: 3
+                },
+                CellSpan {
+                    row: 0,
+                    col: 4,
+                    row_span: 1,
+                    col_span: 3
+                },
+            ]
+        );
+        assert_eq!(cells[0], row(&["", "2023", "", "", "2024", "", ""]));
+
+        // An empty header cell over data is not a group
+        let mut cells = vec![
+            row(&["Name", "", "Score"]),
+            row(&["Alice", "Smith", "90"]),
+            row(&["Bob", "Jones", "85"]),
+        ];
+        assert_eq!(stream_header_spans(&mut cells), (vec![], 1));
+    }
+
+    #[test]
+    fn test_merge_wrapped_rows() {
+        let row = |cells: &[&str]| cells.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        let mut cells = vec![
+            row(&["Item", "2023", "2022"]),
+            row(&["Revenue", "1,200", "1,100"]),
+            // Values on the first line
+            row(&["Net income attributable to", "(300)", "250"]),
+            row(&["shareholders", "", ""]),
+            // Values on the last line, three lines in all
+            row(&["Other comprehensive", "", ""]),
+            row(&["income, net of", "", ""]),
+            row(&["Tax", "40", "35"]),
+            // A section label at the usual row pitch is a row of its own
+            row(&["Operating expenses", "", ""]),
+            row(&["Salaries", "500", "480"]),
+        ];
+        let mut rows = vec![
+            500.0, 484.0, 468.0, 458.0, 442.0, 432.0, 422.0, 406.0, 390.0,
+        ];
+        let mut bounds = vec![vec![None; 3]; cells.len()];
+        bounds[2][0] = Some([100.0, 468.0, 210.0, 477.0]);
+        bounds[3][0] = Some([100.0, 458.0, 160.0, 467.0]);
+        merge_wrapped_rows(&mut cells, &mut bounds, &mut rows, 9.0);
+
+        assert_eq!(
+            cells,
+            vec![
+                row(&["Item", "2023", "2022"]),
+                row(&["Revenue", "1,200", "1,100"]),
+                row(&["Net income attributable to\nshareholders", "(300)", "250"]),
+                row(&["Other comprehensive\nincome, net of\nTax", "40", "35"]),
+                row(&["Operating expenses", "", ""]),
+                row(&["Salaries", "500", "480"]),
+            ]
+        );
+        assert_eq!(rows, vec![500.0, 484.0, 468.0, 442.0, 406.0, 390.0]);
+        assert_eq!(bounds.len(), 6);
+        assert_eq!(bounds[2][0], Some([100.0, 458.0, 210.0, 477.0]));
+
+        let table = Table {
+            columns: vec![100.0, 300.0, 400.0],
+            rows,
+            cells,
+            item_indices: vec![],
+            spans: vec![],
+            header_rows: 1,
+            cell_bounds: bounds,
+            row_levels: vec![],
+            row_kinds: vec![],
+            row_pages: vec![],
+        };
+        assert!(table_to_markdown(&table).contains("| Net income attributable to shareholders "));
+        assert!(table_to_markdown_with_line_breaks(&table, "<br>")
+            .contains("| Net income attributable to<br>shareholders "));
     }
 
     #[test]
@@ -1201,6 +3009,265 @@ mod tests {
         assert_eq!(tables[0].rows.len(), 4);
     }
 
+    #[test]
+    fn test_financial_statement() {
+        let label = |text: &str, x: f32, y: f32, bold: bool| TextItem {
+            width: text.len() as f32 * 5.0,
+            is_bold: bold,
+            ..make_item(text, x, y, 10.0)
+        };
+        // Right-aligned at 300 and 380, the widest far left of the narrowest
+        let amount = |text: &str, right: f32, y: f32| TextItem {
+            x: right - text.len() as f32 * 5.0,
+            ..label(text, 0.0, y, false)
+        };
+        let rows: [(&str, f32, [&str; 2], bool); 8] = [
+            ("Balance sheet", 72.0, ["2023", "2022"], false),
+            ("Cash", 84.0, ["1,250,000", "980,000"], false),
+            ("Receivables", 84.0, ["12,400", "9,870"], false),
+            ("Inventory", 84.0, ["310", "(45)"], false),
+            (
+                "Total current assets",
+                84.0,
+                ["1,262,710", "989,825"],
+                false,
+            ),
+            ("Property", 72.0, ["4,000", "4,100"], false),
+            ("Goodwill", 72.0, ["-", "120"], false),
+            ("Total assets", 72.0, ["1,266,710", "994,045"], true),
+        ];
+        let mut items = Vec::new();
+        for (i, (text, x, amounts, bold)) in rows.iter().enumerate() {
+            let y = 600.0 - 16.0 * i as f32;
+            items.push(label(text, *x, y, *bold));
+            items.push(amount(amounts[0], 300.0, y));
+            items.push(amount(amounts[1], 380.0, y));
+        }
+        // Subtotal rule above "Total current assets", double rule below the total
+        let graphics = vec![
+            rule(250.0, 549.0, 380.0, 549.0),
+            rule(250.0, 485.0, 380.0, 485.0),
+            rule(250.0, 483.0, 380.0, 483.0),
+        ];
+
+        let tables =
+            detect_tables_with_config(&items, &graphics, 10.0, &TableDetectionConfig::default());
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!(table.columns.len(), 3, "{:?}", table.cells);
+        assert_eq!(table.cells[1], vec!["Cash", "1,250,000", "980,000"]);
+        assert_eq!(table.cells[3], vec!["Inventory", "310", "(45)"]);
+        assert_eq!(table.row_levels, vec![0, 1, 1, 1, 1, 0, 0, 0]);
+        assert_eq!(
+            table.row_kinds,
+            vec![
+                RowKind::Header,
+                RowKind::Data,
+                RowKind::Data,
+                RowKind::Data,
+                RowKind::Subtotal,
+                RowKind::Data,
+                RowKind::Data,
+                RowKind::Total,
+            ]
+        );
+
+        // Amounts lined up on the decimal point
+        let decimal = |text: &str, y: f32| {
+            let point = text.find('.').unwrap() as f32;
+            TextItem {
+                x: 300.0 - point * 5.0,
+                ..label(text, 0.0, y, false)
+            }
+        };
+        let numbers = [
+            decimal("1.5", 600.0),
+            decimal("12.25", 584.0),
+            decimal("100.125", 568.0),
+        ];
+        let indexed: Vec<(usize, &TextItem)> = numbers.iter().enumerate().collect();
+        let (columns, members) = aligned_number_columns(&indexed);
+        assert_eq!(columns.len(), 1);
+        assert_eq!(columns[0].align, ColumnAlign::Decimal);
+        assert!((columns[0].anchor - 300.0).abs() < 0.01);
+        assert_eq!(members.len(), 3);
+    }
+
+    #[test]
+    fn test_table_detection_config() {
+        let mut items = Vec::new();
+        for (row, y) in [500.0, 480.0, 460.0, 440.0].into_iter().enumerate() {
+            for (col, x) in [100.0, 200.0, 280.0, 360.0].into_iter().enumerate() {
+                let text = if row == 0 || col == 0 {
+                    format!("H{row}{col}")
+                } else {
+                    format!("{row}.{col}")
+                };
+                items.push(make_item(&text, x, y, 8.0));
+            }
+        }
+        let detect = |items: &[TextItem], config: &TableDetectionConfig| {
+            detect_tables_with_config(items, &[], 10.0, config)
+        };
+        assert_eq!(detect(&items, &TableDetectionConfig::default()).len(), 1);
+
+        // 8pt text is not small next to 10pt body text at a 0.75 ratio
+        let config = TableDetectionConfig {
+            small_font_ratio: 0.75,
+            modes: vec![TableDetectionMode::SmallFont],
+            ..Default::default()
+        };
+        assert!(detect(&items, &config).is_empty());
+        assert!(detect(&items, &TableDetectionConfig::disabled()).is_empty());
+
+        // A label/value block the heuristics reject becomes a table when a
+        // region says so, even with detection off
+        let form = vec![
+            make_item("Name", 100.0, 300.0, 10.0),
+            make_item("Alice", 200.0, 300.0, 10.0),
+            make_item("Role", 100.0, 285.0, 10.0),
+            make_item("Admin", 200.0, 285.0, 10.0),
+            make_item("Outside", 100.0, 200.0, 10.0),
+        ];
+        assert!(detect(&form, &TableDetectionConfig::default()).is_empty());
+        let config = TableDetectionConfig {
+            regions: vec![TableRegion {
+                page: 1,
+                bbox: [90.0, 280.0, 260.0, 312.0],
+            }],
+            ..TableDetectionConfig::disabled()
+        };
+        let tables = detect(&form, &config);
+        assert_eq!(tables.len(), 1);
+        assert_eq!(
+            tables[0].cells,
+            vec![vec!["Name", "Alice"], vec!["Role", "Admin"]]
+        );
+        assert_eq!(tables[0].item_indices.len(), 4);
+
+        // Regions on other pages are ignored
+        let config = TableDetectionConfig {
+            regions: vec![TableRegion {
+                page: 2,
+                ..config.regions[0]
+            }],
+            ..config
+        };
+        assert!(detect(&form, &config).is_empty());
+    }
+
+    #[test]
+    fn test_config_needs_graphics() {
+        assert!(TableDetectionConfig::default().needs_graphics());
+        assert!(!TableDetectionConfig::disabled().needs_graphics());
+        let no_passes = TableDetectionConfig {
+            ruled: false,
+            modes: Vec::new(),
+            ..Default::default()
+        };
+        assert!(!no_passes.needs_graphics());
+        let stream_only = TableDetectionConfig {
+            ruled: false,
+            ..Default::default()
+        };
+        assert!(stream_only.needs_graphics());
+        let regions_only = TableDetectionConfig {
+            regions: vec![TableRegion {
+                page: 1,
+                bbox: [0.0, 0.0, 100.0, 100.0],
+            }],
+            ..TableDetectionConfig::disabled()
+        };
+        assert!(regions_only.needs_graphics());
+    }
+
+    #[test]
+    fn test_stitch_tables() {
+        let part = |page: u32, columns: Vec<f32>, cells: &[[&str; 3]], header_rows: usize| {
+            let rows: Vec<f32> = (0..cells.len()).map(|i| 700.0 - 20.0 * i as f32).collect();
+            Table {
+                columns,
+                cells: cells
+                    .iter()
+                    .map(|row| row.iter().map(|c| c.to_string()).collect())
+                    .collect(),
+                item_indices: vec![],
+                spans: vec![],
+                header_rows,
+                cell_bounds: vec![vec![None; 3]; cells.len()],
+                row_levels: vec![],
+                row_kinds: (0..cells.len())
+                    .map(|i| {
+                        if i < header_rows {
+                            RowKind::Header
+                        } else {
+                            RowKind::Data
+                        }
+                    })
+                    .collect(),
+                row_pages: vec![page; rows.len()],
+                rows,
+            }
+        };
+        let header = ["Date", "Description", "Amount"];
+        let columns = vec![72.0, 150.0, 400.0];
+
+        // Repeated header dropped on page 2; page 3 continues without one
+        let parts = vec![
+            part(
+                1,
+                columns.clone(),
+                &[header, ["01/02", "Coffee", "3.50"]],
+                1,
+            ),
+            part(
+                2,
+                vec![73.0, 151.0, 399.0],
+                &[header, ["01/03", "Rent", "1,200.00"]],
+                1,
+            ),
+            part(3, columns.clone(), &[["01/04", "Books", "(42.00)"]], 1),
+        ];
+        let tables = stitch_tables(parts.clone(), &[]);
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!(table.cells.len(), 4);
+        assert_eq!(table.cells[2][1], "Rent");
+        assert_eq!(table.cells[3][2], "(42.00)");
+        assert_eq!(table.row_pages, vec![1, 1, 2, 3]);
+        assert_eq!(table.pages(), vec![1, 2, 3]);
+        assert_eq!(
+            table.row_kinds,
+            vec![RowKind::Header, RowKind::Data, RowKind::Data, RowKind::Data]
+        );
+
+        // Columns elsewhere, a first row shaped differently or a page
+        // skipped keep tables apart
+        let first = part(
+            1,
+            columns.clone(),
+            &[header, ["01/02", "Coffee", "3.50"]],
+            1,
+        );
+        let moved = part(2, vec![72.0, 250.0, 400.0], &[["01/03", "Rent", "9.00"]], 0);
+        assert_eq!(stitch_tables(vec![first.clone(), moved], &[]).len(), 2);
+        let text = part(2, columns.clone(), &[["Notes", "See below", "n/a"]], 0);
+        assert!(!is_continuation(&first, &text));
+        let later = part(3, columns, &[["01/03", "Rent", "9.00"]], 0);
+        assert!(is_continuation(&first, &later));
+        assert_eq!(stitch_tables(vec![first, later], &[]).len(), 2);
+
+        // Body text after the table on page 1 ends it there
+        let note = TextLine {
+            items: vec![],
+            y: 600.0,
+            page: 1,
+        };
+        let tables = stitch_tables(parts, &[note]);
+        assert_eq!(tables.len(), 2);
+        assert_eq!(tables[1].row_pages, vec![2, 2, 3]);
+    }
+
     #[test]
     fn test_table_to_markdown() {
         let table = Table {
@@ -1211,6 +3278,12 @@ mod tests {
                 vec!["Cell 1".into(), "Cell 2".into()],
             ],
             item_indices: vec![],
+            spans: vec![],
+            header_rows: 1,
+            cell_bounds: vec![],
+            row_levels: vec![],
+            row_kinds: vec![],
+            row_pages: vec![],
         };
 
         let md = table_to_markdown(&table);
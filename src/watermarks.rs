@@ -0,0 +1,340 @@
+//! Watermark detection
+//!
+//! "DRAFT" or "CONFIDENTIAL" stamped across a page is drawn as ordinary text,
+//! and would otherwise be merged into the body lines it crosses. An item is a
+//! watermark when it is tagged as one (`/Artifact /Watermark` marked content or
+//! a Watermark annotation), or when it is diagonal and shows at least one more
+//! sign: far larger than the body text, a light fill color, transparency, or the
+//! same text on many pages. Horizontal text must show all of these at once:
+//! oversized, light or transparent, and repeated. A large light title on a
+//! cover, or a large section title on several pages, is left alone.
+
+use crate::extractor::{ItemType, TextItem};
+use crate::hidden_text::{luminance, stands_out_from};
+use std::collections::{BTreeSet, HashMap};
+
+/// Font size, relative to the body text, above which text is oversized
+const OVERSIZED_RATIO: f32 = 3.0;
+
+/// Deviation from horizontal or vertical, in degrees, above which text is diagonal
+const DIAGONAL_TOLERANCE: f32 = 10.0;
+
+/// Luminance above which a fill color is light
+const LIGHT_LUMINANCE: f32 = 0.6;
+
+/// Fill opacity below which text is transparent
+const TRANSPARENT_OPACITY: f32 = 0.9;
+
+/// Minimum fraction of the document's pages repeated text must appear on
+const REPEATED_PAGE_FRACTION: f32 = 0.5;
+
+/// Evidence that text is a watermark
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WatermarkSignal {
+    /// Tagged as `/Artifact /Watermark`, or drawn by a Watermark annotation
+    Tagged,
+    /// Neither horizontal nor vertical
+    Diagonal,
+    /// Far larger than the body text
+    Oversized,
+    /// Light fill color, with no darker fill or image behind it
+    Light,
+    /// Semi-transparent fill
+    Transparent,
+    /// Same text on many pages
+    Repeated,
+}
+
+/// A watermark found in the document
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watermark {
+    /// Watermark text
+    pub text: String,
+    /// Pages it appears on
+    pub pages: Vec<u32>,
+    /// Evidence found for it, across all pages
+    pub signals: Vec<WatermarkSignal>,
+}
+
+/// Find the watermark items among `items`, returning their indices
+pub fn watermark_indices(items: &[TextItem]) -> Vec<usize> {
+    classify(items).into_iter().map(|(idx, _)| idx).collect()
+}
+
+/// Find the document's watermarks
+pub fn detect_watermarks(items: &[TextItem]) -> Vec<Watermark> {
+    // Consecutive watermark items on a page form one watermark; glyph-by-glyph
+    // drawn text is joined back together
+    let mut runs: Vec<(u32, String, BTreeSet<WatermarkSignal>)> = Vec::new();
+    let mut prev_idx = None;
+    for (idx, signals) in classify(items) {
+        let item = &items[idx];
+        let text = item.text.trim();
+        match runs.last_mut() {
+            Some((page, run, run_signals))
+                if *page == item.page && prev_idx == Some(idx.wrapping_sub(1)) =>
+            {
+                let glyphs = run.chars().last().is_some_and(|c| !c.is_whitespace())
+                    && text.chars().count() == 1;
+                if !glyphs {
+                    run.push(' ');
+                }
+                run.push_str(text);
+                run_signals.extend(signals);
+            }
+            _ => runs.push((item.page, text.to_string(), signals.into_iter().collect())),
+        }
+        prev_idx = Some(idx);
+    }
+
+    let mut watermarks: Vec<Watermark> = Vec::new();
+    for (page, text, signals) in runs {
+        match watermarks.iter_mut().find(|w| w.text == text) {
+            Some(watermark) => {
+                if !watermark.pages.contains(&page) {
+                    watermark.pages.push(page);
+                }
+                for signal in signals {
+                    if !watermark.signals.contains(&signal) {
+                        watermark.signals.push(signal);
+                    }
+                }
+                watermark.signals.sort();
+            }
+            None => watermarks.push(Watermark {
+                text,
+                pages: vec![page],
+                signals: signals.into_iter().collect(),
+            }),
+        }
+    }
+    watermarks
+}
+
+/// Drop watermark items
+pub fn remove_watermarks(items: Vec<TextItem>) -> Vec<TextItem> {
+    let watermarks: BTreeSet<usize> = watermark_indices(&items).into_iter().collect();
+    if watermarks.is_empty() {
+        return items;
+    }
+    items
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| !watermarks.contains(idx))
+        .map(|(_, item)| item)
+        .collect()
+}
+
+/// Watermark items with the signals found for each, in item order
+fn classify(items: &[TextItem]) -> Vec<(usize, Vec<WatermarkSignal>)> {
+    let text_items = || {
+        items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.item_type == ItemType::Text && !item.text.trim().is_empty())
+    };
+    let body_size = body_font_size(items);
+
+    // Pages each text appears on
+    let mut text_pages: HashMap<String, BTreeSet<u32>> = HashMap::new();
+    for (_, item) in text_items() {
+        text_pages
+            .entry(item.text.trim().to_lowercase())
+            .or_default()
+            .insert(item.page);
+    }
+    let page_count = text_items()
+        .map(|(_, item)| item.page)
+        .collect::<BTreeSet<_>>()
+        .len();
+    let min_repeats = ((page_count as f32 * REPEATED_PAGE_FRACTION).ceil() as usize).max(2);
+
+    let mut result = Vec::new();
+    for (idx, item) in text_items() {
+        let mut signals = Vec::new();
+        if item.artifact.as_deref() == Some("Watermark")
+            || item.annotation.as_deref() == Some("Watermark")
+        {
+            signals.push(WatermarkSignal::Tagged);
+        }
+        let off_axis = item.rotation.rem_euclid(90.0);
+        if off_axis > DIAGONAL_TOLERANCE && off_axis < 90.0 - DIAGONAL_TOLERANCE {
+            signals.push(WatermarkSignal::Diagonal);
+        }
+        if body_size > 0.0 && item.font_size >= body_size * OVERSIZED_RATIO {
+            signals.push(WatermarkSignal::Oversized);
+        }
+        // Light text on a dark band is as legible as dark text on the page
+        if luminance(item.fill_color) >= LIGHT_LUMINANCE
+            && !stands_out_from(item.fill_color, item.backdrop)
+        {
+            signals.push(WatermarkSignal::Light);
+        }
+        if item.opacity.is_some_and(|a| a < TRANSPARENT_OPACITY) {
+            signals.push(WatermarkSignal::Transparent);
+        }
+        if text_pages[&item.text.trim().to_lowercase()].len() >= min_repeats {
+            signals.push(WatermarkSignal::Repeated);
+        }
+
+        let has = |signal| signals.contains(&signal);
+        let horizontal_stamp = has(WatermarkSignal::Oversized)
+            && (has(WatermarkSignal::Light) || has(WatermarkSignal::Transparent))
+            && has(WatermarkSignal::Repeated);
+        if has(WatermarkSignal::Tagged)
+            || (has(WatermarkSignal::Diagonal) && signals.len() >= 2)
+            || horizontal_stamp
+        {
+            result.push((idx, signals));
+        }
+    }
+    result
+}
+
+/// Most common font size of text items, weighted by text length
+fn body_font_size(items: &[TextItem]) -> f32 {
+    let mut counts: HashMap<i32, usize> = HashMap::new();
+    for item in items.iter().filter(|i| i.item_type == ItemType::Text) {
+        *counts
+            .entry((item.font_size * 2.0).round() as i32)
+            .or_default() += item.text.chars().count();
+    }
+    counts
+        .into_iter()
+        .max_by_key(|&(size, count)| (count, -size))
+        .map_or(0.0, |(size, _)| size as f32 / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(text: &str, font_size: f32, page: u32) -> TextItem {
+        TextItem {
+            text: text.to_string(),
+            x: 72.0,
+            y: 400.0,
+            width: text.len() as f32 * font_size * 0.5,
+            height: font_size,
+            font_size,
+            page,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_detect_watermarks() {
+        let mut items = Vec::new();
+        for page in 1..=3 {
+            items.push(item(
+                "Body text that fills the page with words.",
+                11.0,
+                page,
+            ));
+            let mut draft = item("DRAFT", 72.0, page);
+            draft.rotation = 45.0;
+            draft.fill_color = [0.85; 3];
+            items.push(draft);
+        }
+        // Large title on one page only: oversized but no other signal
+        items.push(item("Annual Report", 40.0, 1));
+        // Tagged watermark drawn glyph by glyph
+        for c in ["C", "O", "P", "Y"] {
+            let mut glyph = item(c, 11.0, 2);
+            glyph.artifact = Some("Watermark".to_string());
+            items.push(glyph);
+        }
+
+        let watermarks = detect_watermarks(&items);
+        assert_eq!(watermarks.len(), 2);
+        assert_eq!(watermarks[0].text, "DRAFT");
+        assert_eq!(watermarks[0].pages, vec![1, 2, 3]);
+        assert_eq!(
+            watermarks[0].signals,
+            vec![
+                WatermarkSignal::Diagonal,
+                WatermarkSignal::Oversized,
+                WatermarkSignal::Light,
+                WatermarkSignal::Repeated,
+            ]
+        );
+        assert_eq!(watermarks[1].text, "COPY");
+        assert_eq!(watermarks[1].signals, vec![WatermarkSignal::Tagged]);
+
+        let kept: Vec<String> = remove_watermarks(items)
+            .into_iter()
+            .map(|i| i.text)
+            .collect();
+        assert_eq!(kept.len(), 4);
+        assert!(kept.contains(&"Annual Report".to_string()));
+    }
+
+    #[test]
+    fn test_large_light_headings_are_not_watermarks() {
+        let mut items = Vec::new();
+        for page in 1..=4 {
+            items.push(item(
+                "Body text that fills the page with words.",
+                11.0,
+                page,
+            ));
+        }
+        // White cover title on a dark band
+        let mut title = item("Annual Report", 40.0, 1);
+        title.fill_color = [1.0; 3];
+        title.backdrop = Some(0.1);
+        items.push(title);
+        // Light grey title on the bare page
+        let mut subtitle = item("Fiscal Year 2024", 36.0, 1);
+        subtitle.fill_color = [0.7; 3];
+        items.push(subtitle);
+        // Big section title repeated on half the pages
+        for page in [2, 4] {
+            items.push(item("Financial Statements", 40.0, page));
+        }
+        assert!(detect_watermarks(&items).is_empty());
+        assert_eq!(remove_watermarks(items.clone()).len(), items.len());
+
+        // Turned diagonal, light text on a dark band is not light
+        let mut stamp = item("SAMPLE", 40.0, 3);
+        stamp.rotation = 45.0;
+        stamp.fill_color = [0.9; 3];
+        stamp.backdrop = Some(0.2);
+        items.push(stamp);
+        let watermarks = detect_watermarks(&items);
+        assert_eq!(watermarks.len(), 1);
+        assert_eq!(
+            watermarks[0].signals,
+            vec![WatermarkSignal::Diagonal, WatermarkSignal::Oversized]
+        );
+    }
+
+    #[test]
+    fn test_horizontal_repeated_light_stamp() {
+        let mut items = Vec::new();
+        for page in 1..=4 {
+            items.push(item(
+                "Body text that fills the page with words.",
+                11.0,
+                page,
+            ));
+            let mut stamp = item("CONFIDENTIAL", 60.0, page);
+            stamp.fill_color = [0.8; 3];
+            items.push(stamp);
+        }
+
+        let watermarks = detect_watermarks(&items);
+        assert_eq!(watermarks.len(), 1);
+        assert_eq!(watermarks[0].text, "CONFIDENTIAL");
+        assert_eq!(watermarks[0].pages, vec![1, 2, 3, 4]);
+        assert_eq!(
+            watermarks[0].signals,
+            vec![
+                WatermarkSignal::Oversized,
+                WatermarkSignal::Light,
+                WatermarkSignal::Repeated,
+            ]
+        );
+        assert_eq!(remove_watermarks(items).len(), 4);
+    }
+}
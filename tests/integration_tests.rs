@@ -22,6 +22,7 @@ fn make_text_item(text: &str, x: f32, y: f32, font_size: f32, page: u32) -> Text
         is_bold: false,
         is_italic: false,
         item_type: ItemType::Text,
+        ..Default::default()
     }
 }
 
@@ -46,9 +47,54 @@ fn make_text_item_with_font(
         is_bold: is_bold_font(font),
         is_italic: is_italic_font(font),
         item_type: ItemType::Text,
+        ..Default::default()
     }
 }
 
+// Helper to serialize a one-page PDF showing `text` in Helvetica, with extra
+// catalog entries
+fn text_pdf(text: &str, catalog_entries: lopdf::Dictionary) -> Vec<u8> {
+    let content = format!("BT /F1 12 Tf 72 700 Td ({}) Tj ET", text);
+    single_page_pdf(content.as_bytes(), catalog_entries)
+}
+
+// Helper to serialize a one-page PDF with the given content stream, Helvetica
+// as font /F1, and extra catalog entries
+fn single_page_pdf(content: &[u8], catalog_entries: lopdf::Dictionary) -> Vec<u8> {
+    use lopdf::{dictionary, Document, Object, Stream};
+    let mut doc = Document::with_version("1.5");
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+    let content_id = doc.add_object(Stream::new(dictionary! {}, content.to_vec()));
+    let pages_id = doc.new_object_id();
+    let page_id = doc.add_object(dictionary! {
+        "Type" => "Page",
+        "Parent" => pages_id,
+        "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        "Contents" => content_id,
+        "Resources" => dictionary! { "Font" => dictionary! { "F1" => font_id } },
+    });
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => vec![page_id.into()],
+            "Count" => 1,
+        }),
+    );
+    let mut catalog = dictionary! { "Type" => "Catalog", "Pages" => pages_id };
+    catalog.extend(&catalog_entries);
+    let catalog_id = doc.add_object(catalog);
+    doc.trailer.set("Root", catalog_id);
+
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+    buffer
+}
+
 // ============================================================================
 // Detection Config Tests
 // ============================================================================
@@ -255,16 +301,30 @@ fn test_markdown_options_default() {
 fn test_markdown_options_custom() {
     let opts = MarkdownOptions {
         detect_headers: false,
+        detect_color_headers: false,
         detect_lists: true,
         detect_code: false,
         base_font_size: Some(14.0),
         remove_page_numbers: false,
+        running_text: pdf_inspector::RunningTextMode::Keep,
+        remove_watermarks: false,
+        remove_hidden_text: true,
         format_urls: false,
         fix_hyphenation: false,
         detect_bold: false,
         detect_italic: false,
         include_images: false,
         include_links: false,
+        internal_links: false,
+        include_annotations: false,
+        page_markers: true,
+        page_labels: Some(vec!["i".to_string()]),
+        image_sources: Default::default(),
+        image_alt_from_captions: false,
+        html_tables: true,
+        table_line_breaks: true,
+        table_detection: pdf_inspector::TableDetectionConfig::disabled(),
+        key_values: pdf_inspector::KeyValueMode::BoldKeys,
     };
     assert!(!opts.detect_headers);
     assert!(opts.detect_lists);
@@ -277,6 +337,8 @@ fn test_markdown_options_custom() {
     assert!(!opts.detect_italic);
     assert!(!opts.include_images);
     assert!(!opts.include_links);
+    assert!(!opts.table_detection.enabled);
+    assert_eq!(opts.key_values, pdf_inspector::KeyValueMode::BoldKeys);
 }
 
 // ============================================================================
@@ -450,6 +512,650 @@ fn test_markdown_from_items_page_breaks() {
     assert!(md.contains("Content on second page"));
 }
 
+#[test]
+fn test_markdown_from_items_page_markers_use_labels() {
+    use pdf_inspector::markdown::to_markdown_from_items;
+
+    let items = vec![
+        make_text_item("Preface text here.", 72.0, 700.0, 12.0, 1),
+        make_text_item("Body text here.", 72.0, 700.0, 12.0, 2),
+        make_text_item("Appendix text here.", 72.0, 700.0, 12.0, 3),
+    ];
+    let options = MarkdownOptions {
+        page_markers: true,
+        page_labels: Some(vec!["i".to_string(), "1".to_string()]),
+        ..Default::default()
+    };
+    let md = to_markdown_from_items(items, options);
+    assert!(md.starts_with("<!-- page: i -->\n\nPreface text here."));
+    assert!(md.contains("\n\n<!-- page: 1 -->\n\nBody text here."));
+    // Pages beyond the supplied labels fall back to the physical number
+    assert!(md.contains("<!-- page: 3 -->\n\nAppendix text here."));
+
+    let md = to_markdown_from_items(
+        vec![make_text_item("Body text here.", 72.0, 700.0, 12.0, 1)],
+        MarkdownOptions::default(),
+    );
+    assert!(!md.contains("<!-- page"));
+}
+
+#[test]
+fn test_markdown_from_items_image_sources() {
+    use pdf_inspector::extractor::ItemType;
+    use pdf_inspector::markdown::to_markdown_from_items;
+    use pdf_inspector::ImageInfo;
+
+    let image = |name: &str, object_id: u32, y: f32| TextItem {
+        text: format!("[Image: {}]", name),
+        x: 72.0,
+        y,
+        width: 200.0,
+        height: 100.0,
+        page: 1,
+        item_type: ItemType::Image,
+        image: Some(ImageInfo {
+            name: name.to_string(),
+            object_id: (object_id, 0),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    // A Form XObject can reuse the page's resource name for another image
+    let items = vec![
+        make_text_item("Text above the figures.", 72.0, 700.0, 12.0, 1),
+        image("Im1", 12, 500.0),
+        image("Im1", 15, 300.0),
+        image("Im2", 18, 100.0),
+    ];
+    let mut options = MarkdownOptions::default();
+    options
+        .image_sources
+        .insert((1, (12, 0)), "images/page1-Im1-12.png".to_string());
+    options
+        .image_sources
+        .insert((1, (15, 0)), "images/page1-Im1-15.png".to_string());
+
+    let md = to_markdown_from_items(items, options);
+    assert!(md.contains("![Image: Im1](images/page1-Im1-12.png)"));
+    assert!(md.contains("![Image: Im1](images/page1-Im1-15.png)"));
+    // Images without an exported source keep the placeholder target
+    assert!(md.contains("![Image: Im2](image)"));
+}
+
+#[test]
+fn test_markdown_from_items_image_caption_alt_text() {
+    use pdf_inspector::extractor::ItemType;
+    use pdf_inspector::markdown::to_markdown_from_items;
+
+    let items = vec![
+        make_text_item("Results are shown below.", 72.0, 700.0, 12.0, 1),
+        TextItem {
+            text: "[Image: Im1]".to_string(),
+            x: 72.0,
+            y: 450.0,
+            width: 300.0,
+            height: 200.0,
+            page: 1,
+            item_type: ItemType::Image,
+            ..Default::default()
+        },
+        make_text_item("Figure 2: Revenue [EUR] by quarter", 72.0, 430.0, 10.0, 1),
+        make_text_item("Figure 3: Not this one", 72.0, 100.0, 10.0, 1),
+    ];
+
+    let options = MarkdownOptions {
+        image_alt_from_captions: true,
+        ..Default::default()
+    };
+    let md = to_markdown_from_items(items.clone(), options);
+    assert!(md.contains("![Figure 2: Revenue \\[EUR\\] by quarter](image)"));
+
+    let md = to_markdown_from_items(items, MarkdownOptions::default());
+    assert!(md.contains("![Image: Im1](image)"));
+}
+
+#[test]
+fn test_markdown_from_items_running_headers_and_footers() {
+    use pdf_inspector::markdown::to_markdown_from_items;
+    use pdf_inspector::RunningTextMode;
+
+    let mut items = Vec::new();
+    for page in 1..=5 {
+        items.push(make_text_item(
+            "Journal of Examples, Vol. 12",
+            72.0,
+            760.0,
+            9.0,
+            page,
+        ));
+        let body = ["Overview", "Methods", "Results", "Discussion", "Appendix"];
+        items.push(make_text_item(
+            &format!("{} section text.", body[page as usize - 1]),
+            72.0,
+            600.0,
+            12.0,
+            page,
+        ));
+        items.push(make_text_item(
+            &format!("Printed {} March 2024 - Page {} of 5", page, page),
+            72.0,
+            40.0,
+            9.0,
+            page,
+        ));
+    }
+
+    let md = to_markdown_from_items(items.clone(), MarkdownOptions::default());
+    assert_eq!(md.matches("Journal of Examples").count(), 5);
+
+    let options = MarkdownOptions {
+        running_text: RunningTextMode::Remove,
+        ..Default::default()
+    };
+    let md = to_markdown_from_items(items.clone(), options);
+    assert!(!md.contains("Journal of Examples"));
+    assert!(!md.contains("Printed"));
+    assert!(md.contains("Appendix section text."));
+
+    let options = MarkdownOptions {
+        running_text: RunningTextMode::Metadata,
+        ..Default::default()
+    };
+    let md = to_markdown_from_items(items, options);
+    assert!(md.starts_with(
+        "<!-- running header: Journal of Examples, Vol. 12 -->\n\
+         <!-- running footer: Printed 1 March 2024 - Page 1 of 5 -->\n"
+    ));
+    assert_eq!(md.matches("Journal of Examples").count(), 1);
+}
+
+#[test]
+fn test_markdown_from_items_watermarks() {
+    use pdf_inspector::markdown::to_markdown_from_items;
+
+    let mut items = Vec::new();
+    for (page, body) in (1..=3).zip(["Overview", "Methods", "Results"]) {
+        items.push(make_text_item(
+            &format!("{} section text.", body),
+            72.0,
+            600.0,
+            12.0,
+            page,
+        ));
+        let mut draft = make_text_item("DRAFT", 150.0, 400.0, 72.0, page);
+        draft.rotation = 45.0;
+        draft.fill_color = [0.85; 3];
+        items.push(draft);
+    }
+
+    let md = to_markdown_from_items(items.clone(), MarkdownOptions::default());
+    assert_eq!(md.matches("DRAFT").count(), 3);
+
+    let options = MarkdownOptions {
+        remove_watermarks: true,
+        ..Default::default()
+    };
+    let md = to_markdown_from_items(items, options);
+    assert!(!md.contains("DRAFT"));
+    assert_eq!(md.matches("section text.").count(), 3);
+}
+
+#[test]
+fn test_markdown_from_items_color_headers() {
+    use pdf_inspector::markdown::to_markdown_from_items;
+
+    let blue = [0.1, 0.2, 0.7];
+    let colored = |text: &str, y: f32| TextItem {
+        fill_color: blue,
+        ..make_text_item(text, 72.0, y, 12.0, 1)
+    };
+    let items = vec![
+        colored("Payment Terms", 700.0),
+        make_text_item(
+            "Invoices are due within thirty days of receipt",
+            72.0,
+            680.0,
+            12.0,
+            1,
+        ),
+        make_text_item(
+            "and are payable by bank transfer only.",
+            72.0,
+            666.0,
+            12.0,
+            1,
+        ),
+        // A colored sentence is emphasis, not a header
+        colored("Late payments incur interest.", 640.0),
+    ];
+
+    let options = MarkdownOptions {
+        detect_color_headers: true,
+        ..Default::default()
+    };
+    let md = to_markdown_from_items(items.clone(), options);
+    assert!(md.contains("# Payment Terms\n"));
+    assert!(!md.contains("# Late payments"));
+
+    let md = to_markdown_from_items(items, MarkdownOptions::default());
+    assert!(!md.contains("# Payment Terms"));
+}
+
+#[test]
+fn test_markdown_from_items_with_graphics_ruled_table() {
+    use pdf_inspector::{to_markdown_from_items_with_graphics, GraphicItem, GraphicKind};
+
+    let rule = |x1: f32, y1: f32, x2: f32, y2: f32| GraphicItem {
+        kind: GraphicKind::Line,
+        x1,
+        y1,
+        x2,
+        y2,
+        page: 1,
+        stroked: true,
+        filled: false,
+        line_width: 0.5,
+        stroke_color: [0.0; 3],
+        fill_color: [0.0; 3],
+    };
+    let mut graphics: Vec<GraphicItem> = [600.0, 580.0, 560.0]
+        .iter()
+        .map(|&y| rule(72.0, y, 372.0, y))
+        .collect();
+    for x in [72.0, 222.0, 372.0] {
+        graphics.push(rule(x, 560.0, x, 600.0));
+    }
+    let items = vec![
+        make_text_item("Quarterly summary", 72.0, 650.0, 12.0, 1),
+        make_text_item("Item", 76.0, 586.0, 12.0, 1),
+        make_text_item("Status", 226.0, 586.0, 12.0, 1),
+        make_text_item("Audit", 76.0, 566.0, 12.0, 1),
+        make_text_item("Complete", 226.0, 566.0, 12.0, 1),
+    ];
+
+    let md =
+        to_markdown_from_items_with_graphics(items.clone(), &graphics, MarkdownOptions::default());
+    assert!(md.contains("| Item"), "{md}");
+    assert!(md.contains("| Audit"), "{md}");
+    assert!(md.find("Quarterly summary") < md.find("| Item"));
+
+    // Tables without merged cells stay markdown even when HTML is allowed
+    let options = MarkdownOptions {
+        html_tables: true,
+        ..Default::default()
+    };
+    let md = to_markdown_from_items_with_graphics(items.clone(), &graphics, options);
+    assert!(md.contains("| Item") && !md.contains("<table>"));
+
+    // Without the rules the same text is left as prose
+    let md = to_markdown_from_items_with_graphics(items, &[], MarkdownOptions::default());
+    assert!(!md.contains('|'));
+}
+
+#[test]
+fn test_markdown_from_items_wrapped_table_cells() {
+    use pdf_inspector::markdown::to_markdown_from_items;
+
+    // Body-font table: the wrapped label must not split it
+    let mut items = vec![make_text_item(
+        "The results for the year are summarised below.",
+        72.0,
+        700.0,
+        9.0,
+        1,
+    )];
+    let rows: [(&[&str], f32); 6] = [
+        (&["Line item", "2023", "2022"], 640.0),
+        (&["Revenue", "1,200", "1,100"], 624.0),
+        (&["Net income attributable to", "300", "250"], 608.0),
+        (&["shareholders", "", ""], 598.0),
+        (&["Dividends", "90", "80"], 582.0),
+        (&["Retained", "210", "170"], 566.0),
+    ];
+    for (cells, y) in rows {
+        for (text, x) in cells.iter().zip([72.0, 250.0, 350.0]) {
+            if !text.is_empty() {
+                items.push(make_text_item(text, x, y, 9.0, 1));
+            }
+        }
+    }
+
+    let md = to_markdown_from_items(items.clone(), MarkdownOptions::default());
+    assert!(
+        md.contains("| Net income attributable to shareholders | 300 "),
+        "{md}"
+    );
+    // Header, separator and four rows
+    assert_eq!(md.lines().filter(|l| l.starts_with('|')).count(), 6, "{md}");
+
+    let options = MarkdownOptions {
+        table_line_breaks: true,
+        ..Default::default()
+    };
+    let md = to_markdown_from_items(items, options);
+    assert!(md.contains("| Net income attributable to<br>shareholders | 300 "));
+}
+
+#[test]
+fn test_markdown_from_items_table_regions() {
+    use pdf_inspector::markdown::to_markdown_from_items;
+    use pdf_inspector::{TableDetectionConfig, TableRegion};
+
+    let items = vec![
+        make_text_item("Invoice details", 72.0, 700.0, 12.0, 1),
+        make_text_item("Invoice", 72.0, 660.0, 12.0, 1),
+        make_text_item("INV-0042", 250.0, 660.0, 12.0, 1),
+        make_text_item("Due", 72.0, 644.0, 12.0, 1),
+        make_text_item("30 days", 250.0, 644.0, 12.0, 1),
+    ];
+    let md = to_markdown_from_items(items.clone(), MarkdownOptions::default());
+    assert!(!md.contains('|'), "{md}");
+
+    // A region from a layout model makes the block a table
+    let options = MarkdownOptions {
+        table_detection: TableDetectionConfig {
+            regions: vec![TableRegion {
+                page: 1,
+                bbox: [70.0, 640.0, 320.0, 675.0],
+            }],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let md = to_markdown_from_items(items, options);
+    assert!(md.contains("| Invoice | INV-0042 |"), "{md}");
+    assert!(md.contains("| Due     | 30 days  |"), "{md}");
+    assert!(md.find("Invoice details") < md.find("| Invoice"));
+}
+
+#[test]
+fn test_tables_from_items_financial_statement() {
+    use pdf_inspector::table_export::tables_from_items;
+    use pdf_inspector::{GraphicItem, GraphicKind, RowKind};
+
+    // Labels indented by level, amounts right-aligned at 300 and 380
+    let rows: [(&str, f32, [&str; 2]); 6] = [
+        ("Income statement", 72.0, ["2023", "2022"]),
+        ("Product revenue", 84.0, ["1,204,500", "998,000"]),
+        ("Services", 84.0, ["56,200", "61,000"]),
+        ("Total revenue", 72.0, ["1,260,700", "1,059,000"]),
+        ("Cost of sales", 72.0, ["(803,000)", "(655,400)"]),
+        ("Gross profit", 72.0, ["457,700", "403,600"]),
+    ];
+    let mut items = Vec::new();
+    for (i, (label, x, amounts)) in rows.iter().enumerate() {
+        let y = 600.0 - 16.0 * i as f32;
+        let mut label = make_text_item(label, *x, y, 10.0, 1);
+        label.is_bold = i == 3 || i == 5;
+        items.push(label);
+        for (text, right) in amounts.iter().zip([300.0, 380.0]) {
+            let width = text.len() as f32 * 5.0;
+            items.push(make_text_item(text, right - width, y, 10.0, 1));
+        }
+    }
+    let graphics = vec![GraphicItem {
+        kind: GraphicKind::Line,
+        x1: 250.0,
+        y1: 533.0,
+        x2: 380.0,
+        y2: 533.0,
+        page: 1,
+        stroked: true,
+        filled: false,
+        line_width: 0.5,
+        stroke_color: [0.0; 3],
+        fill_color: [0.0; 3],
+    }];
+
+    let tables = tables_from_items(items, &graphics, &MarkdownOptions::default());
+    assert_eq!(tables.len(), 1);
+    let table = &tables[0];
+    assert_eq!(table.cols, 3);
+    assert_eq!(table.row_levels, vec![0, 1, 1, 0, 0, 0]);
+    assert_eq!(
+        table.row_kinds,
+        vec![
+            RowKind::Header,
+            RowKind::Data,
+            RowKind::Data,
+            RowKind::Total,
+            RowKind::Data,
+            RowKind::Total,
+        ]
+    );
+    let csv = table.to_csv();
+    assert!(
+        csv.starts_with("level,Income statement,2023,2022\r\n"),
+        "{csv}"
+    );
+    assert!(
+        csv.contains("\r\n1,Services,\"56,200\",\"61,000\"\r\n"),
+        "{csv}"
+    );
+    assert!(table
+        .to_json()
+        .contains(r#""row_kinds":["header","data","data","total","data","total"]"#));
+}
+
+#[test]
+fn test_tables_from_items_typed_cells() {
+    use pdf_inspector::table_export::tables_from_items;
+    use pdf_inspector::{CellValue, GraphicItem, GraphicKind};
+
+    let rule = |x1: f32, y1: f32, x2: f32, y2: f32| GraphicItem {
+        kind: GraphicKind::Line,
+        x1,
+        y1,
+        x2,
+        y2,
+        page: 2,
+        stroked: true,
+        filled: false,
+        line_width: 0.5,
+        stroke_color: [0.0; 3],
+        fill_color: [0.0; 3],
+    };
+    let mut graphics: Vec<GraphicItem> = [600.0, 580.0, 560.0, 540.0]
+        .iter()
+        .map(|&y| rule(72.0, y, 372.0, y))
+        .collect();
+    for x in [72.0, 172.0, 272.0, 372.0] {
+        graphics.push(rule(x, 540.0, x, 600.0));
+    }
+    let items = vec![
+        make_text_item("Date", 76.0, 586.0, 12.0, 2),
+        make_text_item("Amount", 176.0, 586.0, 12.0, 2),
+        make_text_item("Change", 276.0, 586.0, 12.0, 2),
+        make_text_item("2023-12-31", 76.0, 566.0, 12.0, 2),
+        make_text_item("$1,250.00", 176.0, 566.0, 12.0, 2),
+        make_text_item("4.5%", 276.0, 566.0, 12.0, 2),
+        make_text_item("2022-12-31", 76.0, 546.0, 12.0, 2),
+        make_text_item("(1,100)", 176.0, 546.0, 12.0, 2),
+    ];
+
+    let tables = tables_from_items(items, &graphics, &MarkdownOptions::default());
+    assert_eq!(tables.len(), 1);
+    let table = &tables[0];
+    assert_eq!((table.page, table.rows, table.cols), (2, 3, 3));
+    assert_eq!(table.header_rows, 1);
+    assert_eq!(table.bbox, [72.0, 540.0, 372.0, 600.0]);
+
+    let value = |row: usize, col: usize| {
+        let cell = table.cells.iter().find(|c| (c.row, c.col) == (row, col));
+        cell.map(|c| c.value.clone())
+    };
+    assert_eq!(
+        value(1, 0),
+        Some(CellValue::Date {
+            year: 2023,
+            month: 12,
+            day: 31
+        })
+    );
+    assert_eq!(
+        value(1, 1),
+        Some(CellValue::Currency {
+            amount: 1250.0,
+            currency: "$".to_string()
+        })
+    );
+    assert_eq!(value(1, 2), Some(CellValue::Percent(4.5)));
+    assert_eq!(value(2, 1), Some(CellValue::Number(-1100.0)));
+    assert_eq!(value(2, 2), Some(CellValue::Empty));
+    assert_eq!(
+        table.cells[4].bbox,
+        Some([172.0, 560.0, 272.0, 580.0]),
+        "ruled cells are bounded by their rules"
+    );
+
+    assert_eq!(
+        table.to_csv(),
+        "Date,Amount,Change\r\n2023-12-31,\"$1,250.00\",4.5%\r\n2022-12-31,\"(1,100)\",\r\n"
+    );
+}
+
+#[test]
+fn test_tables_stitched_across_pages() {
+    use pdf_inspector::markdown::to_markdown_from_items;
+    use pdf_inspector::table_export::tables_from_items;
+
+    // A transaction listing filling the bottom of page 1 and the top of
+    // page 2, repeating its header, with page numbers in the footer
+    let mut items = vec![
+        make_text_item("Account activity for the period", 72.0, 740.0, 12.0, 1),
+        make_text_item("1", 300.0, 30.0, 10.0, 1),
+        make_text_item("2", 300.0, 30.0, 10.0, 2),
+    ];
+    let rows = [
+        ("01/02", "Coffee", "3.50"),
+        ("01/03", "Groceries", "54.20"),
+        ("01/05", "Fuel", "40.00"),
+        ("01/08", "Rent", "1,200.00"),
+        ("01/09", "Books", "18.75"),
+        ("01/12", "Pharmacy", "9.10"),
+    ];
+    for (page, rows, top) in [(1, &rows[..3], 140.0), (2, &rows[3..], 760.0)] {
+        let mut push_row = |cells: [&str; 3], y: f32| {
+            for (text, x) in cells.iter().zip([72.0, 150.0, 400.0]) {
+                items.push(make_text_item(text, x, y, 8.0, page));
+            }
+        };
+        push_row(["Date", "Description", "Amount"], top);
+        for (i, &(date, description, amount)) in rows.iter().enumerate() {
+            push_row([date, description, amount], top - 12.0 * (i + 1) as f32);
+        }
+    }
+    items.push(make_text_item(
+        "Closing balance follows.",
+        72.0,
+        680.0,
+        12.0,
+        2,
+    ));
+
+    let md = to_markdown_from_items(items.clone(), MarkdownOptions::default());
+    assert_eq!(md.matches("| Date").count(), 1, "{md}");
+    let separators = md.lines().filter(|l| l.starts_with("| ---")).count();
+    assert_eq!(separators, 1, "{md}");
+    assert!(md.find("| 01/05") < md.find("| 01/08"), "{md}");
+    assert!(md.find("| 01/12") < md.find("Closing balance"), "{md}");
+
+    let tables = tables_from_items(items.clone(), &[], &MarkdownOptions::default());
+    assert_eq!(tables.len(), 1);
+    assert_eq!((tables[0].page, tables[0].rows), (1, 7));
+    assert_eq!(tables[0].pages, vec![1, 2]);
+    let rent = tables[0].cells.iter().find(|c| c.text == "Rent").unwrap();
+    assert_eq!((rent.row, rent.page), (4, 2));
+
+    // Text between the parts ends the table
+    items.push(make_text_item("Pending transactions", 72.0, 790.0, 12.0, 2));
+    let tables = tables_from_items(items, &[], &MarkdownOptions::default());
+    assert_eq!(tables.len(), 2);
+}
+
+#[test]
+fn test_markdown_from_items_key_values() {
+    use pdf_inspector::key_values::key_values_from_items;
+    use pdf_inspector::markdown::to_markdown_from_items;
+    use pdf_inspector::{KeyValueLayout, KeyValueMode};
+
+    let items = vec![
+        make_text_item("Invoice", 72.0, 740.0, 18.0, 1),
+        make_text_item("Invoice No: INV-0042", 72.0, 700.0, 10.0, 1),
+        make_text_item("Issued: 2024-03-01", 72.0, 686.0, 10.0, 1),
+        make_text_item("Customer", 72.0, 672.0, 10.0, 1),
+        make_text_item("Acme Corporation", 200.0, 672.0, 10.0, 1),
+        make_text_item("Thank you for your business.", 72.0, 620.0, 10.0, 1),
+    ];
+
+    let pairs = key_values_from_items(items.clone(), &[], &MarkdownOptions::default());
+    let found: Vec<(&str, &str, KeyValueLayout)> = pairs
+        .iter()
+        .map(|kv| (kv.key.as_str(), kv.value.as_str(), kv.layout))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("Invoice No", "INV-0042", KeyValueLayout::Colon),
+            ("Issued", "2024-03-01", KeyValueLayout::Colon),
+            ("Customer", "Acme Corporation", KeyValueLayout::LeftOf),
+        ]
+    );
+    assert!(pairs.iter().all(|kv| kv.page == 1));
+
+    // Left in the paragraph by default
+    let md = to_markdown_from_items(items.clone(), MarkdownOptions::default());
+    assert!(md.contains("Invoice No: INV-0042"), "{md}");
+
+    let render = |mode| {
+        let options = MarkdownOptions {
+            key_values: mode,
+            ..Default::default()
+        };
+        to_markdown_from_items(items.clone(), options)
+    };
+    let md = render(KeyValueMode::BoldKeys);
+    assert!(
+        md.contains("**Invoice No:** INV-0042  \n**Issued:** 2024-03-01  \n**Customer:** Acme Corporation\n"),
+        "{md}"
+    );
+    assert!(md.find("# Invoice") < md.find("**Invoice No:**"), "{md}");
+    assert!(md.find("Acme Corporation") < md.find("Thank you"), "{md}");
+    let md = render(KeyValueMode::DefinitionList);
+    assert!(
+        md.contains("Invoice No\n: INV-0042\n\nIssued\n: 2024-03-01\n"),
+        "{md}"
+    );
+}
+
+#[test]
+fn test_markdown_from_items_review_annotations() {
+    use pdf_inspector::extractor::{AnnotationKind, Comment};
+    use pdf_inspector::markdown::to_markdown_from_items;
+    let mut deleted = make_text_item("old wording", 160.0, 700.0, 12.0, 1);
+    deleted.markup = Some(AnnotationKind::StrikeOut);
+    let mut highlighted = make_text_item("key term", 100.0, 700.0, 12.0, 1);
+    highlighted.markup = Some(AnnotationKind::Highlight);
+    highlighted.comments.push(Comment {
+        id: 1,
+        author: Some("Alice".to_string()),
+        text: "Define this".to_string(),
+    });
+    let items = vec![highlighted, deleted];
+
+    let md = to_markdown_from_items(items.clone(), MarkdownOptions::default());
+    assert!(!md.contains("=="));
+    assert!(!md.contains("[^1]"));
+
+    let options = MarkdownOptions {
+        include_annotations: true,
+        ..MarkdownOptions::default()
+    };
+    let md = to_markdown_from_items(items, options);
+    assert!(md.contains("==key term==[^1] ~~old wording~~"));
+    assert!(md.contains("[^1]: **Alice:** Define this"));
+}
+
 // ============================================================================
 // Markdown From Lines Tests
 // ============================================================================
@@ -730,3 +1436,182 @@ fn test_trailing_newline() {
     assert!(md.ends_with('\n'));
     assert!(!md.ends_with("\n\n"));
 }
+
+// ============================================================================
+// Page Label Tests
+// ============================================================================
+
+#[test]
+fn test_process_pdf_reads_page_labels_when_needed() {
+    use lopdf::{dictionary, Object};
+    use pdf_inspector::{process_pdf_mem, process_pdf_mem_with_options, ProcessOptions};
+
+    // Roman numerals with a PDFDocEncoding prefix ("é" is 0xE9)
+    let buffer = text_pdf(
+        "Preface text",
+        dictionary! {
+            "PageLabels" => dictionary! {
+                "Nums" => vec![
+                    0.into(),
+                    dictionary! {
+                        "S" => "r",
+                        "P" => Object::String(b"Pr\xE9f-".to_vec(), lopdf::StringFormat::Literal),
+                    }
+                    .into(),
+                ],
+            },
+        },
+    );
+
+    let plain = process_pdf_mem(&buffer).unwrap();
+    assert!(plain.page_labels.is_empty());
+
+    let mut options = ProcessOptions {
+        page_labels: true,
+        ..Default::default()
+    };
+    let result = process_pdf_mem_with_options(&buffer, &options).unwrap();
+    assert_eq!(result.page_labels, vec!["Préf-i"]);
+    assert!(!result.markdown.unwrap().contains("<!-- page:"));
+
+    options.page_labels = false;
+    options.markdown.page_markers = true;
+    let result = process_pdf_mem_with_options(&buffer, &options).unwrap();
+    assert_eq!(result.page_labels, vec!["Préf-i"]);
+    let markdown = result.markdown.expect("PDF has text");
+    assert!(markdown.contains("<!-- page: Préf-i -->"), "{}", markdown);
+    assert!(markdown.contains("Preface text"));
+
+    // Positioned text carries the label next to the physical page number
+    let items = pdf_inspector::extractor::extract_text_with_positions_mem(&buffer).unwrap();
+    assert_eq!(items[0].page, 1);
+    assert_eq!(items[0].page_label.as_deref(), Some("Préf-i"));
+}
+
+// ============================================================================
+// Attachment Tests
+// ============================================================================
+
+// Helper to attach `data` to the serialized PDF `outer` as an embedded PDF
+// named `name`
+fn attach_pdf(outer: &[u8], name: &str, data: Vec<u8>) -> Vec<u8> {
+    use lopdf::{dictionary, Document, Object, Stream};
+    let mut doc = Document::load_mem(outer).unwrap();
+    let file_id = doc.add_object(Stream::new(
+        dictionary! { "Type" => "EmbeddedFile", "Subtype" => "application/pdf" },
+        data,
+    ));
+    doc.catalog_mut().unwrap().set(
+        "Names",
+        dictionary! {
+            "EmbeddedFiles" => dictionary! {
+                "Names" => vec![
+                    Object::string_literal(name),
+                    dictionary! {
+                        "Type" => "Filespec",
+                        "F" => Object::string_literal(name),
+                        "EF" => dictionary! { "F" => file_id },
+                    }
+                    .into(),
+                ],
+            },
+        },
+    );
+    let mut buffer = Vec::new();
+    doc.save_to(&mut buffer).unwrap();
+    buffer
+}
+
+#[test]
+fn test_process_pdf_includes_attached_pdfs() {
+    use pdf_inspector::{process_pdf_mem, process_pdf_mem_with_options, ProcessOptions};
+
+    let nested = text_pdf("Attached report body", lopdf::dictionary! {});
+    let cover = text_pdf("Portfolio cover page", lopdf::dictionary! {});
+    let buffer = attach_pdf(&cover, "report.pdf", nested);
+
+    let plain = process_pdf_mem(&buffer).unwrap();
+    let markdown = plain.markdown.expect("outer PDF has text");
+    assert!(markdown.contains("Portfolio cover page"));
+    assert!(!markdown.contains("Attached report body"));
+
+    let options = ProcessOptions {
+        include_attachments: true,
+        ..Default::default()
+    };
+    let result = process_pdf_mem_with_options(&buffer, &options).unwrap();
+    let markdown = result.markdown.expect("outer PDF has text");
+    let section = markdown
+        .find("## Attachment: report.pdf")
+        .expect("attachment section");
+    assert!(markdown.find("Portfolio cover page").unwrap() < section);
+    assert!(markdown[section..].contains("Attached report body"));
+}
+
+#[test]
+fn test_scanned_pdf_includes_attached_pdfs() {
+    use pdf_inspector::{process_pdf_mem_with_options, ProcessOptions};
+
+    // A cover page without text, as in portfolios with an image-only cover
+    let nested = text_pdf("Attached report body", lopdf::dictionary! {});
+    let cover = single_page_pdf(b"", lopdf::dictionary! {});
+    let buffer = attach_pdf(&cover, "report.pdf", nested);
+
+    let options = ProcessOptions {
+        include_attachments: true,
+        ..Default::default()
+    };
+    let result = process_pdf_mem_with_options(&buffer, &options).unwrap();
+    assert_eq!(result.pdf_type, PdfType::Scanned);
+    let markdown = result.markdown.expect("attachment section");
+    assert!(markdown.starts_with("## Attachment: report.pdf"));
+    assert!(markdown.contains("Attached report body"));
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), &buffer).unwrap();
+    let result = pdf_inspector::process_pdf_with_options(file.path(), &options).unwrap();
+    assert!(result.markdown.unwrap().contains("Attached report body"));
+}
+
+// ============================================================================
+// Table Export Tests
+// ============================================================================
+
+#[test]
+fn test_process_pdf_returns_the_markdown_tables() {
+    use pdf_inspector::{process_pdf_mem_with_options, CellValue, ProcessOptions};
+
+    let mut content = String::from("BT /F1 12 Tf 72 740 Td (Quarterly results) Tj ET\n");
+    let rows = [
+        ["Quarter", "Revenue", "Costs"],
+        ["Q1", "1,200", "800"],
+        ["Q2", "1,350", "820"],
+        ["Q3", "1,500", "900"],
+    ];
+    for (i, row) in rows.iter().enumerate() {
+        let y = 700 - 20 * i;
+        for (text, x) in row.iter().zip([72, 200, 328]) {
+            content.push_str(&format!("BT /F1 10 Tf {} {} Td ({}) Tj ET\n", x, y, text));
+        }
+    }
+    let buffer = single_page_pdf(content.as_bytes(), lopdf::dictionary! {});
+
+    let options = ProcessOptions {
+        tables: true,
+        ..Default::default()
+    };
+    let result = process_pdf_mem_with_options(&buffer, &options).unwrap();
+    let markdown = result.markdown.unwrap();
+    assert_eq!(result.tables.len(), 1);
+    assert!(markdown.contains("| Q3      | 1,500   | 900   |"));
+    let table = &result.tables[0];
+    let q3_costs = table
+        .cells
+        .iter()
+        .find(|cell| cell.text == "900")
+        .map(|cell| &cell.value);
+    assert_eq!(q3_costs, Some(&CellValue::Number(900.0)));
+
+    let result = process_pdf_mem_with_options(&buffer, &ProcessOptions::default()).unwrap();
+    assert!(result.tables.is_empty());
+}